@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+use wgpu_common::{GoldenOutcome, Metric, Registry, Target, Tolerance};
+
+/// Goldens are always rendered at the same size so they don't depend on the window a screenshot was taken from.
+pub const WIDTH: u32 = 800;
+pub const HEIGHT: u32 = 600;
+/// Render a couple of frames so anything set up in `update` has made it to the screen.
+pub const FRAMES: u32 = 2;
+
+#[derive(Clone, Debug, Default)]
+pub struct GoldenOptions {
+    /// Overwrite the goldens with what is rendered now instead of comparing against them.
+    pub bless: bool,
+    pub tolerance: Tolerance,
+    /// Only check these exercises. Empty checks all of them.
+    pub names: Vec<String>,
+}
+
+impl GoldenOptions {
+    /// Parses `[--bless] [--perceptual] [--tolerance N] [--max-mismatched PERCENT] [name...]`.
+    pub fn parse(args: &[String]) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bless" => options.bless = true,
+                "--perceptual" => options.tolerance.metric = Metric::Perceptual,
+                "--tolerance" => {
+                    let value = args.next().ok_or_else(|| anyhow::anyhow!("--tolerance is missing its value"))?;
+                    options.tolerance.threshold = value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("--tolerance expects a number between 0 and 255, got '{}'", value))?;
+                },
+                "--max-mismatched" => {
+                    let value = args.next().ok_or_else(|| anyhow::anyhow!("--max-mismatched is missing its value"))?;
+                    let percent: f32 = value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("--max-mismatched expects a percentage, got '{}'", value))?;
+                    options.tolerance.max_mismatched = percent / 100.0;
+                },
+                flag if flag.starts_with("--") => anyhow::bail!("unknown golden option '{}'", flag),
+                name => options.names.push(name.to_string()),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Where the golden images live: `exercises/golden/<name>.png`.
+pub fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+pub fn golden_path(name: &str) -> PathBuf {
+    golden_dir().join(format!("{}.png", name))
+}
+
+/// Renders every selected exercise headless and checks it against its golden, printing one line per exercise.
+/// Returns the number of exercises that failed.
+pub fn check_all(registry: &Registry, options: &GoldenOptions) -> anyhow::Result<usize> {
+    for name in &options.names {
+        anyhow::ensure!(registry.position(name).is_some(), "unknown exercise '{}'", name);
+    }
+
+    let mut failures = 0;
+
+    for entry in registry.entries() {
+        if !options.names.is_empty() && !options.names.iter().any(|name| name == entry.name) {
+            continue;
+        }
+
+        let image = match wgpu_common::render_headless(entry, Target::headless(WIDTH, HEIGHT), FRAMES) {
+            Ok(Some(image)) => image,
+            Ok(None) => {
+                println!("  skipped  {:<20} - nothing to capture", entry.name);
+                continue;
+            },
+            Err(e) => {
+                println!("  FAILED   {:<20} - unable to render: {:#}", entry.name, e);
+                failures += 1;
+                continue;
+            }
+        };
+
+        match wgpu_common::check_golden(&image, &golden_path(entry.name), &options.tolerance, options.bless)? {
+            GoldenOutcome::Passed(comparison) => {
+                println!(
+                    "  ok       {:<20} - max difference {:.1}, {:.3}% mismatched",
+                    entry.name,
+                    comparison.max_difference,
+                    comparison.mismatched_fraction() * 100.0
+                );
+            },
+            GoldenOutcome::Blessed => {
+                println!("  blessed  {:<20} - {}", entry.name, golden_path(entry.name).display());
+            },
+            GoldenOutcome::Failed { comparison, actual_path, diff_path } => {
+                println!(
+                    "  FAILED   {:<20} - {:.3}% mismatched (max difference {:.1}), see {} and {}",
+                    entry.name,
+                    comparison.mismatched_fraction() * 100.0,
+                    comparison.max_difference,
+                    actual_path.display(),
+                    diff_path.display()
+                );
+                failures += 1;
+            },
+            GoldenOutcome::Missing => {
+                println!("  FAILED   {:<20} - no golden yet, run with --bless to create it", entry.name);
+                failures += 1;
+            },
+        }
+    }
+
+    Ok(failures)
+}
@@ -0,0 +1,48 @@
+use wgpu_common::Registry;
+
+/// Checks the vertex layouts of every selected exercise against its shader's `@location` inputs, printing one line
+/// per exercise and one per problem. Exercises without vertex inputs to check are skipped. Returns the number of
+/// exercises with errors; unused attributes and component count differences are only warnings.
+pub fn check_all(registry: &Registry, names: &[String]) -> anyhow::Result<usize> {
+    for name in names {
+        anyhow::ensure!(registry.position(name).is_some(), "unknown exercise '{}'", name);
+    }
+
+    let mut failures = 0;
+
+    for entry in registry.entries() {
+        if !names.is_empty() && !names.iter().any(|name| name == entry.name) {
+            continue;
+        }
+
+        let layouts = match entry.shader_layouts() {
+            Some(layouts) => layouts,
+            None => {
+                println!("  skipped  {:<20} - no shader to check", entry.name);
+                continue;
+            },
+        };
+
+        let problems = match layouts.check() {
+            Ok(problems) => problems,
+            Err(e) => {
+                println!("  FAILED   {:<20} - {:#}", entry.name, e);
+                failures += 1;
+                continue;
+            },
+        };
+
+        if problems.iter().any(|problem| problem.is_error()) {
+            println!("  FAILED   {:<20} - {}", entry.name, layouts.path);
+            failures += 1;
+        } else {
+            println!("  ok       {:<20} - {}", entry.name, layouts.path);
+        }
+        for problem in &problems {
+            let severity = if problem.is_error() { "error" } else { "warning" };
+            println!("             {}: {}", severity, problem);
+        }
+    }
+
+    Ok(failures)
+}
@@ -0,0 +1,28 @@
+//! The registry of every exercise in the workspace, shared by the runner binary and the golden image and layout tests.
+
+pub mod golden;
+pub mod layouts;
+
+use wgpu_common::Registry;
+
+/// Every exercise in the workspace, in the order `[` and `]` cycle through them at runtime.
+pub fn registry() -> Registry {
+    let mut registry = Registry::default();
+
+    exercise1_dependencies_and_the_window::register(&mut registry);
+    exercise2_the_surface::register(&mut registry);
+    exercise3_the_pipeline::register(&mut registry);
+    exercise4a_vertex_buffers::register(&mut registry);
+    exercise4b_index_buffers::register(&mut registry);
+    exercise5_textures_and_bind_groups::register(&mut registry);
+    exercise6_uniform_buffers_and_a_3d_camera::register(&mut registry);
+    exercise7_instancing::register(&mut registry);
+
+    web_gpu_exercise2::register(&mut registry);
+    web_gpu_exercise3::register(&mut registry);
+    web_gpu_exercise4::register(&mut registry);
+    web_gpu_exercise5::register(&mut registry);
+    web_gpu_exercise6::register(&mut registry);
+
+    registry
+}
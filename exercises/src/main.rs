@@ -0,0 +1,132 @@
+use std::env;
+use exercises::golden::{self, GoldenOptions};
+use exercises::{layouts, registry};
+use wgpu_common::{Registry, RunOptions, Target};
+
+const BENCH_FRAMES: &str = "300";
+
+pub fn main() {
+    env_logger::init();
+
+    if let Err(e) = run() {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let registry = registry();
+
+    match args.get(1).map(String::as_str) {
+        Some("list") => {
+            list(&registry);
+            Ok(())
+        },
+        Some("run") => match args.get(2) {
+            Some(name) => wgpu_common::run(registry, Some(name), RunOptions::parse(args[3..].iter().cloned())?),
+            None => {
+                eprintln!("Missing exercise name");
+                print_help();
+                Ok(())
+            }
+        },
+        Some("bench") => match args.get(2) {
+            Some(name) => bench(&registry, name, &args[3..]),
+            None => {
+                eprintln!("Missing exercise name");
+                print_help();
+                Ok(())
+            }
+        },
+        Some("golden") => {
+            let options = GoldenOptions::parse(&args[2..])?;
+            let failures = golden::check_all(&registry, &options)?;
+            anyhow::ensure!(failures == 0, "{} exercise(s) do not match their golden image", failures);
+            Ok(())
+        },
+        Some("check-layouts") => {
+            let failures = layouts::check_all(&registry, &args[2..])?;
+            anyhow::ensure!(failures == 0, "{} exercise(s) have vertex layouts that don't match their shader", failures);
+            Ok(())
+        },
+        None | Some("help") => {
+            print_help();
+            Ok(())
+        },
+        Some(command) => {
+            eprintln!("Unknown command: '{}'", command);
+            print_help();
+            Ok(())
+        }
+    }
+}
+
+fn list(registry: &Registry) {
+    println!("Registered exercises:");
+
+    for entry in registry.entries() {
+        println!("  {:<20} - {}", entry.name, entry.description);
+    }
+}
+
+/// Renders frames of an exercise headless twice, reusing its render pipelines and building them again every frame,
+/// and prints how long `render` took each way.
+fn bench(registry: &Registry, name: &str, args: &[String]) -> anyhow::Result<()> {
+    let index = registry
+        .position(name)
+        .ok_or_else(|| anyhow::anyhow!("unknown exercise '{}'", name))?;
+    let entry = &registry.entries()[index];
+
+    // Later flags win, so the defaults below can be overridden from the command line.
+    let defaults = ["--headless", "--frames", BENCH_FRAMES];
+    let options = RunOptions::parse(defaults.iter().map(|s| s.to_string()).chain(args.iter().cloned()))?;
+    let headless = options.headless.expect("bench always renders headless");
+
+    let target = Target::headless(headless.width, headless.height).with_adapter(options.adapter);
+    let cached = wgpu_common::render_headless_timed(entry, target.clone(), headless.frames)?;
+    let rebuilt = wgpu_common::render_headless_timed(entry, target.with_rebuild_pipelines(true), headless.frames)?;
+
+    match &cached.adapter {
+        Some(adapter) => println!("{} on {} ({:?}):", entry.name, adapter.name, adapter.backend),
+        None => println!("{}:", entry.name),
+    }
+    println!("  cached pipelines:            {}", cached.stats);
+    println!("  pipelines rebuilt per frame: {}", rebuilt.stats);
+    Ok(())
+}
+
+fn print_help() {
+    println!("Usage: cargo run -p exercises -- <command>");
+    println!("Supported commands:");
+    println!("  list         - List the registered exercises");
+    println!("  run <name>   - Run an exercise by name");
+    println!("  bench <name> - Render an exercise headless with and without its pipeline cache and report frame times");
+    println!("  golden       - Render every exercise headless and compare it to its golden image");
+    println!("  check-layouts [name...] - Check the Rust vertex layouts against the @location inputs of each shader");
+    println!("  help         - Show this help message");
+    println!();
+    println!("Options for run:");
+    println!("  --headless         - Render offscreen with the fallback adapter instead of opening a window");
+    println!("  --frames <n>       - Number of frames to render before saving (headless only, default 1)");
+    println!("  --out <file.png>   - Where to save the last frame (headless only, default frame.png)");
+    println!("  --size <WxH>       - Size of the offscreen frame (headless only, default 800x600)");
+    println!("  --hot-reload       - Reload shader.wgsl from disk when it is saved (also SHADER_HOT_RELOAD=1)");
+    println!("  --backend <list>   - Backends to use, e.g. vulkan,gl (or software for the fallback adapter)");
+    println!("  --power <pref>     - Power preference: low, high or none");
+    println!("  --adapter <name>   - Adapter name (case insensitive substring) or index");
+    println!("  --fallback-adapter - Force the fallback (software) adapter");
+    println!("The adapter can also be picked through WGPU_BACKEND, WGPU_POWER_PREF, WGPU_ADAPTER_NAME, WGPU_ADAPTER_INDEX");
+    println!("and WGPU_FORCE_FALLBACK_ADAPTER. Flags win over the environment.");
+    println!();
+    println!("bench takes the same options as run (--headless is implied, --frames defaults to {}).", BENCH_FRAMES);
+    println!();
+    println!("Options for golden:");
+    println!("  --bless                  - Regenerate the golden images instead of comparing against them");
+    println!("  --tolerance <n>          - Per-pixel difference (0-255) before a pixel counts as mismatched (default 2)");
+    println!("  --perceptual             - Compare perceptual color distance instead of raw channels");
+    println!("  --max-mismatched <pct>   - Percentage of mismatched pixels that is still accepted (default 0.1)");
+    println!("  <name>...                - Only check these exercises");
+    println!();
+    println!("While an exercise is running, press ] or [ to switch to the next/previous exercise.");
+}
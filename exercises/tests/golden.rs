@@ -0,0 +1,29 @@
+use std::env;
+use exercises::golden::{self, GoldenOptions};
+
+// Renders every exercise with the fallback adapter and compares it against `golden/<name>.png`.
+// Run with BLESS=1 to regenerate the goldens after an intentional change.
+#[test]
+fn exercises_match_their_golden_images() {
+    if !wgpu_common::fallback_adapter_available() {
+        eprintln!("skipping golden image tests: no fallback adapter is available");
+        return;
+    }
+
+    let options = GoldenOptions {
+        bless: env::var_os("BLESS").is_some(),
+        ..Default::default()
+    };
+
+    let failures = golden::check_all(&exercises::registry(), &options).unwrap();
+    assert_eq!(failures, 0, "{} exercise(s) do not match their golden image, see the diff images in golden/", failures);
+}
+
+#[test]
+fn max_mismatched_is_given_in_percent() {
+    let args: Vec<String> = ["--perceptual", "--max-mismatched", "1.5", "triangle-list"].iter().map(|s| s.to_string()).collect();
+    let options = GoldenOptions::parse(&args).unwrap();
+    assert_eq!(options.tolerance.max_mismatched, 0.015);
+    assert_eq!(options.tolerance.metric, wgpu_common::Metric::Perceptual);
+    assert_eq!(options.names, vec!["triangle-list".to_string()]);
+}
@@ -0,0 +1,24 @@
+use exercises::registry;
+
+// Compares every exercise's Rust vertex layouts with the `@location` inputs of its vertex shader. No GPU needed. Like
+// `check-layouts`, only errors fail.
+#[test]
+fn vertex_layouts_match_the_shaders() {
+    let registry = registry();
+    let mut checked = 0;
+
+    for entry in registry.entries() {
+        let layouts = match entry.shader_layouts() {
+            Some(layouts) => layouts,
+            None => continue,
+        };
+
+        let problems = layouts.check().unwrap();
+        let errors = problems.iter().filter(|problem| problem.is_error());
+        let messages: Vec<String> = errors.map(ToString::to_string).collect();
+        assert!(messages.is_empty(), "{} ({}):\n  {}", entry.name, layouts.path, messages.join("\n  "));
+        checked += 1;
+    }
+
+    assert!(checked > 0, "no exercise registered its shader layouts");
+}
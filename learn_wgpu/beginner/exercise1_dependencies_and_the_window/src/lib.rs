@@ -0,0 +1,9 @@
+mod state;
+
+pub use crate::state::State;
+
+use wgpu_common::Registry;
+
+pub fn register(registry: &mut Registry) {
+    registry.register::<State>("window", "Learn WGPU - Dependencies and the Window");
+}
@@ -1,24 +1,35 @@
 use std::sync::Arc;
 use winit::window::Window;
+use wgpu_common::{Exercise, Target};
 
 pub struct State {
-    window: Arc<Window>,
+    // This exercise only opens a window, so there is nothing to hold on to when running headless.
+    window: Option<Arc<Window>>,
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
+    pub async fn new(target: Target) -> anyhow::Result<Self> {
         Ok(Self {
-            window,
+            window: target.window().cloned(),
         })
     }
+}
+
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target))
+    }
 
-    pub fn resize(&mut self, _width: u32, _height: u32) {
+    fn resize(&mut self, _width: u32, _height: u32) {
         
     }
 
-    pub fn render(&mut self) {
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         // Ask the window to redraw the frame as soon as possible, as winit only draws one frame unless the window is 
         // resized or we request it to draw another one.
-        self.window.request_redraw();
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,11 @@
+mod state;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::Registry;
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register::<State>("pipeline", "Learn WGPU - The Pipeline")
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
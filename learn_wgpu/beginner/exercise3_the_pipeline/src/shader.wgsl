@@ -1,7 +1,12 @@
+// barycentric() and wireframe_coverage() for fs_wireframe, shared by every exercise with a wireframe toggle, see
+// wgpu_common/shaders/wireframe.wgsl. The preprocessor in wgpu_common pastes them in.
+#include "wireframe.wgsl"
+
 // struct to store the output of the vertex shader.
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,    // the @builtin(position) bit tells WGPU that this is the value we want to use as the vertex's clip coordinates
     @location(0) vert_pos: vec3<f32>,               // the @builtin(position) in the fragment shader is the framebuffer space. This means that if your window is 800x600, the x and y of clip_position would be between 0-800 and 0-600, respectively, with the y = 0 being the top of the screen.
+    @location(1) barycentric: vec3<f32>,            // used by fs_wireframe to find the triangle's edges
 };
 
 @vertex                                             // @vertex - marks this function as a valid entry point for a vertex shader
@@ -15,6 +20,7 @@ fn vs_main(
     let y = f32(i32(in_vertex_index & 1u) * 2 - 1) * 0.5;
     out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
     out.vert_pos = out.clip_position.xyz;
+    out.barycentric = barycentric(in_vertex_index);
     return out;
 }
 
@@ -24,3 +30,8 @@ fn fs_main(
 ) -> @location(0) vec4<f32> {                       // The @location(0) bit tells WGPU to store the vec4 value returned by this function in the first color target.
     return vec4<f32>(0.3, 0.3, 0.1, 1.0);           // Brown
 }
+
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, wireframe_coverage(in.barycentric));
+}
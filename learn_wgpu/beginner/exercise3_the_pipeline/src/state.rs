@@ -1,52 +1,31 @@
-use std::sync::Arc;
-use winit::event_loop::ActiveEventLoop;
-use winit::keyboard::KeyCode;
-use winit::window::Window;
+use winit::event::WindowEvent;
+use wgpu_common::{Exercise, GpuContext, PipelineStyle, Preprocessor, Shader, ShaderLayouts, Target, Wireframe};
 
 pub struct State {
-    pub window: Arc<Window>,
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    is_surface_configured: bool,
+    context: GpuContext,
     render_pipeline: wgpu::RenderPipeline,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    shader: Shader,
+    wireframe_pipeline: wgpu::RenderPipeline,
+    wireframe: Wireframe,
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
-        let size = window.inner_size();
-        let instance_descriptor = wgpu::InstanceDescriptor::default();
-        let instance = wgpu::Instance::new(&instance_descriptor);
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let request_adapter_options = wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::default(),
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        };
-        let adapter = instance.request_adapter(&request_adapter_options).await?;
-        let device_descriptor = wgpu::DeviceDescriptor {
-            label: None,
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::default(),
-            memory_hints: Default::default(),
-            trace: wgpu::Trace::Off,
-        };
-        let (device, queue) = adapter.request_device(&device_descriptor).await?;
-        let surface_capabilities = surface.get_capabilities(&adapter);
-        let surface_format = surface_capabilities.formats[0];
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: surface_capabilities.present_modes[0],
-            alpha_mode: surface_capabilities.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+    pub async fn new(target: Target) -> anyhow::Result<Self> {
+        let hot_reload = target.hot_reload;
+        let context = GpuContext::builder()
+            .optional_features(Wireframe::FEATURES)     // PolygonMode::Line for the wireframe when the adapter has it
+            .build(target)
+            .await?;
+        let wireframe = Wireframe::new(&context.device);
+
+        // shader.wgsl #includes shared snippets, so it goes through the preprocessor instead of include_wgsl!.
+        let shader = Shader::preprocessed(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+            include_str!("shader.wgsl"),
+            Preprocessor::default(),
+            hot_reload,
+        )?;
 
         let pipeline_layout_descriptor = wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
@@ -54,95 +33,64 @@ impl State {
             push_constant_ranges: &[],
         };
 
-        let render_pipeline_layout = device.create_pipeline_layout(&pipeline_layout_descriptor);
+        let render_pipeline_layout = context.device.create_pipeline_layout(&pipeline_layout_descriptor);
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),                                   // @vertex decorated function in shader.wgsl
-                buffers: &[],                                                   // tells wgpu what type of vertices we want to pass to the vertex shader. We're specifying the vertices in the vertex shader itself, so we'll leave this empty.
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),                                   // @fragment decorated function in shader.wgsl
-                targets: &[Some(wgpu::ColorTargetState {                        // tells wgpu what color outputs it should set up. Currently, we only need one for the surface. 
-                    format: config.format,                                      // we use the surface's format so that copying to it is easy.
-                    blend: Some(wgpu::BlendState::REPLACE),                     // specify that the blending should just replace old pixel data with new data
-                    write_mask: wgpu::ColorWrites::ALL,                         // we also tell wgpu to write to all colors: red, blue, green, and alpha.
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {                                   // describes how to interpret our vertices when converting them into triangles.
-                topology: wgpu::PrimitiveTopology::TriangleList,                // means that every three vertices will correspond to one triangle.
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,                               // FrontFace::Ccw means that a triangle is facing forward if the vertices are arranged in a counter-clockwise direction
-                cull_mode: Some(wgpu::Face::Back),                              // triangles that are not considered facing forward are culled (not included in the render) as specified by CullMode::Back
-
-                // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                polygon_mode: wgpu::PolygonMode::Fill,
-
-                // Requires Features::DEPTH_CLIP_CONTROL
-                unclipped_depth: false,
-
-                // Requires Features::CONSERVATIVE_RASTERIZATION
-                conservative: false,                                                  // allows wgpu to cache shader compilation data. Only really useful for Android build targets.
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,                                                   // determines how many samples the pipeline will use
-                mask: !0,                                                   // specifies which samples should be active. In this case, we are using all of them.
-                alpha_to_coverage_enabled: false,                           // has to do with anti-aliasing
-            },
-            multiview: None,                                                // indicates how many array layers the render attachments can have
-            cache: None,                                                    // allows wgpu to cache shader compilation data
-        });
+        let (render_pipeline, wireframe_pipeline) = shader.build(&context.device, |module| {
+            let create = |style| create_render_pipeline(&context.device, context.pipeline_cache(), &render_pipeline_layout, module, context.config.format, style);
+            Ok((create(PipelineStyle::Fill), create(wireframe.edge_style())))
+        })?;
 
         Ok(Self {
-            window,
-            surface,
-            device,
-            queue,
-            config,
-            is_surface_configured: false,
+            context,
             render_pipeline,
+            render_pipeline_layout,
+            shader,
+            wireframe_pipeline,
+            wireframe,
         })
     }
+}
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        if width > 0 && height > 0 {
-            self.config.width = width;
-            self.config.height = height;
-            self.surface.configure(&self.device, &self.config);
-            self.is_surface_configured = true;
-        }
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target))
     }
 
-    pub fn handle_key(&self, event_loop: &ActiveEventLoop, code: KeyCode, is_pressed: bool) {
-        match(code, is_pressed) {
-            (KeyCode::Escape, true) => {
-                event_loop.exit();
-            },
-            _ => {}
-        }
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        self.wireframe.input(event)
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.context.resize(width, height);
     }
 
-    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        self.window.request_redraw();
+    fn update(&mut self) {
+        // Only does anything with --hot-reload, and keeps the old pipelines if the new shader doesn't compile.
+        let (device, cache, layout, format) =
+            (&self.context.device, self.context.pipeline_cache(), &self.render_pipeline_layout, self.context.config.format);
+        let edge_style = self.wireframe.edge_style();
+        let reloaded = self.shader.reload(device, |module| {
+            let create = |style| create_render_pipeline(device, cache, layout, module, format, style);
+            Ok((create(PipelineStyle::Fill), create(edge_style)))
+        });
+        if let Some((render_pipeline, wireframe_pipeline)) = reloaded {
+            self.render_pipeline = render_pipeline;
+            self.wireframe_pipeline = wireframe_pipeline;
+        }
+    }
 
-        if !self.is_surface_configured {
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        if !self.context.is_surface_configured() {
             return Ok(());
         }
 
-        let output = self.surface.get_current_texture()?;
+        let output = self.context.acquire_frame()?;
         let texture_view_descriptor = wgpu::TextureViewDescriptor::default();
-        let view = output.texture.create_view(&texture_view_descriptor);
+        let view = output.texture().create_view(&texture_view_descriptor);
         let command_encoder_descriptor = wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         };
-        let mut encoder = self.device.create_command_encoder(&command_encoder_descriptor);
+        let mut encoder = self.context.device.create_command_encoder(&command_encoder_descriptor);
 
         {
             let renderpass_descriptor = wgpu::RenderPassDescriptor {
@@ -163,12 +111,90 @@ impl State {
 
             let mut renderpass = encoder.begin_render_pass(&renderpass_descriptor);
             
-            renderpass.set_pipeline(&self.render_pipeline);
-            renderpass.draw(0..3, 0..1);                                            // tell wgpu to draw something with three vertices and one instance. This is where @builtin(vertex_index) comes from.
+            if self.wireframe.draws_fill() {
+                renderpass.set_pipeline(&self.render_pipeline);
+                renderpass.draw(0..3, 0..1);                                        // tell wgpu to draw something with three vertices and one instance. This is where @builtin(vertex_index) comes from.
+            }
+
+            // F cycles fill, wireframe and overlay. A non-indexed draw works for both kinds of edges.
+            if self.wireframe.draws_edges() {
+                renderpass.set_pipeline(&self.wireframe_pipeline);
+                renderpass.draw(0..3, 0..1);
+            }
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.context.queue.submit(std::iter::once(encoder.finish()));
         output.present();
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn context(&self) -> Option<&GpuContext> {
+        Some(&self.context)
+    }
+}
+
+/// The vertex buffers the pipeline feeds `vs_main`, checked against shader.wgsl by `check-layouts`.
+pub const SHADER_LAYOUTS: ShaderLayouts = ShaderLayouts {
+    path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+    source: include_str!("shader.wgsl"),
+    entry_point: "vs_main",
+    buffers: Vec::new,
+};
+
+// Spelled out field by field since this exercise is about the pipeline. The later exercises build theirs with
+// wgpu_common::RenderPipelineBuilder, which fills in the same defaults.
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    cache: Option<&wgpu::PipelineCache>,
+    render_pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    style: PipelineStyle,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(render_pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),                                   // @vertex decorated function in shader.wgsl
+            buffers: &[],                                                   // tells wgpu what type of vertices we want to pass to the vertex shader. We're specifying the vertices in the vertex shader itself, so we'll leave this empty.
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(style.fragment_entry_point()),                // @fragment decorated function in shader.wgsl
+            targets: &[Some(wgpu::ColorTargetState {                        // tells wgpu what color outputs it should set up. Currently, we only need one for the surface. 
+                format,                              // we use the surface's format so that copying to it is easy.
+                blend: style.blend(Some(wgpu::BlendState::REPLACE)),        // specify that the blending should just replace old pixel data with new data
+                write_mask: wgpu::ColorWrites::ALL,                         // we also tell wgpu to write to all colors: red, blue, green, and alpha.
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: style.constants(),
+                ..Default::default()
+            },
+        }),
+        primitive: wgpu::PrimitiveState {                                   // describes how to interpret our vertices when converting them into triangles.
+            topology: wgpu::PrimitiveTopology::TriangleList,                // means that every three vertices will correspond to one triangle.
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,                               // FrontFace::Ccw means that a triangle is facing forward if the vertices are arranged in a counter-clockwise direction
+            cull_mode: Some(wgpu::Face::Back),                              // triangles that are not considered facing forward are culled (not included in the render) as specified by CullMode::Back
+
+            // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
+            polygon_mode: style.polygon_mode(),
+
+            // Requires Features::DEPTH_CLIP_CONTROL
+            unclipped_depth: false,
+
+            // Requires Features::CONSERVATIVE_RASTERIZATION
+            conservative: false,                                                  // allows wgpu to cache shader compilation data. Only really useful for Android build targets.
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,                                                   // determines how many samples the pipeline will use
+            mask: !0,                                                   // specifies which samples should be active. In this case, we are using all of them.
+            alpha_to_coverage_enabled: false,                           // has to do with anti-aliasing
+        },
+        multiview: None,                                                // indicates how many array layers the render attachments can have
+        cache,                                                          // allows wgpu to cache shader compilation data between runs, see GpuContext::pipeline_cache
+    })
+}
@@ -0,0 +1,12 @@
+mod state;
+mod vertex;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::Registry;
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register::<State>("vertex-buffers", "Learn WGPU - Vertex Buffers")
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
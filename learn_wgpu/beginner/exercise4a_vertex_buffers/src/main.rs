@@ -1,22 +1,14 @@
-mod app;
-mod state;
-mod vertex;
-
-use winit::event_loop::{EventLoop, ControlFlow, };
-
-use crate::app::App;
-
 pub fn main() {
-    let _ = run();
+    if let Err(e) = run() {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
 }
 
 fn run() -> anyhow::Result<()> {
     env_logger::init();
 
-    let event_loop = EventLoop::with_user_event().build()?;
-    event_loop.set_control_flow(ControlFlow::Poll);
-
-    let mut app = App::default();
-    let _ = event_loop.run_app(&mut app);
-    Ok(())
-}
\ No newline at end of file
+    let mut registry = wgpu_common::Registry::default();
+    exercise4a_vertex_buffers::register(&mut registry);
+    wgpu_common::run(registry, None, wgpu_common::RunOptions::from_env()?)
+}
@@ -22,6 +22,8 @@ pub const VERTICES: &[Vertex] = &[
     Vertex { position: [ 0.5, -0.5, 0.0], color: [0.0, 0.0, 1.0], },
 ];
 
+// Written out by hand since this exercise is about vertex buffers. The later exercises derive the same layout with
+// #[derive(wgpu_common::VertexLayout)].
 impl Vertex {
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -1,162 +1,121 @@
-use std::sync::Arc;
 use wgpu::util::DeviceExt;
-use winit::event_loop::ActiveEventLoop;
-use winit::keyboard::KeyCode;
-use winit::window::Window;
+use winit::event::WindowEvent;
+use wgpu_common::{deindex, Exercise, GpuContext, IndexBuffer, PipelineStyle, Preprocessor, RenderPipelineBuilder, Shader, ShaderLayouts, Target, VertexLayout, Wireframe};
 
-use crate::vertex::{Vertex, VERTICES, INDICES, };
+use crate::vertex::{indices, Vertex, VERTICES};
 
 pub struct State {
-    pub window: Arc<Window>,
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    is_surface_configured: bool,
+    context: GpuContext,
     render_pipeline: wgpu::RenderPipeline,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    shader: Shader,
+    wireframe_pipeline: wgpu::RenderPipeline,
+    wireframe: Wireframe,
+    wireframe_vertex_buffer: wgpu::Buffer,      // the indexed geometry expanded for the barycentric fallback
     vertex_buffer: wgpu::Buffer,
+    #[allow(dead_code)]
     num_vertices: u32,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
+    index_buffer: IndexBuffer,
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>) -> anyhow::Result<Self> {
-        let size = window.inner_size();
-        let instance_descriptor = wgpu::InstanceDescriptor::default();
-        let instance = wgpu::Instance::new(&instance_descriptor);
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let request_adapter_options = wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::default(),
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        };
-        let adapter = instance.request_adapter(&request_adapter_options).await?;
-        let device_descriptor = wgpu::DeviceDescriptor {
-            label: None,
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::default(),
-            memory_hints: Default::default(),
-            trace: wgpu::Trace::Off,
-        };
-        let (device, queue) = adapter.request_device(&device_descriptor).await?;
-        let surface_capabilities = surface.get_capabilities(&adapter);
-        let surface_format = surface_capabilities.formats[0];
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: surface_capabilities.present_modes[0],
-            alpha_mode: surface_capabilities.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+    pub async fn new(target: Target) -> anyhow::Result<Self> {
+        let hot_reload = target.hot_reload;
+        let context = GpuContext::builder()
+            .optional_features(Wireframe::FEATURES)     // PolygonMode::Line for the wireframe when the adapter has it
+            .build(target)
+            .await?;
+        let wireframe = Wireframe::new(&context.device);
+        // shader.wgsl #includes shared snippets, so it goes through the preprocessor instead of include_wgsl!.
+        let shader = Shader::preprocessed(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+            include_str!("shader.wgsl"),
+            Preprocessor::default(),
+            hot_reload,
+        )?;
         let pipeline_layout_descriptor = wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
             bind_group_layouts: &[],
             push_constant_ranges: &[],
         };
-        let render_pipeline_layout = device.create_pipeline_layout(&pipeline_layout_descriptor);
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let render_pipeline_layout = context.device.create_pipeline_layout(&pipeline_layout_descriptor);
+        let vertex_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: bytemuck::cast_slice(VERTICES),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
         // NEW - Index Buffer
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
+        // Stored as u16 while every index fits, u32 otherwise, and set_index_buffer is told which one it got.
+        let indices = indices();
+        let index_buffer = IndexBuffer::new(&context.device, "Index Buffer", &indices);
+
+        let (render_pipeline, wireframe_pipeline) = shader.build(&context.device, |module| {
+            let create = |style| create_render_pipeline(&context.device, context.pipeline_cache(), &render_pipeline_layout, module, context.config.format, style);
+            Ok((create(PipelineStyle::Fill)?, create(wireframe.edge_style())?))
+        })?;
+
+        let wireframe_vertex_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Wireframe Vertex Buffer"),
+            contents: bytemuck::cast_slice(&deindex(VERTICES, &indices)),
+            usage: wgpu::BufferUsages::VERTEX,
         });
 
         Ok(Self {
-            window,
-            surface,
-            device,
-            queue,
-            config,
-            is_surface_configured: false,
+            context,
             render_pipeline,
+            render_pipeline_layout,
+            shader,
+            wireframe_pipeline,
+            wireframe,
+            wireframe_vertex_buffer,
             vertex_buffer,
             num_vertices: VERTICES.len() as u32,
             index_buffer,
-            num_indices: INDICES.len() as u32,
         })
     }
+}
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        if width > 0 && height > 0 {
-            self.config.width = width;
-            self.config.height = height;
-            self.surface.configure(&self.device, &self.config);
-            self.is_surface_configured = true;
-        }
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target))
     }
 
-    pub fn handle_key(&self, event_loop: &ActiveEventLoop, code: KeyCode, is_pressed: bool) {
-        match(code, is_pressed) {
-            (KeyCode::Escape, true) => {
-                event_loop.exit();
-            },
-            _ => {}
-        }
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        self.wireframe.input(event)
     }
 
-    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        self.window.request_redraw();
+    fn resize(&mut self, width: u32, height: u32) {
+        self.context.resize(width, height);
+    }
 
-        if !self.is_surface_configured {
+    fn update(&mut self) {
+        // Only does anything with --hot-reload, and keeps the old pipelines if the new shader doesn't compile.
+        let (device, cache, layout, format) =
+            (&self.context.device, self.context.pipeline_cache(), &self.render_pipeline_layout, self.context.config.format);
+        let edge_style = self.wireframe.edge_style();
+        let reloaded = self.shader.reload(device, |module| {
+            let create = |style| create_render_pipeline(device, cache, layout, module, format, style);
+            Ok((create(PipelineStyle::Fill)?, create(edge_style)?))
+        });
+        if let Some((render_pipeline, wireframe_pipeline)) = reloaded {
+            self.render_pipeline = render_pipeline;
+            self.wireframe_pipeline = wireframe_pipeline;
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        if !self.context.is_surface_configured() {
             return Ok(())
         }
 
-        let output = self.surface.get_current_texture()?;
+        let output = self.context.acquire_frame()?;
         let texture_view_descriptor = wgpu::TextureViewDescriptor::default();
-        let view = output.texture.create_view(&texture_view_descriptor);
+        let view = output.texture().create_view(&texture_view_descriptor);
         let command_encoder_descriptor = wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         };
-        let mut encoder = self.device.create_command_encoder(&command_encoder_descriptor);
+        let mut encoder = self.context.device.create_command_encoder(&command_encoder_descriptor);
 
         {
             let renderpass_descriptor = wgpu::RenderPassDescriptor {
@@ -177,27 +136,71 @@ impl State {
 
             let mut renderpass = encoder.begin_render_pass(&renderpass_descriptor);
 
-            renderpass.set_pipeline(&self.render_pipeline);
             renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
 
-            renderpass.set_index_buffer(
-                self.index_buffer.slice(..), 
-                wgpu::IndexFormat::Uint16
-            );
-
-            // When using an index buffer, you need to use draw_indexed because the draw method ignores the index buffer
-            renderpass.draw_indexed(
-                0..self.num_indices, 
-                0, 
-                0..1
-            );
-
-            renderpass.draw(0..self.num_vertices, 0..1);
-            renderpass.draw(0..3, 0..1);
+            self.index_buffer.bind(&mut renderpass);
+
+            if self.wireframe.draws_fill() {
+                renderpass.set_pipeline(&self.render_pipeline);
+
+                // When using an index buffer, you need to use draw_indexed because the draw method ignores the index buffer
+                self.index_buffer.draw(&mut renderpass, 0..1);
+
+                // renderpass.draw(0..self.num_vertices, 0..1);
+                // renderpass.draw(0..3, 0..1);
+            }
+
+            // F cycles fill, wireframe and overlay. The barycentric edges need every triangle to have its own three
+            // vertices, so they are drawn from the expanded copy of the indexed geometry.
+            if self.wireframe.draws_edges() {
+                renderpass.set_pipeline(&self.wireframe_pipeline);
+                if self.wireframe.needs_deindexed() {
+                    renderpass.set_vertex_buffer(0, self.wireframe_vertex_buffer.slice(..));
+                    renderpass.draw(0..self.index_buffer.count, 0..1);
+                    renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                } else {
+                    self.index_buffer.draw(&mut renderpass, 0..1);
+                }
+            }
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.context.queue.submit(std::iter::once(encoder.finish()));
         output.present();
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn context(&self) -> Option<&GpuContext> {
+        Some(&self.context)
+    }
+}
+
+/// The vertex buffers the pipeline feeds `vs_main`, checked against shader.wgsl by `check-layouts`.
+pub const SHADER_LAYOUTS: ShaderLayouts = ShaderLayouts {
+    path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+    source: include_str!("shader.wgsl"),
+    entry_point: "vs_main",
+    buffers: vertex_buffers,
+};
+
+fn vertex_buffers() -> Vec<wgpu::VertexBufferLayout<'static>> {
+    vec![Vertex::desc()]
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    cache: Option<&wgpu::PipelineCache>,
+    render_pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    style: PipelineStyle,
+) -> anyhow::Result<wgpu::RenderPipeline> {
+    RenderPipelineBuilder::new(shader)
+        .label("Render Pipeline")
+        .layout(render_pipeline_layout)
+        .vertex_buffers(vertex_buffers())
+        .color_target(format)
+        .cull_mode(Some(wgpu::Face::Back))
+        .style(style)
+        .cache(cache)
+        .build(device)
+}
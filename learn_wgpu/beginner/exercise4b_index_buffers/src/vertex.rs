@@ -1,5 +1,7 @@
+use wgpu_common::{Polygon, VertexLayout};
+
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
 pub struct Vertex {
     position: [f32; 3],
     color: [f32; 3],
@@ -29,30 +31,8 @@ pub const VERTICES: &[Vertex] = &[
     }, // E
 ];
 
-pub const INDICES : &[u16] = &[
-    0, 1, 4,
-    1, 2, 4,
-    2, 3, 4,
-];
-
-
-impl Vertex {
-    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
-                }
-            ]
-        }
-    }
-}
\ No newline at end of file
+// NEW - the triangles are cut from the outline instead of being listed by hand, so reshaping the pentagon
+// (or giving it more sides) keeps the index buffer right.
+pub fn indices() -> Vec<u32> {
+    Polygon::new(VERTICES.iter().map(|vertex| [vertex.position[0], vertex.position[1]])).triangulate()
+}
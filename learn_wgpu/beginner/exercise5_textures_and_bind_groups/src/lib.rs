@@ -0,0 +1,13 @@
+mod state;
+mod texture;
+mod vertex;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::Registry;
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register::<State>("textures", "Learn WGPU - Textures and Bind Groups")
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
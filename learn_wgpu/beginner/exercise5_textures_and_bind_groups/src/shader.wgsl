@@ -1,3 +1,7 @@
+// barycentric() and wireframe_coverage() for fs_wireframe, shared by every exercise with a wireframe toggle, see
+// wgpu_common/shaders/wireframe.wgsl. The preprocessor in wgpu_common pastes them in.
+#include "wireframe.wgsl"
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) texture_coords: vec2<f32>,
@@ -6,15 +10,18 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) texture_coords: vec2<f32>,
+    @location(1) barycentric: vec3<f32>,
 };
 
 @vertex
 fn vs_main(
-    model: VertexInput
+    model: VertexInput,
+    @builtin(vertex_index) vertex_index: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
     out.texture_coords = model.texture_coords;
     out.clip_position = vec4<f32>(model.position, 1.0);
+    out.barycentric = barycentric(vertex_index);
     return out;
 }
 
@@ -28,4 +35,9 @@ fn fs_main(
     in: VertexOutput
 ) -> @location(0) vec4<f32> {
     return textureSample(texture_diffuse, sampler_diffuse, in.texture_coords);
-}
\ No newline at end of file
+}
+
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, wireframe_coverage(in.barycentric));
+}
@@ -42,7 +42,7 @@ impl Camera {
         // That means that in normalized device coordinates, the x-axis and y-axis are in the range of -1.0 to +1.0, 
         //      and the z-axis is 0.0 to +1.0.
         // cgmath crate (as well as most game math crates) is built for OpenGL's coordinate system
-        return OPENGL_TO_WGPU_MATRIX * proj * view;
+        OPENGL_TO_WGPU_MATRIX * proj * view
     }
 }
 
@@ -121,7 +121,6 @@ impl CameraController {
     }
 
     pub fn update_camera(&self, camera: &mut Camera) {
-        use cgmath::InnerSpace;
         let forward = camera.target - camera.eye;
         let forward_norm = forward.normalize();
         let forward_mag = forward.magnitude();
@@ -0,0 +1,14 @@
+mod camera;
+mod state;
+mod texture;
+mod vertex;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::Registry;
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register::<State>("camera", "Learn WGPU - Uniform Buffers and a 3d Camera")
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
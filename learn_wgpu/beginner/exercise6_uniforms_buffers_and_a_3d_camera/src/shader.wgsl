@@ -1,9 +1,8 @@
-struct CameraUniform {
-    view_proj: mat4x4<f32>,
-};
-
-@group(1) @binding(0)
-var<uniform> camera: CameraUniform;
+// The camera uniform is shared with the instancing exercise, see wgpu_common/shaders/camera.wgsl. The
+// preprocessor in wgpu_common pastes it in.
+#include "camera.wgsl"
+// barycentric() and wireframe_coverage() for fs_wireframe, shared by every exercise with a wireframe toggle.
+#include "wireframe.wgsl"
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -13,13 +12,15 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) texture_coords: vec2<f32>,
+    @location(1) barycentric: vec3<f32>,
 };
 
 @vertex
-fn vs_main(model: VertexInput) -> VertexOutput {
+fn vs_main(model: VertexInput, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
     var out: VertexOutput;
     out.texture_coords = model.texture_coords;
     out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.barycentric = barycentric(vertex_index);
     return out;
 }
 
@@ -33,4 +34,9 @@ fn fs_main(
     in: VertexOutput
 ) -> @location(0) vec4<f32> {
     return textureSample(texture_diffuse, sampler_diffuse, in.texture_coords);
-}
\ No newline at end of file
+}
+
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, wireframe_coverage(in.barycentric));
+}
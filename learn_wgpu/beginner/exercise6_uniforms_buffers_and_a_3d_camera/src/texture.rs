@@ -16,7 +16,7 @@ impl Texture {
         label: &str,
     ) -> Result<Self> {
         let texture_image = image::load_from_memory(bytes)?;
-        Self::from_image(&device, &queue, &texture_image, Some(label))
+        Self::from_image(device, queue, &texture_image, Some(label))
     }
 
     pub fn from_image(
@@ -27,9 +27,9 @@ impl Texture {
     ) -> Result<Self> {
         let rgba = texture_image.to_rgba8();
         let dimensions = texture_image.dimensions();
-        let texture = Self::create_texture(&device, &queue, &rgba, dimensions, label);
+        let texture = Self::create_texture(device, queue, &rgba, dimensions, label);
         let view = Self::create_view(&texture);
-        let sampler = Self::create_sampler(&device);
+        let sampler = Self::create_sampler(device);
 
         Ok(Self {
             texture,
@@ -88,9 +88,7 @@ impl Texture {
     ) -> wgpu::TextureView {
         let texture_view_descriptor = wgpu::TextureViewDescriptor::default();
         
-        let view = texture.create_view(&texture_view_descriptor);
-
-        view
+        texture.create_view(&texture_view_descriptor)
     }
 
     fn create_sampler(
@@ -106,8 +104,6 @@ impl Texture {
             ..Default::default()
         };
 
-        let sampler = device.create_sampler(&sampler_descriptor);
-
-        sampler
+        device.create_sampler(&sampler_descriptor)
     }
 }
\ No newline at end of file
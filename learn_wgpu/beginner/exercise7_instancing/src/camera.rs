@@ -36,7 +36,7 @@ impl Camera {
             self.zfar
         );
 
-        return OPENGL_TO_WGPU_MATRIX * proj * view;
+        OPENGL_TO_WGPU_MATRIX * proj * view
     }
 }
 
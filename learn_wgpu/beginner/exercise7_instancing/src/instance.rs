@@ -1,5 +1,5 @@
 use cgmath::Matrix4;
-use std::mem;
+use wgpu_common::VertexLayout;
 
 pub struct Instance {
     pub position: cgmath::Vector3<f32>,
@@ -15,38 +15,11 @@ impl Instance {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+#[vertex(step_mode = "instance")]           // only move on to the next one for the next instance
 pub struct InstanceRaw {
+    // A mat4 takes up 4 vertex slots as it is technically 4 vec4s, so it is split into @location(5) to @location(8).
+    // Vertex uses 0 and 1, but we start at 5 to leave room for more vertex attributes later.
+    #[vertex(location = 5, matrix)]
     model: [[f32; 4]; 4],
 }
-
-impl InstanceRaw {
-    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Instance,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 5,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                    shader_location: 6,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
-                    shader_location: 7,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-                wgpu::VertexAttribute {
-                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
-                    shader_location: 8,
-                    format: wgpu::VertexFormat::Float32x4,
-                },
-            ],
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,15 @@
+mod camera;
+mod instance;
+mod state;
+mod texture;
+mod vertex;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::Registry;
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register::<State>("instancing", "Learn WGPU - Instancing")
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
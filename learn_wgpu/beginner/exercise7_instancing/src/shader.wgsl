@@ -1,42 +1,33 @@
-struct InstanceInput {
-    @location(5) model_matrix_0: vec4<f32>,
-    @location(6) model_matrix_1: vec4<f32>,
-    @location(7) model_matrix_2: vec4<f32>,
-    @location(8) model_matrix_3: vec4<f32>,
-};
-
-struct CameraUniform {
-    view_proj: mat4x4<f32>,
-};
-
-@group(1) @binding(0)
-var<uniform> camera: CameraUniform;
+// Shared with the other exercises, see wgpu_common/shaders. The preprocessor in wgpu_common pastes them in.
+#include "camera.wgsl"
+#include "instance.wgsl"
+#include "wireframe.wgsl"
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) texture_coords: vec2<f32>,
+    @location(2) normal: vec3<f32>,             // not lit yet, but loaded models come with normals
+    @location(3) tangent: vec4<f32>,            // and tangents, for normal mapping later
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) texture_coords: vec2<f32>,
+    @location(1) barycentric: vec3<f32>,
 };
 
 @vertex
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
+    @builtin(vertex_index) vertex_index: u32,
 ) -> VertexOutput {
-    let model_matrix = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
+    let model_matrix = instance_model_matrix(instance);
 
     var out: VertexOutput;
     out.texture_coords = model.texture_coords;
     out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
+    out.barycentric = barycentric(vertex_index);
     return out;
 }
 
@@ -49,4 +40,9 @@ var sampler_diffuse: sampler;
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return textureSample(texture_diffuse, sampler_diffuse, in.texture_coords);
-}
\ No newline at end of file
+}
+
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, wireframe_coverage(in.barycentric));
+}
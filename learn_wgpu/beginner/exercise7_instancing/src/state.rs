@@ -1,10 +1,10 @@
+use std::path::PathBuf;
+
 use cgmath::prelude::*;
-use std::sync::Arc;
 use wgpu::util::DeviceExt;
-use winit::event::WindowEvent;
-use winit::event_loop::ActiveEventLoop;
-use winit::keyboard::KeyCode;
-use winit::window::Window;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use wgpu_common::{deindex, BindGroupLayouts, Exercise, GpuContext, IndexBuffer, Model, PipelineStyle, Preprocessor, RenderPipelineBuilder, Scene, Shader, ShaderLayouts, Target, VertexLayout, Wireframe};
 
 use crate::camera::{Camera, CameraUniform, CameraController, };
 use crate::instance::{Instance, InstanceRaw, };
@@ -12,16 +12,15 @@ use crate::texture::Texture;
 use crate::vertex::{Vertex, VERTICES, INDICES, };
 
 pub struct State {
-    pub window: Arc<Window>,
-    surface: wgpu::Surface<'static>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    is_surface_configured: bool,
+    context: GpuContext,
     render_pipeline: wgpu::RenderPipeline,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    shader: Shader,
+    wireframe_pipeline: wgpu::RenderPipeline,
+    wireframe: Wireframe,
+    wireframe_vertex_buffer: wgpu::Buffer,      // the indexed geometry expanded for the barycentric fallback
     vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
+    index_buffer: IndexBuffer,
     diffuse_bind_group: wgpu::BindGroup,
 
     #[allow(dead_code)]
@@ -34,72 +33,39 @@ pub struct State {
 
     instances: Vec<Instance>,
     instance_buffer: wgpu::Buffer,
+
+    // M cycles the pentagon, a model loaded from an OBJ file and a glTF scene, skipping the ones that didn't load.
+    model: Option<Model>,
+    scene: Option<Scene>,
+    showing: Showing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Showing {
+    Pentagon,
+    Model,
+    Scene,
 }
 
 impl State {
     pub async fn new(
-        window: Arc<Window>,
+        target: Target,
     ) -> anyhow::Result<Self> {
-        let size = window.inner_size();
-        let instance_descriptor = wgpu::InstanceDescriptor::default();
-        let instance = wgpu::Instance::new(&instance_descriptor);
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let request_adapter_options = wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::default(),
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        };
-        let adapter = instance.request_adapter(&request_adapter_options).await?;
-        let device_descriptor = wgpu::DeviceDescriptor {
-            label: None,
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::default(),
-            memory_hints: Default::default(),
-            trace: wgpu::Trace::Off,
-        };
-        let (device, queue) = adapter.request_device(&device_descriptor).await?;
-
-        // Surface
-        let surface_capabilities = surface.get_capabilities(&adapter);
-        let surface_format = surface_capabilities.formats[0];
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width,
-            height: size.height,
-            present_mode: surface_capabilities.present_modes[0],
-            alpha_mode: surface_capabilities.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
+        let hot_reload = target.hot_reload;
+        let context = GpuContext::builder()
+            .optional_features(Wireframe::FEATURES)     // PolygonMode::Line for the wireframe when the adapter has it
+            .build(target)
+            .await?;
+        let wireframe = Wireframe::new(&context.device);
+
+        // Instead of spelling out the bind group layouts like the earlier exercises, read them from the
+        // @group/@binding declarations in the shader, so the two can't drift apart.
+        let bind_groups = BindGroupLayouts::from_wgsl(SHADER_LAYOUTS.path, SHADER_LAYOUTS.source)?;
 
         // Texture
         let diffuse_bytes = include_bytes!("../.assets/happy-tree.png");
-        let diffuse_texture = Texture::from_bytes(&device, &queue, diffuse_bytes, "happy-tree.png").unwrap();
-        let bind_group_layout_descriptor = wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float {
-                            filterable: true,
-                        },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
-            label: Some("texture_bind_goup_layout"),
-        };
-        let texture_bind_goup_layout = device.create_bind_group_layout(&bind_group_layout_descriptor);
+        let diffuse_texture = Texture::from_bytes(&context.device, &context.queue, diffuse_bytes, "happy-tree.png").unwrap();
+        let texture_bind_goup_layout = bind_groups.create(&context.device, 0, "texture_bind_goup_layout")?;
         let diffuse_bind_group_descriptor = wgpu::BindGroupDescriptor {
             layout: &texture_bind_goup_layout,
             entries: &[
@@ -114,41 +80,26 @@ impl State {
             ],
             label: Some("diffuse_bind_group"),
         };
-        let diffuse_bind_group = device.create_bind_group(&diffuse_bind_group_descriptor);
+        let diffuse_bind_group = context.device.create_bind_group(&diffuse_bind_group_descriptor);
 
         // Camera
         let camera = Camera {
             eye: (0.0, 1.0, 2.0).into(),
             target: (0.0, 0.0, 0.0).into(),
             up: cgmath::Vector3::unit_y(),
-            aspect: config.width as f32 / config.height as f32,
+            aspect: context.config.width as f32 / context.config.height as f32,
             fovy: 45.0,
             znear: 0.1,
             zfar: 100.0,
         };
         let mut camera_uniform = CameraUniform::new();
         camera_uniform.update_view_proj(&camera);
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let camera_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
             contents: bytemuck::cast_slice(&[camera_uniform]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
-        let camera_bind_group_layout_descriptor = wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }
-            ],
-            label: Some("camera_bind_group_layout"),
-        };
-        let camera_bind_group_layout = device.create_bind_group_layout(&camera_bind_group_layout_descriptor);
+        let camera_bind_group_layout = bind_groups.create(&context.device, 1, "camera_bind_group_layout")?;
         let camera_bind_group_descriptor = wgpu::BindGroupDescriptor {
             layout: &camera_bind_group_layout,
             entries: &[
@@ -159,7 +110,7 @@ impl State {
             ],
             label: Some("camera_bind_group"),
         };
-        let camera_bind_group = device.create_bind_group(&camera_bind_group_descriptor);
+        let camera_bind_group = context.device.create_bind_group(&camera_bind_group_descriptor);
         let camera_controller = CameraController::new(0.2);
 
         // Instancing
@@ -193,10 +144,43 @@ impl State {
             contents: bytemuck::cast_slice(&instance_data),
             usage: wgpu::BufferUsages::VERTEX,
         };
-        let instance_buffer = device.create_buffer_init(&instance_buffer_descriptor);
+        let instance_buffer = context.device.create_buffer_init(&instance_buffer_descriptor);
+
+        // Model
+        // INSTANCING_MODEL=path/to/model.obj draws your own model instead of the bundled cube.
+        let model_path = std::env::var_os("INSTANCING_MODEL")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/.assets/cube.obj")));
+        let model = match Model::load_obj(&context.device, &context.queue, &model_path, &texture_bind_goup_layout) {
+            Ok(model) => Some(model),
+            Err(e) => {
+                log::warn!("No model to draw with M: {:#}", e);
+                None
+            },
+        };
+
+        // glTF scene
+        // INSTANCING_SCENE=path/to/scene.gltf (or .glb) draws your own scene instead of the bundled one. The nodes
+        // bring their own transforms, so the scene is drawn once rather than once per instance.
+        let scene_path = std::env::var_os("INSTANCING_SCENE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/.assets/scene.gltf")));
+        let scene = match Scene::load_gltf(&context.device, &context.queue, &scene_path, &texture_bind_goup_layout) {
+            Ok(scene) => Some(scene),
+            Err(e) => {
+                log::warn!("No glTF scene to draw with M: {:#}", e);
+                None
+            },
+        };
 
         // Pipeline
-        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        // shader.wgsl #includes shared snippets, so it goes through the preprocessor instead of include_wgsl!.
+        let shader = Shader::preprocessed(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+            include_str!("shader.wgsl"),
+            Preprocessor::default(),
+            hot_reload,
+        )?;
         let pipeline_layout_descriptor = wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
             bind_group_layouts: &[
@@ -205,72 +189,34 @@ impl State {
             ],
             push_constant_ranges: &[],
         };
-        let render_pipeline_layout = device.create_pipeline_layout(&pipeline_layout_descriptor);
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        let render_pipeline_layout = context.device.create_pipeline_layout(&pipeline_layout_descriptor);
+        let vertex_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: bytemuck::cast_slice(VERTICES),
             usage: wgpu::BufferUsages::VERTEX,
         });
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[
-                    Vertex::desc(),
-                    InstanceRaw::desc(),            // add buffer layout to the render pipeline so that we can use it when we render.
-                ],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent::REPLACE,
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
+        let index_buffer = IndexBuffer::new(&context.device, "Index Buffer", INDICES);
+        let (render_pipeline, wireframe_pipeline) = shader.build(&context.device, |module| {
+            let create = |style| create_render_pipeline(&context.device, context.pipeline_cache(), &render_pipeline_layout, module, context.config.format, style);
+            Ok((create(PipelineStyle::Fill)?, create(wireframe.edge_style())?))
+        })?;
+
+        let wireframe_vertex_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Wireframe Vertex Buffer"),
+            contents: bytemuck::cast_slice(&deindex(VERTICES, INDICES)),
+            usage: wgpu::BufferUsages::VERTEX,
         });
 
         Ok(Self {
-            window,
-            surface,
-            device,
-            queue,
-            config,
-            is_surface_configured: false,
+            context,
             render_pipeline,
+            render_pipeline_layout,
+            shader,
+            wireframe_pipeline,
+            wireframe,
+            wireframe_vertex_buffer,
             vertex_buffer,
             index_buffer,
-            num_indices: INDICES.len() as u32,
             diffuse_bind_group,
             diffuse_texture,
             camera,
@@ -280,51 +226,91 @@ impl State {
             camera_controller,
             instances,
             instance_buffer,
+            model,
+            scene,
+            showing: Showing::Pentagon,
         })
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        if width > 0 && height > 0 {
-            self.config.width = width;
-            self.config.height = height;
-            self.surface.configure(&self.device, &self.config);
-            self.is_surface_configured = true;
+    fn next_showing(&self) -> Showing {
+        let loaded = |showing| match showing {
+            Showing::Pentagon => true,
+            Showing::Model => self.model.is_some(),
+            Showing::Scene => self.scene.is_some(),
+        };
+        let after = |showing| match showing {
+            Showing::Pentagon => Showing::Model,
+            Showing::Model => Showing::Scene,
+            Showing::Scene => Showing::Pentagon,
+        };
+
+        let mut next = after(self.showing);
+        while !loaded(next) {
+            next = after(next);
         }
+        next
     }
+}
 
-    pub fn input(&mut self, event: &WindowEvent) -> bool {
-        self.camera_controller.process_event(event)
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target))
     }
 
-    pub fn update(&mut self) {
-        self.camera_controller.update_camera(&mut self.camera);
-        self.camera_uniform.update_view_proj(&self.camera);
-        self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+    fn resize(&mut self, width: u32, height: u32) {
+        self.context.resize(width, height);
     }
 
-    pub fn handle_key(&self, event_loop: &ActiveEventLoop, code: KeyCode, is_pressed: bool) {
-        match (code, is_pressed) {
-            (KeyCode::Escape, true) => {
-                event_loop.exit();
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        if let WindowEvent::KeyboardInput {
+            event: KeyEvent {
+                physical_key: PhysicalKey::Code(KeyCode::KeyM),
+                state: ElementState::Pressed,
+                repeat: false,
+                ..
             },
-            _ => {}
+            ..
+        } = event
+        {
+            self.showing = self.next_showing();
+            log::info!("Drawing the {:?}", self.showing);
+            return true;
         }
+
+        self.wireframe.input(event) || self.camera_controller.process_event(event)
     }
 
-    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        self.window.request_redraw();
+    fn update(&mut self) {
+        // Only does anything with --hot-reload, and keeps the old pipelines if the new shader doesn't compile.
+        let (device, cache, layout, format) =
+            (&self.context.device, self.context.pipeline_cache(), &self.render_pipeline_layout, self.context.config.format);
+        let edge_style = self.wireframe.edge_style();
+        let reloaded = self.shader.reload(device, |module| {
+            let create = |style| create_render_pipeline(device, cache, layout, module, format, style);
+            Ok((create(PipelineStyle::Fill)?, create(edge_style)?))
+        });
+        if let Some((render_pipeline, wireframe_pipeline)) = reloaded {
+            self.render_pipeline = render_pipeline;
+            self.wireframe_pipeline = wireframe_pipeline;
+        }
+
+        self.camera_controller.update_camera(&mut self.camera);
+        self.camera_uniform.update_view_proj(&self.camera);
+        self.context.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[self.camera_uniform]));
+    }
 
-        if !self.is_surface_configured {
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        if !self.context.is_surface_configured() {
             return Ok(());
         }
 
-        let output = self.surface.get_current_texture()?;
+        let output = self.context.acquire_frame()?;
         let texture_view_descriptor = wgpu::TextureViewDescriptor::default();
-        let view = output.texture.create_view(&texture_view_descriptor);
+        let view = output.texture().create_view(&texture_view_descriptor);
         let command_encoder_descriptor = wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         };
-        let mut encoder = self.device.create_command_encoder(&command_encoder_descriptor);
+        let mut encoder = self.context.device.create_command_encoder(&command_encoder_descriptor);
 
         {
             let renderpass_descriptor = wgpu::RenderPassDescriptor {
@@ -347,25 +333,106 @@ impl State {
 
             let mut renderpass = encoder.begin_render_pass(&renderpass_descriptor);
 
-            renderpass.set_pipeline(&self.render_pipeline);
             renderpass.set_bind_group(0, &self.diffuse_bind_group, &[]);
             renderpass.set_bind_group(1, &self.camera_bind_group, &[]);
-            renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
 
             // NEW
             renderpass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+            let instances = 0..self.instances.len() as u32;
+
+            // A loaded model brings its own vertex and index buffers and binds each material's texture to group 0 in
+            // place of the happy tree.
+            if let (Showing::Model, Some(model)) = (self.showing, &self.model) {
+                if self.wireframe.draws_fill() {
+                    renderpass.set_pipeline(&self.render_pipeline);
+                    model.draw_instanced(&mut renderpass, instances.clone(), 0);
+                }
+                if self.wireframe.draws_edges() {
+                    renderpass.set_pipeline(&self.wireframe_pipeline);
+                    if self.wireframe.needs_deindexed() {
+                        model.draw_deindexed_instanced(&mut renderpass, instances, 0);
+                    } else {
+                        model.draw_instanced(&mut renderpass, instances, 0);
+                    }
+                }
+            } else if let (Showing::Scene, Some(scene)) = (self.showing, &self.scene) {
+                // Same as the model, but every glTF mesh binds the transforms of its nodes to slot 1 instead of the
+                // grid of instances.
+                if self.wireframe.draws_fill() {
+                    renderpass.set_pipeline(&self.render_pipeline);
+                    scene.draw(&mut renderpass, 0, 1);
+                }
+                if self.wireframe.draws_edges() {
+                    renderpass.set_pipeline(&self.wireframe_pipeline);
+                    if self.wireframe.needs_deindexed() {
+                        scene.draw_deindexed(&mut renderpass, 0, 1);
+                    } else {
+                        scene.draw(&mut renderpass, 0, 1);
+                    }
+                }
+            } else {
+                renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                self.index_buffer.bind(&mut renderpass);
 
-            renderpass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                if self.wireframe.draws_fill() {
+                    renderpass.set_pipeline(&self.render_pipeline);
+                    self.index_buffer.draw(&mut renderpass, instances.clone());    // UPDATED
+                }
 
-            renderpass.draw_indexed(
-                0..self.num_indices,
-                0,
-                0..self.instances.len() as _    // UPDATED
-            );
+                // F cycles fill, wireframe and overlay. The barycentric edges need every triangle to have its own three
+                // vertices, so they are drawn from the expanded copy of the indexed geometry, still once per instance.
+                if self.wireframe.draws_edges() {
+                    renderpass.set_pipeline(&self.wireframe_pipeline);
+                    if self.wireframe.needs_deindexed() {
+                        renderpass.set_vertex_buffer(0, self.wireframe_vertex_buffer.slice(..));
+                        renderpass.draw(0..self.index_buffer.count, instances);
+                    } else {
+                        self.index_buffer.draw(&mut renderpass, instances);
+                    }
+                }
+            }
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        self.context.queue.submit(std::iter::once(encoder.finish()));
         output.present();
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn context(&self) -> Option<&GpuContext> {
+        Some(&self.context)
+    }
+}
+
+/// The vertex buffers the pipeline feeds `vs_main`, checked against shader.wgsl by `check-layouts`.
+pub const SHADER_LAYOUTS: ShaderLayouts = ShaderLayouts {
+    path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+    source: include_str!("shader.wgsl"),
+    entry_point: "vs_main",
+    buffers: vertex_buffers,
+};
+
+fn vertex_buffers() -> Vec<wgpu::VertexBufferLayout<'static>> {
+    vec![
+        Vertex::desc(),
+        InstanceRaw::desc(),            // add buffer layout to the render pipeline so that we can use it when we render.
+    ]
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    cache: Option<&wgpu::PipelineCache>,
+    render_pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    style: PipelineStyle,
+) -> anyhow::Result<wgpu::RenderPipeline> {
+    RenderPipelineBuilder::new(shader)
+        .label("Render Pipeline")
+        .layout(render_pipeline_layout)
+        .vertex_buffers(vertex_buffers())
+        .color_target(format)
+        .cull_mode(Some(wgpu::Face::Back))
+        .style(style)
+        .cache(cache)
+        .build(device)
+}
@@ -1,16 +1,13 @@
-#[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct Vertex {
-    position: [f32; 3],
-    texture_coords: [f32; 2],
-}
+// The pentagon is made of the same vertex the model loader produces, so both can be drawn with one pipeline. It faces
+// the camera, so every normal points along +z. u grows along +x and v downwards, hence the tangent's -1 handedness.
+pub use wgpu_common::ModelVertex as Vertex;
 
 pub const VERTICES: &[Vertex] = &[
-    Vertex { position: [-0.0868241, 0.49240386, 0.0], texture_coords: [0.4131759, 0.00759614], },
-    Vertex { position: [-0.49513406, 0.06958647, 0.0], texture_coords: [0.0048659444, 0.43041354], },
-    Vertex { position: [-0.21918549, -0.44939706, 0.0], texture_coords: [0.28081453, 0.949397], },
-    Vertex { position: [0.35966998, -0.3473291, 0.0], texture_coords: [0.85967, 0.84732914], },
-    Vertex { position: [0.44147372, 0.2347359, 0.0], texture_coords: [0.9414737, 0.2652641], },
+    Vertex { position: [-0.0868241, 0.49240386, 0.0], texture_coords: [0.4131759, 0.00759614], normal: [0.0, 0.0, 1.0], tangent: [1.0, 0.0, 0.0, -1.0], },
+    Vertex { position: [-0.49513406, 0.06958647, 0.0], texture_coords: [0.0048659444, 0.43041354], normal: [0.0, 0.0, 1.0], tangent: [1.0, 0.0, 0.0, -1.0], },
+    Vertex { position: [-0.21918549, -0.44939706, 0.0], texture_coords: [0.28081453, 0.949397], normal: [0.0, 0.0, 1.0], tangent: [1.0, 0.0, 0.0, -1.0], },
+    Vertex { position: [0.35966998, -0.3473291, 0.0], texture_coords: [0.85967, 0.84732914], normal: [0.0, 0.0, 1.0], tangent: [1.0, 0.0, 0.0, -1.0], },
+    Vertex { position: [0.44147372, 0.2347359, 0.0], texture_coords: [0.9414737, 0.2652641], normal: [0.0, 0.0, 1.0], tangent: [1.0, 0.0, 0.0, -1.0], },
 ];
 
 pub const INDICES : &[u16] = &[
@@ -18,24 +15,3 @@ pub const INDICES : &[u16] = &[
     1, 2, 4,
     2, 3, 4,
 ];
-
-impl Vertex {
-    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x2,
-                },
-            ]
-        }
-    }
-}
\ No newline at end of file
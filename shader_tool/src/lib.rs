@@ -0,0 +1,343 @@
+//! Translates the exercise shaders to SPIR-V, GLSL, HLSL and MSL with naga and summarizes what they declare, so you
+//! can see what each backend gets and find out about features that don't translate without running anything.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use naga::back::{glsl, hlsl, msl, spv};
+use naga::common::wgsl::TypeContext;
+use naga::valid::{Capabilities, ModuleInfo, ValidationFlags, Validator};
+use naga::{AddressSpace, Binding, ShaderStage};
+use wgpu_common::Preprocessor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    SpirV,
+    Glsl,
+    Hlsl,
+    Msl,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [Backend::SpirV, Backend::Glsl, Backend::Hlsl, Backend::Msl];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::SpirV => "spv",
+            Backend::Glsl => "glsl",
+            Backend::Hlsl => "hlsl",
+            Backend::Msl => "msl",
+        }
+    }
+
+    pub fn parse(name: &str) -> anyhow::Result<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|backend| backend.name() == name)
+            .ok_or_else(|| anyhow::anyhow!("unknown backend '{}', expected spv, glsl, hlsl or msl", name))
+    }
+}
+
+/// Which backends to translate to and how.
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub backends: Vec<Backend>,
+    /// GLES 3.0 is what wgpu's GL backend (and WebGL2) needs at least, so that is what we check against by default.
+    pub glsl_version: glsl::Version,
+    /// Defines for the preprocessor, as if the shader started with `#define NAME value`.
+    pub defines: Vec<(String, String)>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            backends: Backend::ALL.to_vec(),
+            glsl_version: glsl::Version::new_gles(300),
+            defines: Vec::new(),
+        }
+    }
+}
+
+/// Parses a GLSL version such as `300es`, `310es` or `450` (core).
+pub fn parse_glsl_version(text: &str) -> anyhow::Result<glsl::Version> {
+    let (number, embedded) = match text.strip_suffix("es") {
+        Some(number) => (number, true),
+        None => (text, false),
+    };
+    let number: u16 = number
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("expected a GLSL version such as 300es or 450, got '{}'", text))?;
+
+    // Versions naga can't write are reported by the GLSL writer.
+    Ok(if embedded { glsl::Version::new_gles(number) } else { glsl::Version::Desktop(number) })
+}
+
+/// A preprocessed and validated shader.
+pub struct Shader {
+    pub path: PathBuf,
+    /// The shader and everything it includes.
+    pub files: Vec<PathBuf>,
+    pub module: naga::Module,
+    pub info: ModuleInfo,
+}
+
+impl Shader {
+    /// Reads `path` from disk, runs the preprocessor with `defines` and validates the result.
+    pub fn load(path: &Path, defines: &[(String, String)]) -> anyhow::Result<Self> {
+        let preprocessor = defines
+            .iter()
+            .fold(Preprocessor::default(), |preprocessor, (name, value)| preprocessor.define(name, value));
+        let preprocessed = preprocessor.process_file(path)?;
+        let module = preprocessed.validate()?;
+
+        // Validated above already, this is for the ModuleInfo the backends need.
+        let info = Validator::new(ValidationFlags::all(), Capabilities::all()).validate(&module)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            files: preprocessed.files().to_vec(),
+            module,
+            info,
+        })
+    }
+
+    /// Translates the shader for `backend`. GLSL has one file per entry point, the other backends one in total.
+    pub fn translate(&self, backend: Backend, options: &Options) -> anyhow::Result<Vec<Output>> {
+        // Every backend needs the `override` constants resolved. Use their defaults, like a pipeline without
+        // `constants` would.
+        let (module, info) = naga::back::pipeline_constants::process_overrides(
+            &self.module,
+            &self.info,
+            None,
+            &naga::back::PipelineConstants::default(),
+        )
+        .map_err(|e| anyhow::anyhow!("unable to resolve the override constants: {}", e))?;
+
+        let stem = self.path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("shader");
+
+        match backend {
+            Backend::SpirV => {
+                let words = spv::write_vec(&module, &info, &spv::Options::default(), None)?;
+                let bytes = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+                Ok(vec![Output::new(format!("{}.spv", stem), bytes)])
+            },
+            Backend::Glsl => module
+                .entry_points
+                .iter()
+                .map(|entry_point| {
+                    let glsl_options = glsl::Options {
+                        version: options.glsl_version,
+                        ..Default::default()
+                    };
+                    let pipeline_options = glsl::PipelineOptions {
+                        shader_stage: entry_point.stage,
+                        entry_point: entry_point.name.clone(),
+                        multiview: None,
+                    };
+
+                    let mut source = String::new();
+                    glsl::Writer::new(
+                        &mut source,
+                        &module,
+                        &info,
+                        &glsl_options,
+                        &pipeline_options,
+                        naga::proc::BoundsCheckPolicies::default(),
+                    )
+                    .and_then(|mut writer| writer.write())
+                    .map_err(|e| anyhow::anyhow!("{}: {}", entry_point.name, e))?;
+
+                    let extension = match entry_point.stage {
+                        ShaderStage::Vertex => "vert",
+                        ShaderStage::Fragment => "frag",
+                        _ => "comp",
+                    };
+                    Ok(Output::new(format!("{}.{}.{}", stem, entry_point.name, extension), source.into_bytes()))
+                })
+                .collect(),
+            Backend::Hlsl => {
+                let hlsl_options = hlsl::Options::default();
+                let pipeline_options = hlsl::PipelineOptions::default();
+                let mut source = String::new();
+                hlsl::Writer::new(&mut source, &hlsl_options, &pipeline_options).write(&module, &info, None)?;
+                Ok(vec![Output::new(format!("{}.hlsl", stem), source.into_bytes())])
+            },
+            Backend::Msl => {
+                let msl_options = msl::Options {
+                    lang_version: (2, 0),
+                    ..Default::default()
+                };
+                let (source, _) = msl::write_string(&module, &info, &msl_options, &msl::PipelineOptions::default())?;
+                Ok(vec![Output::new(format!("{}.metal", stem), source.into_bytes())])
+            },
+        }
+    }
+
+    /// The entry points, bindings, vertex inputs and override constants, one per line.
+    pub fn summary(&self) -> String {
+        let module = &self.module;
+        let types = module.to_ctx();
+        let mut summary = String::new();
+
+        writeln!(summary, "entry points:").unwrap();
+        for entry_point in &module.entry_points {
+            match entry_point.stage {
+                ShaderStage::Compute => {
+                    let [x, y, z] = entry_point.workgroup_size;
+                    writeln!(summary, "  compute   {} @workgroup_size({}, {}, {})", entry_point.name, x, y, z)
+                },
+                stage => writeln!(summary, "  {:<9} {}", stage_name(stage), entry_point.name),
+            }
+            .unwrap();
+        }
+
+        writeln!(summary, "bindings:").unwrap();
+        if module.global_variables.iter().all(|(_, global)| global.binding.is_none()) {
+            writeln!(summary, "  none").unwrap();
+        }
+        for (handle, global) in module.global_variables.iter() {
+            let binding = match &global.binding {
+                Some(binding) => binding,
+                None => continue,
+            };
+
+            let used_in: Vec<&str> = module
+                .entry_points
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| !self.info.get_entry_point(index)[handle].is_empty())
+                .map(|(_, entry_point)| entry_point.name.as_str())
+                .collect();
+            let space = match global.space {
+                AddressSpace::Uniform => "var<uniform> ",
+                AddressSpace::Storage { access } if access.contains(naga::StorageAccess::STORE) => {
+                    "var<storage, read_write> "
+                },
+                AddressSpace::Storage { .. } => "var<storage> ",
+                _ => "",
+            };
+            let size = match global.space {
+                AddressSpace::Uniform | AddressSpace::Storage { .. } => {
+                    format!(", {} bytes", module.types[global.ty].inner.size(types))
+                },
+                _ => String::new(),
+            };
+
+            writeln!(
+                summary,
+                "  @group({}) @binding({}) {}: {}{}{} - used by {}",
+                binding.group,
+                binding.binding,
+                global.name.as_deref().unwrap_or("?"),
+                space,
+                types.type_to_string(global.ty),
+                size,
+                if used_in.is_empty() { "nothing".to_string() } else { used_in.join(", ") }
+            )
+            .unwrap();
+        }
+
+        for entry_point in module.entry_points.iter().filter(|ep| ep.stage == ShaderStage::Vertex) {
+            writeln!(summary, "vertex inputs ({}):", entry_point.name).unwrap();
+            for argument in &entry_point.function.arguments {
+                let members = match &module.types[argument.ty].inner {
+                    naga::TypeInner::Struct { members, .. } if argument.binding.is_none() => members
+                        .iter()
+                        .map(|member| (member.name.as_deref(), member.binding.as_ref(), member.ty))
+                        .collect(),
+                    _ => vec![(argument.name.as_deref(), argument.binding.as_ref(), argument.ty)],
+                };
+                for (name, binding, ty) in members {
+                    let binding = match binding {
+                        Some(Binding::Location { location, .. }) => format!("@location({})", location),
+                        Some(Binding::BuiltIn(built_in)) => format!("@builtin({})", snake_case(&format!("{:?}", built_in))),
+                        None => continue,
+                    };
+                    writeln!(summary, "  {} {}: {}", binding, name.unwrap_or("?"), types.type_to_string(ty)).unwrap();
+                }
+            }
+        }
+
+        if !module.overrides.is_empty() {
+            writeln!(summary, "overrides:").unwrap();
+            for (_, constant) in module.overrides.iter() {
+                let default = if constant.init.is_some() { "has a default" } else { "no default, must be set" };
+                writeln!(
+                    summary,
+                    "  {}: {} ({})",
+                    constant.name.as_deref().unwrap_or("?"),
+                    types.type_to_string(constant.ty),
+                    default
+                )
+                .unwrap();
+            }
+        }
+
+        summary
+    }
+}
+
+/// One translated file.
+pub struct Output {
+    pub file_name: String,
+    pub contents: Vec<u8>,
+}
+
+impl Output {
+    fn new(file_name: String, contents: Vec<u8>) -> Self {
+        Self { file_name, contents }
+    }
+}
+
+/// `vertex_index` for `VertexIndex`, which is how WGSL spells the built-ins.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn stage_name(stage: ShaderStage) -> &'static str {
+    match stage {
+        ShaderStage::Vertex => "vertex",
+        ShaderStage::Fragment => "fragment",
+        ShaderStage::Compute => "compute",
+        ShaderStage::Task => "task",
+        ShaderStage::Mesh => "mesh",
+    }
+}
+
+/// Every `shader.wgsl` under `root`, skipping `target` and hidden directories. Snippets that are only ever included
+/// have other names.
+pub fn find_shaders(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut shaders = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        for entry in std::fs::read_dir(&directory)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if path.is_dir() {
+                if name != "target" && !name.starts_with('.') {
+                    directories.push(path);
+                }
+            } else if name == "shader.wgsl" {
+                shaders.push(path);
+            }
+        }
+    }
+
+    shaders.sort();
+    Ok(shaders)
+}
+
+/// The root of the workspace, where `--all` looks for shaders.
+pub fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
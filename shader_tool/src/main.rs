@@ -0,0 +1,135 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use shader_tool::{find_shaders, parse_glsl_version, workspace_root, Backend, Options, Shader};
+
+pub fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "help" || arg == "--help") {
+        print_help();
+        return Ok(());
+    }
+
+    let mut options = Options::default();
+    let mut out = PathBuf::from("target/shaders");
+    let mut write = true;
+    let mut shaders = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => shaders.extend(find_shaders(&workspace_root())?),
+            "--out" => out = PathBuf::from(args.next().ok_or_else(|| anyhow::anyhow!("--out is missing its value"))?),
+            "--summary-only" => write = false,
+            "--backends" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!("--backends is missing its value"))?;
+                options.backends = value.split(',').map(Backend::parse).collect::<anyhow::Result<_>>()?;
+            },
+            "--glsl-version" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!("--glsl-version is missing its value"))?;
+                options.glsl_version = parse_glsl_version(value)?;
+            },
+            "--define" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!("--define is missing its value"))?;
+                let (name, value) = value.split_once('=').unwrap_or((value, ""));
+                options.defines.push((name.to_string(), value.to_string()));
+            },
+            flag if flag.starts_with("--") => anyhow::bail!("unknown option '{}'", flag),
+            path => shaders.push(PathBuf::from(path)),
+        }
+    }
+    anyhow::ensure!(!shaders.is_empty(), "no shaders given, pass a path to a shader.wgsl or --all");
+
+    let mut failures = 0;
+    for path in &shaders {
+        failures += inspect(path, &options, if write { Some(&out) } else { None });
+    }
+    anyhow::ensure!(failures == 0, "{} translation(s) failed", failures);
+    Ok(())
+}
+
+/// Prints the summary of one shader and translates it. Returns how many backends failed.
+fn inspect(path: &Path, options: &Options, out: Option<&PathBuf>) -> usize {
+    println!("{}", path.display());
+
+    let shader = match Shader::load(path, &options.defines) {
+        Ok(shader) => shader,
+        Err(e) => {
+            println!("  FAILED to load: {:#}", e);
+            return 1;
+        },
+    };
+    for include in &shader.files[1..] {
+        println!("  includes {}", include.display());
+    }
+    for line in shader.summary().lines() {
+        println!("  {}", line);
+    }
+
+    // Shaders are named shader.wgsl, so keep the outputs apart by the crate they belong to.
+    let directory = out.map(|out| out.join(crate_name(path)));
+    let mut failures = 0;
+
+    println!("  backends:");
+    for &backend in &options.backends {
+        let written = shader.translate(backend, options).and_then(|outputs| {
+            let names: Vec<String> = outputs.iter().map(|output| output.file_name.clone()).collect();
+            if let Some(directory) = &directory {
+                std::fs::create_dir_all(directory)?;
+                for output in &outputs {
+                    std::fs::write(directory.join(&output.file_name), &output.contents)?;
+                }
+            }
+            Ok(names)
+        });
+
+        match written {
+            Ok(names) => println!("    {:<4} ok      {}", backend.name(), names.join(", ")),
+            Err(e) => {
+                println!("    {:<4} FAILED  {:#}", backend.name(), e);
+                failures += 1;
+            },
+        }
+    }
+    if let Some(directory) = &directory {
+        println!("  written to {}", directory.display());
+    }
+    println!();
+
+    failures
+}
+
+/// `exercise7_instancing` for `learn_wgpu/beginner/exercise7_instancing/src/shader.wgsl`.
+fn crate_name(path: &Path) -> String {
+    let directory = match path.parent() {
+        Some(parent) if parent.ends_with("src") => parent.parent(),
+        parent => parent,
+    };
+    directory
+        .and_then(|directory| directory.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or("shader")
+        .to_string()
+}
+
+fn print_help() {
+    println!("Usage: cargo run -p shader_tool -- [options] <shader.wgsl>... | --all");
+    println!("Translates WGSL to SPIR-V, GLSL, HLSL and MSL with naga and prints the entry points, bindings,");
+    println!("vertex inputs and override constants of each shader.");
+    println!();
+    println!("Options:");
+    println!("  --all                  - Every shader.wgsl in the workspace");
+    println!("  --out <dir>            - Where to write the translations (default target/shaders/<crate>/)");
+    println!("  --summary-only         - Only print the summary, don't write anything");
+    println!("  --backends <list>      - Comma separated subset of spv,glsl,hlsl,msl (default all)");
+    println!("  --glsl-version <ver>   - GLSL to write, e.g. 300es, 310es or 450 (default 300es, what wgpu's GL backend needs)");
+    println!("  --define NAME[=VALUE]  - Define for the preprocessor, as if the shader started with #define NAME VALUE");
+    println!();
+    println!("Exits with an error if a shader doesn't load or a backend can't translate it.");
+}
@@ -0,0 +1,46 @@
+use shader_tool::{find_shaders, workspace_root, Backend, Options, Shader};
+
+// Every exercise shader has to make it through naga to every backend, GLES 3.0 included, before it ever meets a GPU.
+#[test]
+fn every_shader_translates_to_every_backend() {
+    let shaders = find_shaders(&workspace_root()).unwrap();
+    assert!(shaders.len() >= 10, "only found {:?}", shaders);
+
+    let options = Options::default();
+    let mut failures = Vec::new();
+    for path in &shaders {
+        let shader = Shader::load(path, &options.defines).unwrap();
+        for &backend in &options.backends {
+            match shader.translate(backend, &options) {
+                Ok(outputs) => assert!(outputs.iter().all(|output| !output.contents.is_empty())),
+                Err(e) => failures.push(format!("{} ({}): {:#}", path.display(), backend.name(), e)),
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn summary_lists_entry_points_bindings_and_vertex_inputs() {
+    let path = workspace_root().join("learn_wgpu/beginner/exercise7_instancing/src/shader.wgsl");
+    let shader = Shader::load(&path, &[]).unwrap();
+    let summary = shader.summary();
+
+    for line in [
+        "  vertex    vs_main",
+        "  fragment  fs_main",
+        "  @group(1) @binding(0) camera: var<uniform> CameraUniform, 64 bytes - used by vs_main",
+        "  @group(0) @binding(1) sampler_diffuse: sampler - used by fs_main",
+        "  @location(5) model_matrix_0: vec4<f32>",
+        "  @builtin(vertex_index) vertex_index: u32",
+        "  NATIVE_LINES: bool (has a default)",
+    ] {
+        assert!(summary.lines().any(|l| l == line), "missing '{}' in:\n{}", line, summary);
+    }
+
+    // One GLSL file per entry point.
+    let glsl = shader.translate(Backend::Glsl, &Options::default()).unwrap();
+    let names: Vec<&str> = glsl.iter().map(|output| output.file_name.as_str()).collect();
+    assert_eq!(names, ["shader.vs_main.vert", "shader.fs_main.frag", "shader.fs_wireframe.frag"]);
+}
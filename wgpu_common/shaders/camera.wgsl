@@ -0,0 +1,13 @@
+// The view-projection matrix the learn_wgpu camera (CameraUniform in camera.rs) uploads every frame.
+// #define CAMERA_GROUP before including this to bind it somewhere other than @group(1).
+
+#ifndef CAMERA_GROUP
+#define CAMERA_GROUP 1
+#endif
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(CAMERA_GROUP) @binding(0)
+var<uniform> camera: CameraUniform;
@@ -0,0 +1,28 @@
+// The WGSL side of wgpu_common's Wireframe toggle. A shader that includes this passes barycentric(vertex_index) from
+// vs_main to its fs_wireframe, which only has to return wireframe_coverage(in.barycentric) as the alpha of the edges.
+
+// Corner i of every triangle gets the i-th unit vector, so across the triangle the smallest component tells how close
+// a fragment is to an edge. Only meaningful for non-indexed draws, indexed geometry has to be expanded first.
+fn barycentric(vertex_index: u32) -> vec3<f32> {
+    let corner = vertex_index % 3u;
+    return vec3<f32>(f32(corner == 0u), f32(corner == 1u), f32(corner == 2u));
+}
+
+// Set when the GPU rasterizes the edges itself (PolygonMode::Line), every fragment is on an edge then.
+override NATIVE_LINES: bool = false;
+
+// How much of an edge covers this fragment, fading out over the second pixel. Fragments away from every edge are
+// discarded.
+fn wireframe_coverage(barycentric: vec3<f32>) -> f32 {
+    if NATIVE_LINES {
+        return 1.0;
+    }
+
+    // Distance to each edge in pixels.
+    let distance = barycentric / fwidth(barycentric);
+    let coverage = 1.0 - clamp(min(min(distance.x, distance.y), distance.z) - 0.5, 0.0, 1.0);
+    if coverage <= 0.0 {
+        discard;
+    }
+    return coverage;
+}
@@ -0,0 +1,208 @@
+use std::env;
+use std::fmt;
+
+/// Which adapter to run on, as requested by the user through CLI flags or environment variables.
+///
+/// Anything left unset falls back to what the exercise asked for through [`GpuContextBuilder`](crate::GpuContextBuilder).
+#[derive(Clone, Debug, Default)]
+pub struct AdapterSelection {
+    /// `--backend vulkan,gl` / `WGPU_BACKEND`.
+    pub backends: Option<wgpu::Backends>,
+    /// `--power low|high|none` / `WGPU_POWER_PREF`.
+    pub power_preference: Option<wgpu::PowerPreference>,
+    /// `--adapter <name or index>` / `WGPU_ADAPTER_NAME` or `WGPU_ADAPTER_INDEX`.
+    pub adapter: Option<AdapterChoice>,
+    /// `--fallback-adapter` (or `--backend software`) / `WGPU_FORCE_FALLBACK_ADAPTER=1`.
+    pub force_fallback_adapter: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdapterChoice {
+    /// Case insensitive substring of the adapter name, e.g. "nvidia" or "llvmpipe".
+    Name(String),
+    /// Position in the adapter list for the selected backends, as printed by `web-gpu-exercise1`.
+    Index(usize),
+}
+
+impl fmt::Display for AdapterChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdapterChoice::Name(name) => write!(f, "named '{}'", name),
+            AdapterChoice::Index(index) => write!(f, "with index {}", index),
+        }
+    }
+}
+
+impl AdapterSelection {
+    /// Reads `WGPU_BACKEND`, `WGPU_POWER_PREF`, `WGPU_ADAPTER_NAME`, `WGPU_ADAPTER_INDEX` and
+    /// `WGPU_FORCE_FALLBACK_ADAPTER`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut selection = Self::default();
+
+        if let Ok(value) = env::var("WGPU_BACKEND") {
+            selection.set_backends(&value)?;
+        }
+        if let Ok(value) = env::var("WGPU_POWER_PREF") {
+            selection.power_preference = Some(parse_power_preference(&value)?);
+        }
+        if let Ok(value) = env::var("WGPU_ADAPTER_NAME") {
+            selection.adapter = Some(AdapterChoice::Name(value));
+        }
+        if let Ok(value) = env::var("WGPU_ADAPTER_INDEX") {
+            selection.adapter = Some(parse_index(&value)?);
+        }
+        if let Ok(value) = env::var("WGPU_FORCE_FALLBACK_ADAPTER") {
+            selection.force_fallback_adapter = matches!(value.as_str(), "1" | "true" | "yes");
+        }
+
+        Ok(selection)
+    }
+
+    /// Applies `--backend`, `--power`, `--adapter` and `--fallback-adapter`, taking their value from `args`. Returns
+    /// false for arguments that aren't adapter flags.
+    pub fn apply_flag<I: Iterator<Item = String>>(&mut self, flag: &str, args: &mut I) -> anyhow::Result<bool> {
+        match flag {
+            "--backend" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!("--backend is missing its value"))?;
+                self.set_backends(&value)?;
+            },
+            "--power" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!("--power is missing its value"))?;
+                self.power_preference = Some(parse_power_preference(&value)?);
+            },
+            "--adapter" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!("--adapter is missing its value"))?;
+                self.adapter = Some(match value.parse() {
+                    Ok(index) => AdapterChoice::Index(index),
+                    Err(_) => AdapterChoice::Name(value),
+                });
+            },
+            "--fallback-adapter" => self.force_fallback_adapter = true,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    // Backend lists are comma separated. "software" isn't a wgpu backend, it means "whatever fallback adapter there is",
+    // e.g. llvmpipe (GL) or lavapipe (Vulkan) in containers.
+    fn set_backends(&mut self, value: &str) -> anyhow::Result<()> {
+        let mut backends = wgpu::Backends::empty();
+
+        for name in value.split(',').map(|name| name.trim().to_lowercase()) {
+            backends |= match name.as_str() {
+                "vulkan" | "vk" => wgpu::Backends::VULKAN,
+                "gl" | "gles" | "opengl" => wgpu::Backends::GL,
+                "dx12" | "d3d12" => wgpu::Backends::DX12,
+                "metal" | "mtl" => wgpu::Backends::METAL,
+                "primary" => wgpu::Backends::PRIMARY,
+                "all" => wgpu::Backends::all(),
+                "software" | "fallback" => {
+                    self.force_fallback_adapter = true;
+                    continue;
+                },
+                _ => anyhow::bail!(
+                    "unknown backend '{}', expected one of vulkan, gl, dx12, metal, primary, all or software",
+                    name
+                ),
+            };
+        }
+
+        if !backends.is_empty() {
+            self.backends = Some(backends);
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_power_preference(value: &str) -> anyhow::Result<wgpu::PowerPreference> {
+    match value.to_lowercase().as_str() {
+        "low" => Ok(wgpu::PowerPreference::LowPower),
+        "high" => Ok(wgpu::PowerPreference::HighPerformance),
+        "none" => Ok(wgpu::PowerPreference::None),
+        _ => anyhow::bail!("unknown power preference '{}', expected low, high or none", value),
+    }
+}
+
+fn parse_index(value: &str) -> anyhow::Result<AdapterChoice> {
+    value
+        .parse()
+        .map(AdapterChoice::Index)
+        .map_err(|_| anyhow::anyhow!("WGPU_ADAPTER_INDEX expects a number, got '{}'", value))
+}
+
+/// Picks the adapter for `selection`. An explicit name/index is looked up in the adapter list, everything else goes
+/// through `request_adapter`. Either way the error lists the adapters that do exist.
+pub(crate) async fn select_adapter(
+    instance: &wgpu::Instance,
+    backends: wgpu::Backends,
+    selection: &AdapterSelection,
+    options: &wgpu::RequestAdapterOptions<'_, '_>,
+) -> anyhow::Result<wgpu::Adapter> {
+    // Only mention the backends in errors when the user restricted them.
+    let on_backends = match selection.backends {
+        Some(backends) => {
+            let names: Vec<&str> = backends.iter_names().map(|(name, _)| name).collect();
+            format!(" on {}", names.join("/"))
+        },
+        None => String::new(),
+    };
+
+    let adapter = match selection.adapter.as_ref() {
+        Some(choice) => {
+            let adapters = instance.enumerate_adapters(backends);
+            let found = match choice {
+                AdapterChoice::Name(name) => {
+                    let name = name.to_lowercase();
+                    adapters.into_iter().find(|adapter| adapter.get_info().name.to_lowercase().contains(&name))
+                },
+                AdapterChoice::Index(index) => adapters.into_iter().nth(*index),
+            };
+
+            let adapter = match found {
+                Some(adapter) => adapter,
+                None => anyhow::bail!("no adapter {}{}{}", choice, on_backends, available_adapters(instance, backends)),
+            };
+
+            if let Some(surface) = options.compatible_surface {
+                anyhow::ensure!(
+                    adapter.is_surface_supported(surface),
+                    "adapter '{}' can't present to this window",
+                    adapter.get_info().name
+                );
+            }
+
+            adapter
+        },
+        None => match instance.request_adapter(options).await {
+            Ok(adapter) => adapter,
+            Err(_) => anyhow::bail!(
+                "no {}adapter{}{}{}",
+                if options.force_fallback_adapter { "fallback " } else { "" },
+                on_backends,
+                if options.compatible_surface.is_some() { " can present to this window" } else { "" },
+                available_adapters(instance, backends)
+            ),
+        },
+    };
+
+    let info = adapter.get_info();
+    log::info!("Using adapter '{}' ({:?}, {:?})", info.name, info.backend, info.device_type);
+
+    Ok(adapter)
+}
+
+fn available_adapters(instance: &wgpu::Instance, backends: wgpu::Backends) -> String {
+    let adapters = instance.enumerate_adapters(backends);
+    if adapters.is_empty() {
+        return ". No adapters are available".to_string();
+    }
+
+    let mut list = ". Available adapters:".to_string();
+    for (index, adapter) in adapters.iter().enumerate() {
+        let info = adapter.get_info();
+        list.push_str(&format!("\n  {}: {} ({:?}, {:?})", index, info.name, info.backend, info.device_type));
+    }
+    list
+}
@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use winit::application::ApplicationHandler;
+use winit::event::{KeyEvent, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Window, WindowId};
+
+use crate::adapter::AdapterSelection;
+use crate::exercise::{Exercise, Registry};
+use crate::frame_timer::FrameTimer;
+use crate::headless::run_headless;
+use crate::options::RunOptions;
+use crate::target::Target;
+
+/// How often the windowed loop logs frame times.
+const FRAME_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runs the registered exercises in a single window, starting with `start` (or the first registered exercise).
+///
+/// - ESCAPE closes the window.
+/// - `]` and `[` switch to the next/previous registered exercise without restarting the event loop.
+///
+/// With `--hot-reload` exercises that support it rebuild their pipeline whenever their WGSL file is saved.
+///
+/// How long `render` takes is logged every few seconds, see [`FrameTimer`].
+///
+/// With `--headless` no window is opened. The exercise renders offscreen and its last frame is saved as a PNG.
+pub fn run(registry: Registry, start: Option<&str>, options: RunOptions) -> anyhow::Result<()> {
+    anyhow::ensure!(!registry.is_empty(), "no exercises are registered");
+
+    let current = match start {
+        Some(name) => registry
+            .position(name)
+            .ok_or_else(|| anyhow::anyhow!("unknown exercise '{}'", name))?,
+        None => 0,
+    };
+
+    if let Some(headless) = &options.headless {
+        return run_headless(&registry, current, headless, &options.adapter);
+    }
+
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+
+    let mut app = App::new(registry, current, options.adapter, options.hot_reload, options.pipeline_cache);
+    event_loop.run_app(&mut app)?;
+    Ok(())
+}
+
+pub struct App {
+    registry: Registry,
+    current: usize,
+    adapter: AdapterSelection,
+    hot_reload: bool,
+    pipeline_cache: Option<PathBuf>,
+    window: Option<Arc<Window>>,
+    exercise: Option<Box<dyn Exercise>>,
+    frame_timer: FrameTimer,
+    last_report: Instant,
+}
+
+impl App {
+    pub fn new(
+        registry: Registry,
+        current: usize,
+        adapter: AdapterSelection,
+        hot_reload: bool,
+        pipeline_cache: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            registry,
+            current,
+            adapter,
+            hot_reload,
+            pipeline_cache,
+            window: None,
+            exercise: None,
+            frame_timer: FrameTimer::new(),
+            last_report: Instant::now(),
+        }
+    }
+
+    fn start_exercise(&mut self, index: usize) -> anyhow::Result<()> {
+        let window = self.window.clone().unwrap();
+
+        // Drop the running exercise first so its surface is released before the next one creates its own.
+        self.exercise = None;
+        self.current = index;
+        self.frame_timer = FrameTimer::new();
+        self.last_report = Instant::now();
+
+        let entry = &self.registry.entries()[index];
+        window.set_title(entry.name);
+        log::info!("Starting exercise '{}'", entry.name);
+
+        let target = Target::for_window(window.clone())
+            .with_adapter(self.adapter.clone())
+            .with_hot_reload(self.hot_reload)
+            .with_pipeline_cache(self.pipeline_cache.clone());
+        let exercise = entry.init(target).map_err(|e| e.context(format!("Unable to start exercise '{}'", entry.name)))?;
+        self.exercise = Some(exercise);
+        window.request_redraw();
+        Ok(())
+    }
+
+    fn switch_exercise(&mut self, event_loop: &ActiveEventLoop, forward: bool) {
+        let count = self.registry.len();
+        let next = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+
+        let previous = self.current;
+        if let Err(e) = self.start_exercise(next) {
+            log::error!("{:#}", e);
+            // The previous exercise was already dropped to release its surface, bring it back instead of closing.
+            if let Err(e) = self.start_exercise(previous) {
+                log::error!("{:#}", e);
+                event_loop.exit();
+            }
+        }
+    }
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+
+        let window_attributes = Window::default_attributes();
+        self.window = Some(Arc::new(event_loop.create_window(window_attributes).unwrap()));
+        if let Err(e) = self.start_exercise(self.current) {
+            log::error!("{:#}", e);
+            event_loop.exit();
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        let (window, exercise) = match (self.window.as_ref(), self.exercise.as_mut()) {
+            (Some(window), Some(exercise)) => (window, exercise),
+            _ => return,
+        };
+
+        if exercise.input(&event) {
+            return;
+        }
+
+        match event {
+            WindowEvent::CloseRequested => {
+                event_loop.exit();
+            },
+            WindowEvent::Resized(size) => {
+                exercise.resize(size.width, size.height);
+            },
+            WindowEvent::RedrawRequested => {
+                exercise.update();
+
+                match self.frame_timer.time(|| exercise.render()) {
+                    Ok(_) => {},
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        let size = window.inner_size();
+                        exercise.resize(size.width, size.height);
+                    },
+                    Err(e) => {
+                        log::error!("Unable to render {}", e);
+                    }
+                }
+
+                if self.last_report.elapsed() >= FRAME_REPORT_INTERVAL {
+                    log::info!("'{}' render time: {}", self.registry.entries()[self.current].name, self.frame_timer.stats());
+                    self.frame_timer.reset_window();
+                    self.last_report = Instant::now();
+                }
+
+                // winit only draws one frame unless the window is resized or we ask for another one.
+                window.request_redraw();
+            },
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    physical_key: PhysicalKey::Code(code),
+                    state: key_state,
+                    repeat: false,
+                    ..
+                },
+                ..
+            } if key_state.is_pressed() => match code {
+                KeyCode::Escape => event_loop.exit(),
+                KeyCode::BracketRight => self.switch_exercise(event_loop, true),
+                KeyCode::BracketLeft => self.switch_exercise(event_loop, false),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
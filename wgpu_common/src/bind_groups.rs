@@ -0,0 +1,506 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::Path;
+
+use naga::{AddressSpace, ImageClass, ImageDimension, ScalarKind, ShaderStage, StorageAccess, StorageFormat, TypeInner};
+use wgpu::{BindGroupLayoutEntry, BindingType, BufferBindingType, SamplerBindingType, ShaderStages, TextureSampleType};
+
+use crate::preprocess::Preprocessor;
+
+/// The bind group layouts a shader expects, reflected from its `@group(N) @binding(M)` declarations.
+///
+/// Types, sample types, storage access and minimum buffer sizes come from the declarations, visibility from the entry
+/// points that use each binding. Float textures are filterable when some entry point samples them, the same rule
+/// wgpu uses for `layout: None`.
+///
+/// ```text
+/// let bind_groups = BindGroupLayouts::from_wgsl(path, include_str!("shader.wgsl"))?;
+/// let texture_bind_group_layout = bind_groups.create(&context.device, 0, "texture_bind_group_layout")?;
+///
+/// // Or keep the handwritten layout and make sure it agrees with the shader:
+/// for problem in bind_groups.check(0, &entries) { log::warn!("{}", problem); }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BindGroupLayouts {
+    groups: BTreeMap<u32, Vec<BindGroupLayoutEntry>>,
+}
+
+impl BindGroupLayouts {
+    /// Preprocesses (with the default [`Preprocessor`]), parses and validates `source` and reflects its bind groups.
+    /// `path` is only used in messages.
+    pub fn from_wgsl(path: impl AsRef<Path>, source: &str) -> anyhow::Result<Self> {
+        let module = Preprocessor::default().process(path, source)?.validate()?;
+        Self::reflect(&module)
+    }
+
+    pub fn reflect(module: &naga::Module) -> anyhow::Result<Self> {
+        // The module was validated when it was parsed, this only recomputes which entry point uses which global.
+        let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all());
+        let info = validator.validate(module)?;
+
+        let mut groups: BTreeMap<u32, Vec<BindGroupLayoutEntry>> = BTreeMap::new();
+        for (handle, global) in module.global_variables.iter() {
+            let binding = match &global.binding {
+                Some(binding) => binding,
+                None => continue,
+            };
+
+            let mut visibility = ShaderStages::NONE;
+            let mut sampled = false;
+            for (index, entry_point) in module.entry_points.iter().enumerate() {
+                let function = info.get_entry_point(index);
+                if !function[handle].is_empty() {
+                    visibility |= stage(entry_point.stage);
+                }
+                sampled |= function.sampling_set.iter().any(|key| key.image == handle);
+            }
+
+            let (inner, count) = match module.types[global.ty].inner {
+                TypeInner::BindingArray { base, size } => (&module.types[base].inner, Some(binding_array_count(size)?)),
+                ref inner => (inner, None),
+            };
+            let name = global.name.as_deref().unwrap_or("?");
+            let ty = binding_type(module, global.space, inner, sampled).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "@group({}) @binding({}) '{}' is not a buffer, texture or sampler",
+                    binding.group,
+                    binding.binding,
+                    name
+                )
+            })?;
+
+            groups.entry(binding.group).or_default().push(BindGroupLayoutEntry {
+                binding: binding.binding,
+                visibility,
+                ty,
+                count,
+            });
+        }
+
+        for entries in groups.values_mut() {
+            entries.sort_by_key(|entry| entry.binding);
+        }
+        Ok(Self { groups })
+    }
+
+    /// The groups the shader declares bindings in, in ascending order.
+    pub fn groups(&self) -> impl Iterator<Item = u32> + '_ {
+        self.groups.keys().copied()
+    }
+
+    /// The entries of `group`, sorted by binding. Empty if the shader declares nothing in it.
+    pub fn entries(&self, group: u32) -> &[BindGroupLayoutEntry] {
+        self.groups.get(&group).map_or(&[], Vec::as_slice)
+    }
+
+    /// Creates the layout of `group` straight from the shader.
+    pub fn create(&self, device: &wgpu::Device, group: u32, label: &str) -> anyhow::Result<wgpu::BindGroupLayout> {
+        anyhow::ensure!(self.groups.contains_key(&group), "the shader declares no bindings in @group({})", group);
+
+        Ok(device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries: self.entries(group),
+        }))
+    }
+
+    /// Compares a handwritten layout for `group` with what the shader declares, in binding order.
+    pub fn check(&self, group: u32, layout: &[BindGroupLayoutEntry]) -> Vec<BindingProblem> {
+        let mut problems = Vec::new();
+        let layout: BTreeMap<u32, &BindGroupLayoutEntry> = layout.iter().map(|entry| (entry.binding, entry)).collect();
+
+        for expected in self.entries(group) {
+            let binding = expected.binding;
+            let entry = match layout.get(&binding) {
+                Some(entry) => entry,
+                // Declared but not used by any entry point, wgpu doesn't look for it.
+                None if expected.visibility.is_empty() => continue,
+                None => {
+                    problems.push(BindingProblem::MissingBinding {
+                        group,
+                        binding,
+                        shader: expected.ty,
+                    });
+                    continue;
+                },
+            };
+
+            if !compatible(&expected.ty, &entry.ty) {
+                problems.push(BindingProblem::TypeMismatch {
+                    group,
+                    binding,
+                    shader: expected.ty,
+                    layout: entry.ty,
+                });
+            } else if let Some((shader_size, min_binding_size)) = too_small(&expected.ty, &entry.ty) {
+                problems.push(BindingProblem::BufferTooSmall {
+                    group,
+                    binding,
+                    shader_size,
+                    min_binding_size,
+                });
+            }
+            if expected.count != entry.count {
+                problems.push(BindingProblem::CountMismatch {
+                    group,
+                    binding,
+                    shader: expected.count,
+                    layout: entry.count,
+                });
+            }
+            if !entry.visibility.contains(expected.visibility) {
+                problems.push(BindingProblem::MissingVisibility {
+                    group,
+                    binding,
+                    used_in: expected.visibility,
+                    visibility: entry.visibility,
+                });
+            }
+        }
+
+        for &binding in layout.keys() {
+            if !self.entries(group).iter().any(|expected| expected.binding == binding) {
+                problems.push(BindingProblem::UnusedBinding { group, binding });
+            }
+        }
+
+        problems.sort_by_key(BindingProblem::binding);
+        problems
+    }
+}
+
+/// Something a handwritten bind group layout and the shader disagree on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindingProblem {
+    /// The shader uses a binding the layout doesn't have. wgpu rejects the pipeline.
+    MissingBinding { group: u32, binding: u32, shader: BindingType },
+    /// The layout has the binding as a different kind of resource, sample type or view dimension.
+    TypeMismatch { group: u32, binding: u32, shader: BindingType, layout: BindingType },
+    /// The layout's `min_binding_size` is smaller than the type the shader declares.
+    BufferTooSmall { group: u32, binding: u32, shader_size: u64, min_binding_size: u64 },
+    /// One side is a binding array and the other isn't, or they differ in length.
+    CountMismatch { group: u32, binding: u32, shader: Option<NonZeroU32>, layout: Option<NonZeroU32> },
+    /// The binding isn't visible in every stage that uses it.
+    MissingVisibility { group: u32, binding: u32, used_in: ShaderStages, visibility: ShaderStages },
+    /// The layout has a binding the shader doesn't declare. Allowed, but usually a typo or leftover.
+    UnusedBinding { group: u32, binding: u32 },
+}
+
+impl BindingProblem {
+    /// Whether wgpu would refuse to build the pipeline, as opposed to a warning such as an unused binding.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, BindingProblem::UnusedBinding { .. })
+    }
+
+    fn binding(&self) -> u32 {
+        match *self {
+            BindingProblem::MissingBinding { binding, .. }
+            | BindingProblem::TypeMismatch { binding, .. }
+            | BindingProblem::BufferTooSmall { binding, .. }
+            | BindingProblem::CountMismatch { binding, .. }
+            | BindingProblem::MissingVisibility { binding, .. }
+            | BindingProblem::UnusedBinding { binding, .. } => binding,
+        }
+    }
+}
+
+impl fmt::Display for BindingProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingProblem::MissingBinding { group, binding, shader } => write!(
+                f,
+                "@group({}) @binding({}): the shader uses a {} but the layout has no such binding",
+                group,
+                binding,
+                describe(shader)
+            ),
+            BindingProblem::TypeMismatch {
+                group,
+                binding,
+                shader,
+                layout,
+            } => write!(
+                f,
+                "@group({}) @binding({}): the shader declares a {} but the layout has a {}",
+                group,
+                binding,
+                describe(shader),
+                describe(layout)
+            ),
+            BindingProblem::BufferTooSmall {
+                group,
+                binding,
+                shader_size,
+                min_binding_size,
+            } => write!(
+                f,
+                "@group({}) @binding({}): the shader reads {} bytes but the layout's min_binding_size is {}",
+                group, binding, shader_size, min_binding_size
+            ),
+            BindingProblem::CountMismatch {
+                group,
+                binding,
+                shader,
+                layout,
+            } => write!(
+                f,
+                "@group({}) @binding({}): the shader declares {} but the layout has {}",
+                group,
+                binding,
+                describe_count(*shader),
+                describe_count(*layout)
+            ),
+            BindingProblem::MissingVisibility {
+                group,
+                binding,
+                used_in,
+                visibility,
+            } => write!(
+                f,
+                "@group({}) @binding({}): used in {} but only visible in {}",
+                group,
+                binding,
+                describe_stages(*used_in),
+                describe_stages(*visibility)
+            ),
+            BindingProblem::UnusedBinding { group, binding } => write!(
+                f,
+                "@group({}) @binding({}): the layout has a binding the shader doesn't declare",
+                group, binding
+            ),
+        }
+    }
+}
+
+fn stage(stage: ShaderStage) -> ShaderStages {
+    match stage {
+        ShaderStage::Vertex => ShaderStages::VERTEX,
+        ShaderStage::Fragment => ShaderStages::FRAGMENT,
+        ShaderStage::Compute => ShaderStages::COMPUTE,
+        ShaderStage::Task => ShaderStages::TASK,
+        ShaderStage::Mesh => ShaderStages::MESH,
+    }
+}
+
+fn binding_array_count(size: naga::ArraySize) -> anyhow::Result<NonZeroU32> {
+    match size {
+        naga::ArraySize::Constant(count) => Ok(count),
+        _ => anyhow::bail!("binding arrays need a constant length to be reflected"),
+    }
+}
+
+/// The binding type for a global in `space`, or `None` for resources the exercises have no use for.
+fn binding_type(module: &naga::Module, space: AddressSpace, inner: &TypeInner, sampled: bool) -> Option<BindingType> {
+    let buffer = |ty| BindingType::Buffer {
+        ty,
+        has_dynamic_offset: false,
+        min_binding_size: NonZeroU64::new(inner.size(module.to_ctx()) as u64),
+    };
+
+    match (space, inner) {
+        (AddressSpace::Uniform, _) => Some(buffer(BufferBindingType::Uniform)),
+        (AddressSpace::Storage { access }, _) => Some(buffer(BufferBindingType::Storage {
+            read_only: !access.contains(StorageAccess::STORE),
+        })),
+        (AddressSpace::Handle, TypeInner::Sampler { comparison: true }) => {
+            Some(BindingType::Sampler(SamplerBindingType::Comparison))
+        },
+        (AddressSpace::Handle, TypeInner::Sampler { comparison: false }) => {
+            Some(BindingType::Sampler(SamplerBindingType::Filtering))
+        },
+        (AddressSpace::Handle, &TypeInner::Image { dim, arrayed, class }) => {
+            let view_dimension = view_dimension(dim, arrayed);
+            Some(match class {
+                ImageClass::Sampled { kind, multi } => BindingType::Texture {
+                    sample_type: match kind {
+                        ScalarKind::Sint => TextureSampleType::Sint,
+                        ScalarKind::Uint => TextureSampleType::Uint,
+                        _ => TextureSampleType::Float { filterable: sampled },
+                    },
+                    view_dimension,
+                    multisampled: multi,
+                },
+                ImageClass::Depth { multi } => BindingType::Texture {
+                    sample_type: TextureSampleType::Depth,
+                    view_dimension,
+                    multisampled: multi,
+                },
+                ImageClass::Storage { format, access } => BindingType::StorageTexture {
+                    access: if access.contains(StorageAccess::ATOMIC) {
+                        wgpu::StorageTextureAccess::Atomic
+                    } else if access.contains(StorageAccess::LOAD | StorageAccess::STORE) {
+                        wgpu::StorageTextureAccess::ReadWrite
+                    } else if access.contains(StorageAccess::STORE) {
+                        wgpu::StorageTextureAccess::WriteOnly
+                    } else {
+                        wgpu::StorageTextureAccess::ReadOnly
+                    },
+                    format: storage_format(format),
+                    view_dimension,
+                },
+            })
+        },
+        _ => None,
+    }
+}
+
+fn view_dimension(dim: ImageDimension, arrayed: bool) -> wgpu::TextureViewDimension {
+    match (dim, arrayed) {
+        (ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+        (ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+        (ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+    }
+}
+
+fn storage_format(format: StorageFormat) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat as Tf;
+
+    match format {
+        StorageFormat::R8Unorm => Tf::R8Unorm,
+        StorageFormat::R8Snorm => Tf::R8Snorm,
+        StorageFormat::R8Uint => Tf::R8Uint,
+        StorageFormat::R8Sint => Tf::R8Sint,
+        StorageFormat::R16Uint => Tf::R16Uint,
+        StorageFormat::R16Sint => Tf::R16Sint,
+        StorageFormat::R16Float => Tf::R16Float,
+        StorageFormat::Rg8Unorm => Tf::Rg8Unorm,
+        StorageFormat::Rg8Snorm => Tf::Rg8Snorm,
+        StorageFormat::Rg8Uint => Tf::Rg8Uint,
+        StorageFormat::Rg8Sint => Tf::Rg8Sint,
+        StorageFormat::R32Uint => Tf::R32Uint,
+        StorageFormat::R32Sint => Tf::R32Sint,
+        StorageFormat::R32Float => Tf::R32Float,
+        StorageFormat::Rg16Uint => Tf::Rg16Uint,
+        StorageFormat::Rg16Sint => Tf::Rg16Sint,
+        StorageFormat::Rg16Float => Tf::Rg16Float,
+        StorageFormat::Rgba8Unorm => Tf::Rgba8Unorm,
+        StorageFormat::Rgba8Snorm => Tf::Rgba8Snorm,
+        StorageFormat::Rgba8Uint => Tf::Rgba8Uint,
+        StorageFormat::Rgba8Sint => Tf::Rgba8Sint,
+        StorageFormat::Bgra8Unorm => Tf::Bgra8Unorm,
+        StorageFormat::Rgb10a2Uint => Tf::Rgb10a2Uint,
+        StorageFormat::Rgb10a2Unorm => Tf::Rgb10a2Unorm,
+        StorageFormat::Rg11b10Ufloat => Tf::Rg11b10Ufloat,
+        StorageFormat::R64Uint => Tf::R64Uint,
+        StorageFormat::Rg32Uint => Tf::Rg32Uint,
+        StorageFormat::Rg32Sint => Tf::Rg32Sint,
+        StorageFormat::Rg32Float => Tf::Rg32Float,
+        StorageFormat::Rgba16Uint => Tf::Rgba16Uint,
+        StorageFormat::Rgba16Sint => Tf::Rgba16Sint,
+        StorageFormat::Rgba16Float => Tf::Rgba16Float,
+        StorageFormat::Rgba32Uint => Tf::Rgba32Uint,
+        StorageFormat::Rgba32Sint => Tf::Rgba32Sint,
+        StorageFormat::Rgba32Float => Tf::Rgba32Float,
+        StorageFormat::R16Unorm => Tf::R16Unorm,
+        StorageFormat::R16Snorm => Tf::R16Snorm,
+        StorageFormat::Rg16Unorm => Tf::Rg16Unorm,
+        StorageFormat::Rg16Snorm => Tf::Rg16Snorm,
+        StorageFormat::Rgba16Unorm => Tf::Rgba16Unorm,
+        StorageFormat::Rgba16Snorm => Tf::Rgba16Snorm,
+    }
+}
+
+/// Whether a layout entry of type `layout` can be bound where the shader declares `shader`. Whether a float texture
+/// is filterable and which kind of non-comparison sampler is used are up to the layout.
+fn compatible(shader: &BindingType, layout: &BindingType) -> bool {
+    match (shader, layout) {
+        (BindingType::Buffer { ty: shader, .. }, BindingType::Buffer { ty: layout, .. }) => shader == layout,
+        (BindingType::Sampler(SamplerBindingType::Comparison), BindingType::Sampler(layout)) => {
+            *layout == SamplerBindingType::Comparison
+        },
+        (BindingType::Sampler(_), BindingType::Sampler(layout)) => *layout != SamplerBindingType::Comparison,
+        (
+            BindingType::Texture {
+                sample_type: shader_sample,
+                view_dimension: shader_dimension,
+                multisampled: shader_multisampled,
+            },
+            BindingType::Texture {
+                sample_type: layout_sample,
+                view_dimension: layout_dimension,
+                multisampled: layout_multisampled,
+            },
+        ) => {
+            let same_sample_type = match (shader_sample, layout_sample) {
+                (TextureSampleType::Float { .. }, TextureSampleType::Float { .. }) => true,
+                (a, b) => a == b,
+            };
+            same_sample_type && shader_dimension == layout_dimension && shader_multisampled == layout_multisampled
+        },
+        (BindingType::StorageTexture { .. }, BindingType::StorageTexture { .. }) => shader == layout,
+        _ => false,
+    }
+}
+
+/// The declared size and the layout's `min_binding_size` when the latter is too small for the former.
+fn too_small(shader: &BindingType, layout: &BindingType) -> Option<(u64, u64)> {
+    match (shader, layout) {
+        (
+            BindingType::Buffer {
+                min_binding_size: Some(shader_size),
+                ..
+            },
+            BindingType::Buffer {
+                min_binding_size: Some(layout_size),
+                ..
+            },
+        ) if layout_size < shader_size => Some((shader_size.get(), layout_size.get())),
+        _ => None,
+    }
+}
+
+fn describe(ty: &BindingType) -> String {
+    match *ty {
+        BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            ..
+        } => "uniform buffer".to_string(),
+        BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            ..
+        } => "read-only storage buffer".to_string(),
+        BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: false },
+            ..
+        } => "read-write storage buffer".to_string(),
+        BindingType::Sampler(SamplerBindingType::Comparison) => "comparison sampler".to_string(),
+        BindingType::Sampler(_) => "sampler".to_string(),
+        BindingType::Texture {
+            sample_type,
+            view_dimension,
+            multisampled,
+        } => {
+            let sample_type = match sample_type {
+                TextureSampleType::Float { .. } => "float",
+                TextureSampleType::Sint => "sint",
+                TextureSampleType::Uint => "uint",
+                TextureSampleType::Depth => "depth",
+            };
+            let multisampled = if multisampled { "multisampled " } else { "" };
+            format!("{}{:?} {} texture", multisampled, view_dimension, sample_type)
+        },
+        BindingType::StorageTexture {
+            access,
+            format,
+            view_dimension,
+        } => format!("{:?} {:?} storage texture ({:?})", view_dimension, format, access),
+        ref other => format!("{:?}", other),
+    }
+}
+
+fn describe_stages(stages: ShaderStages) -> String {
+    if stages.is_empty() {
+        return "no stage".to_string();
+    }
+    stages.iter_names().map(|(name, _)| name).collect::<Vec<_>>().join(" | ")
+}
+
+fn describe_count(count: Option<NonZeroU32>) -> String {
+    match count {
+        Some(count) => format!("a binding array of {}", count),
+        None => "a single binding".to_string(),
+    }
+}
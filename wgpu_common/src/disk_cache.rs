@@ -0,0 +1,292 @@
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+/// Starts every cache file, so anything else in the directory is recognized as not ours.
+const MAGIC: &[u8; 8] = b"WGPUEXPC";
+
+/// Bump whenever the header changes. Files with another version are discarded rather than misread.
+pub(crate) const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// A `wgpu::PipelineCache` that is loaded from disk when the context is created and written back when it is dropped,
+/// so the driver doesn't have to compile every pipeline from scratch on each run.
+///
+/// Only backends with a driver side cache support it (Vulkan at the moment), and only when the device was created
+/// with `Features::PIPELINE_CACHE`. [`GpuContextBuilder`](crate::GpuContextBuilder) takes care of both when the
+/// [`Target`](crate::Target) has a cache directory.
+///
+/// Each adapter gets its own file, named by `wgpu::util::pipeline_cache_key`. The file also records the adapter name
+/// and driver version it was written by, and a checksum of the data. A file that is truncated, corrupt or was written
+/// by another driver is deleted and the cache starts out empty.
+pub struct DiskPipelineCache {
+    cache: wgpu::PipelineCache,
+    path: PathBuf,
+    identity: String,
+    status: CacheStatus,
+    /// Checksum of what is on disk, so unchanged data isn't written again.
+    saved_checksum: Option<u64>,
+}
+
+/// What happened to the cache file when the cache was opened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The file was there and `bytes` of pipeline data were handed to the driver.
+    Hit { bytes: usize },
+    /// There was no cache file yet, e.g. on the first run or after a driver update.
+    Miss,
+    /// There was a file, but it couldn't be used and was deleted.
+    Discarded(CacheFileError),
+}
+
+/// Why a cache file was discarded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheFileError {
+    /// The file doesn't start with the cache header.
+    NotACacheFile,
+    UnsupportedVersion(u32),
+    /// The file ends before the header or the data it announces.
+    Truncated,
+    /// The file was written for a different adapter or driver version.
+    OtherAdapter { found: String },
+    ChecksumMismatch,
+    /// The file exists but couldn't be read.
+    Unreadable(String),
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheStatus::Hit { bytes } => write!(f, "hit, loaded {} bytes", bytes),
+            CacheStatus::Miss => write!(f, "miss, no cache file yet"),
+            CacheStatus::Discarded(error) => write!(f, "miss, discarded the cache file: {}", error),
+        }
+    }
+}
+
+impl fmt::Display for CacheFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheFileError::NotACacheFile => write!(f, "not a pipeline cache file"),
+            CacheFileError::UnsupportedVersion(version) => write!(
+                f,
+                "written in format version {}, this build reads version {}",
+                version, CACHE_FORMAT_VERSION
+            ),
+            CacheFileError::Truncated => write!(f, "the file is truncated"),
+            CacheFileError::OtherAdapter { found } => write!(f, "written by another adapter or driver ({})", found),
+            CacheFileError::ChecksumMismatch => write!(f, "the checksum doesn't match, the data is corrupt"),
+            CacheFileError::Unreadable(error) => write!(f, "unable to read it: {}", error),
+        }
+    }
+}
+
+impl DiskPipelineCache {
+    /// Loads the cache file for the adapter from `directory`, or starts an empty cache. Returns `None` when the
+    /// device can't use pipeline caches. Either way the outcome is logged.
+    pub fn open(device: &wgpu::Device, info: &wgpu::AdapterInfo, directory: &Path) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            log::info!(
+                "Pipeline cache: not supported on {} ({:?}), pipelines are compiled from scratch",
+                info.name,
+                info.backend
+            );
+            return None;
+        }
+        let key = match wgpu::util::pipeline_cache_key(info) {
+            Some(key) => key,
+            None => {
+                log::info!(
+                    "Pipeline cache: wgpu has no cache key for {:?} adapters, pipelines are compiled from scratch",
+                    info.backend
+                );
+                return None;
+            },
+        };
+
+        let path = directory.join(format!("{}.bin", key));
+        let identity = Self::identity(info);
+        let (data, status) = match fs::read(&path) {
+            Ok(bytes) => match Self::decode(&bytes, &identity) {
+                Ok(data) => (Some(data.to_vec()), CacheStatus::Hit { bytes: data.len() }),
+                Err(error) => (None, CacheStatus::Discarded(error)),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (None, CacheStatus::Miss),
+            Err(e) => (None, CacheStatus::Discarded(CacheFileError::Unreadable(e.to_string()))),
+        };
+
+        if let CacheStatus::Discarded(_) = status {
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("Pipeline cache: unable to delete {}: {}", path.display(), e);
+            }
+        }
+        log::info!("Pipeline cache: {} ({})", status, path.display());
+
+        // Safety: the data was written by `get_data` for an adapter with the same cache key and driver, and the
+        // checksum says it arrived unchanged. wgpu checks its own header on top of that and, with `fallback`, starts
+        // an empty cache when the driver still rejects it.
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("Pipeline Cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+        let saved_checksum = data.as_deref().map(checksum);
+
+        Some(Self {
+            cache,
+            path,
+            identity,
+            status,
+            saved_checksum,
+        })
+    }
+
+    /// The cache to pass to `RenderPipelineDescriptor::cache`.
+    pub fn cache(&self) -> &wgpu::PipelineCache {
+        &self.cache
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn status(&self) -> &CacheStatus {
+        &self.status
+    }
+
+    /// Writes the cache to disk if it changed since it was loaded or last saved. Returns whether anything was
+    /// written. The file is replaced in one go, so a crash halfway leaves the old one behind rather than half a file.
+    pub fn save(&mut self) -> anyhow::Result<bool> {
+        let data = match self.cache.get_data() {
+            Some(data) if !data.is_empty() => data,
+            _ => return Ok(false),
+        };
+        let data_checksum = checksum(&data);
+        if self.saved_checksum == Some(data_checksum) {
+            return Ok(false);
+        }
+
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).with_context(|| format!("unable to create {}", directory.display()))?;
+        }
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, Self::encode(&self.identity, &data))
+            .with_context(|| format!("unable to write {}", temporary.display()))?;
+        fs::rename(&temporary, &self.path).with_context(|| format!("unable to replace {}", self.path.display()))?;
+
+        self.saved_checksum = Some(data_checksum);
+        log::info!("Pipeline cache: saved {} bytes to {}", data.len(), self.path.display());
+        Ok(true)
+    }
+
+    /// Where pipeline caches go unless told otherwise: `WGPU_PIPELINE_CACHE_DIR`, or `wgpu-exercises/pipelines` in
+    /// the user's cache directory (`$XDG_CACHE_HOME`, `~/.cache` or `%LOCALAPPDATA%`). `None` when there is none.
+    pub fn default_directory() -> Option<PathBuf> {
+        if let Some(directory) = std::env::var_os("WGPU_PIPELINE_CACHE_DIR") {
+            return Some(PathBuf::from(directory));
+        }
+
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(base.join("wgpu-exercises").join("pipelines"))
+    }
+
+    /// What a cache file has to have been written by to be used: the adapter and the exact driver version, since
+    /// drivers are free to change their cache format between releases.
+    pub fn identity(info: &wgpu::AdapterInfo) -> String {
+        format!(
+            "{} ({:?}, {:04x}:{:04x}), driver {} {}",
+            info.name, info.backend, info.vendor, info.device, info.driver, info.driver_info
+        )
+    }
+
+    /// Lays out a cache file: the magic, format version, identity, data length and checksum, then the data itself.
+    /// All numbers are little endian.
+    pub fn encode(identity: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 24 + identity.len() + data.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&CACHE_FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(identity.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(identity.as_bytes());
+        bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&checksum(data).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    /// Checks a cache file written by [`encode`](DiskPipelineCache::encode) and returns its data if it is intact and
+    /// was written for `identity`.
+    pub fn decode<'a>(bytes: &'a [u8], identity: &str) -> Result<&'a [u8], CacheFileError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(CacheFileError::NotACacheFile);
+        }
+        let version = reader.u32()?;
+        if version != CACHE_FORMAT_VERSION {
+            return Err(CacheFileError::UnsupportedVersion(version));
+        }
+
+        let identity_len = reader.u32()? as usize;
+        let found = reader.take(identity_len)?;
+        if found != identity.as_bytes() {
+            return Err(CacheFileError::OtherAdapter {
+                found: String::from_utf8_lossy(found).into_owned(),
+            });
+        }
+
+        let data_len = reader.u64()?;
+        let expected_checksum = reader.u64()?;
+        let data = usize::try_from(data_len)
+            .map_err(|_| CacheFileError::Truncated)
+            .and_then(|len| reader.take(len))?;
+        if !reader.bytes.is_empty() || checksum(data) != expected_checksum {
+            return Err(CacheFileError::ChecksumMismatch);
+        }
+        Ok(data)
+    }
+}
+
+impl Drop for DiskPipelineCache {
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            log::warn!("Pipeline cache: {:#}", e);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CacheFileError> {
+        if self.bytes.len() < len {
+            return Err(CacheFileError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, CacheFileError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, CacheFileError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+// 64-bit FNV-1a. Only has to catch accidental damage, not tampering.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
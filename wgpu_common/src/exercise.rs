@@ -0,0 +1,102 @@
+use winit::event::WindowEvent;
+
+use crate::gpu_context::GpuContext;
+use crate::layout_check::ShaderLayouts;
+use crate::target::Target;
+
+/// Hooks the shared [`App`](crate::App) drives every frame. Exercises implement this on their existing `State`.
+pub trait Exercise {
+    /// Creates the exercise for a freshly created (or reused) window, or for an offscreen headless target.
+    fn init(target: Target) -> anyhow::Result<Self>
+    where
+        Self: Sized;
+
+    /// Gives the exercise first look at every window event. Returning true marks the event as consumed.
+    fn input(&mut self, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// Called once per frame before `render`.
+    fn update(&mut self) {}
+
+    fn resize(&mut self, width: u32, height: u32);
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError>;
+
+    /// The GPU context the exercise renders with. Headless runs capture their output through it, so exercises that
+    /// never touch the GPU keep the default.
+    fn context(&self) -> Option<&GpuContext> {
+        None
+    }
+}
+
+pub type InitFn = fn(Target) -> anyhow::Result<Box<dyn Exercise>>;
+
+pub struct ExerciseEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    init: InitFn,
+    shader_layouts: Option<ShaderLayouts>,
+}
+
+impl ExerciseEntry {
+    pub fn init(&self, target: Target) -> anyhow::Result<Box<dyn Exercise>> {
+        (self.init)(target)
+    }
+
+    /// Lets `check-layouts` and the layout test compare the exercise's vertex shader with its vertex buffers.
+    pub fn with_shader_layouts(&mut self, layouts: ShaderLayouts) -> &mut Self {
+        self.shader_layouts = Some(layouts);
+        self
+    }
+
+    pub fn shader_layouts(&self) -> Option<&ShaderLayouts> {
+        self.shader_layouts.as_ref()
+    }
+}
+
+/// The list of exercises a binary knows how to launch, in registration order.
+#[derive(Default)]
+pub struct Registry {
+    entries: Vec<ExerciseEntry>,
+}
+
+impl Registry {
+    /// Registers an exercise that is created through [`Exercise::init`].
+    pub fn register<E: Exercise + 'static>(&mut self, name: &'static str, description: &'static str) -> &mut ExerciseEntry {
+        self.register_fn(name, description, init_boxed::<E>)
+    }
+
+    /// Registers an exercise with a custom constructor, e.g. one `State` launched with different settings.
+    pub fn register_fn(&mut self, name: &'static str, description: &'static str, init: InitFn) -> &mut ExerciseEntry {
+        assert!(self.position(name).is_none(), "exercise '{}' is registered twice", name);
+
+        self.entries.push(ExerciseEntry {
+            name,
+            description,
+            init,
+            shader_layouts: None,
+        });
+        self.entries.last_mut().unwrap()
+    }
+
+    pub fn entries(&self) -> &[ExerciseEntry] {
+        &self.entries
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn init_boxed<E: Exercise + 'static>(target: Target) -> anyhow::Result<Box<dyn Exercise>> {
+    Ok(Box::new(E::init(target)?))
+}
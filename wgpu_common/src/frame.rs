@@ -0,0 +1,37 @@
+/// The texture a single frame is rendered into. Either a swapchain image or the context's offscreen texture.
+pub struct Frame {
+    texture: FrameTexture,
+}
+
+enum FrameTexture {
+    Surface(wgpu::SurfaceTexture),
+    Offscreen(wgpu::Texture),
+}
+
+impl Frame {
+    pub(crate) fn surface(surface_texture: wgpu::SurfaceTexture) -> Self {
+        Self {
+            texture: FrameTexture::Surface(surface_texture),
+        }
+    }
+
+    pub(crate) fn offscreen(texture: wgpu::Texture) -> Self {
+        Self {
+            texture: FrameTexture::Offscreen(texture),
+        }
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        match &self.texture {
+            FrameTexture::Surface(surface_texture) => &surface_texture.texture,
+            FrameTexture::Offscreen(texture) => texture,
+        }
+    }
+
+    /// Schedules the frame for presentation. Offscreen frames stay in their texture until they are captured.
+    pub fn present(self) {
+        if let FrameTexture::Surface(surface_texture) = self.texture {
+            surface_texture.present();
+        }
+    }
+}
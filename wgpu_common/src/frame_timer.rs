@@ -0,0 +1,92 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Measures how long `render` takes (CPU side, including submitting to the queue).
+///
+/// The first frame is kept apart from the rest since that is where one-off work like building pipelines shows up.
+#[derive(Default)]
+pub struct FrameTimer {
+    first: Option<Duration>,
+    frames: u32,
+    total: Duration,
+    min: Option<Duration>,
+    max: Duration,
+}
+
+/// Summary of the frames measured by a [`FrameTimer`].
+#[derive(Clone, Copy, Debug)]
+pub struct FrameStats {
+    pub first: Option<Duration>,
+    /// Frames after the first one.
+    pub frames: u32,
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `f` and records how long it took.
+    pub fn time<R, F: FnOnce() -> R>(&mut self, f: F) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record(start.elapsed());
+        result
+    }
+
+    pub fn record(&mut self, elapsed: Duration) {
+        if self.first.is_none() {
+            self.first = Some(elapsed);
+            return;
+        }
+
+        self.frames += 1;
+        self.total += elapsed;
+        self.min = Some(self.min.map_or(elapsed, |min| min.min(elapsed)));
+        self.max = self.max.max(elapsed);
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        FrameStats {
+            first: self.first,
+            frames: self.frames,
+            average: if self.frames > 0 { self.total / self.frames } else { Duration::ZERO },
+            min: self.min.unwrap_or_default(),
+            max: self.max,
+        }
+    }
+
+    /// Starts a new measurement window, keeping the first frame time around.
+    pub fn reset_window(&mut self) {
+        self.frames = 0;
+        self.total = Duration::ZERO;
+        self.min = None;
+        self.max = Duration::ZERO;
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(first) = self.first {
+            write!(f, "first frame {:.3} ms", as_ms(first))?;
+        }
+        if self.frames > 0 {
+            write!(
+                f,
+                ", next {} frame(s) avg {:.3} ms (min {:.3} ms, max {:.3} ms)",
+                self.frames,
+                as_ms(self.average),
+                as_ms(self.min),
+                as_ms(self.max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+use crate::model::{compute_tangents, MeshData, ModelVertex};
+
+// Every shape is centered on the origin with +y up, wound counter-clockwise when seen from outside (what
+// `FrontFace::Ccw` with back-face culling keeps), and has its texture coordinates' origin in the top left like the
+// loaded models. Tangents come from `compute_tangents`, so normal maps work the same on generated and loaded meshes.
+
+/// A cube with sides `size` long. Every face has its own four vertices, so the normals are flat and each face shows
+/// the whole texture, upright when seen from outside with +y up (the top and bottom faces are seen with -z up).
+pub fn cube(size: f32) -> MeshData {
+    // Normal, then the directions in which u grows and v shrinks on that face.
+    const FACES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ];
+
+    let half = size * 0.5;
+    let mut vertices = Vec::with_capacity(24);
+    let mut indices = Vec::with_capacity(36);
+    for (normal, right, up) in FACES {
+        let first = vertices.len() as u32;
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            vertices.push(vertex(
+                [0, 1, 2].map(|axis| half * (normal[axis] + x * right[axis] + y * up[axis])),
+                [(x + 1.0) * 0.5, (1.0 - y) * 0.5],
+                normal,
+            ));
+        }
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+
+    mesh("Cube", vertices, indices)
+}
+
+/// A sphere made of `sectors` slices around the y axis and `stacks` rings from the top to the bottom pole. u goes
+/// once around, starting and ending at -z, and v from the top pole to the bottom one. The first and last column of
+/// vertices are at the same place, so the texture doesn't wrap back across the seam.
+///
+/// Panics if `sectors` is less than 3 or `stacks` less than 2.
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> MeshData {
+    assert!(sectors >= 3 && stacks >= 2, "a UV sphere needs at least 3 sectors and 2 stacks");
+
+    let mut vertices = Vec::with_capacity(((sectors + 1) * (stacks + 1)) as usize);
+    for stack in 0..=stacks {
+        let v = stack as f32 / stacks as f32;
+        let polar = v * PI;
+        for sector in 0..=sectors {
+            let u = sector as f32 / sectors as f32;
+            let normal = sphere_direction(u, polar);
+            vertices.push(vertex(normal.map(|component| component * radius), [u, v], normal));
+        }
+    }
+
+    // The quads touching the poles have two corners in the same place, only their other triangle is kept.
+    let mut indices = Vec::with_capacity((6 * sectors * (stacks - 1)) as usize);
+    for_each_quad(sectors, stacks, |row, [bottom_left, bottom_right, top_right, top_left]| {
+        if row != stacks - 1 {
+            indices.extend([bottom_left, bottom_right, top_right]);
+        }
+        if row != 0 {
+            indices.extend([bottom_left, top_right, top_left]);
+        }
+    });
+
+    mesh("UV Sphere", vertices, indices)
+}
+
+/// A sphere made by splitting every triangle of an icosahedron into four `subdivisions` times, so all its triangles
+/// have about the same size: 20 * 4^`subdivisions` of them. Texture coordinates are mapped like [`uv_sphere`]'s, with
+/// the vertices on the seam and at the poles repeated wherever their triangles need a different u. The triangles
+/// across the seam run from just under 1 to just over it, so sample with `AddressMode::Repeat`.
+pub fn icosphere(radius: f32, subdivisions: u32) -> MeshData {
+    let t = (1.0 + 5.0f32.sqrt()) * 0.5;
+    let mut positions: Vec<[f32; 3]> = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&position| normalize(position))
+    .collect();
+    #[rustfmt::skip]
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Neighbouring triangles share the vertex in the middle of their common edge.
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let [pa, pb] = [positions[a as usize], positions[b as usize]];
+                positions.push(normalize([0, 1, 2].map(|axis| pa[axis] + pb[axis])));
+                positions.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let [ab, bc, ca] = [midpoint(a, b), midpoint(b, c), midpoint(c, a)];
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(triangles.len() * 3);
+    let mut repeated = HashMap::new();
+    for triangle in triangles {
+        let directions = triangle.map(|index| positions[index as usize]);
+        let mut us = directions.map(|[x, _, z]| 0.5 + x.atan2(z) / TAU);
+        // A triangle across the seam would otherwise go back through the whole texture.
+        if us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min) > 0.5 {
+            for u in us.iter_mut().filter(|u| **u < 0.5) {
+                *u += 1.0;
+            }
+        }
+        // Every u is right at the poles, the one in the middle of the opposite edge keeps the texture straight.
+        for corner in 0..3 {
+            if directions[corner][1].abs() > 1.0 - 1e-6 {
+                us[corner] = (us[(corner + 1) % 3] + us[(corner + 2) % 3]) * 0.5;
+            }
+        }
+
+        for (corner, &index) in triangle.iter().enumerate() {
+            let u = us[corner];
+            let index = *repeated.entry((index, u.to_bits())).or_insert_with(|| {
+                let normal = directions[corner];
+                let v = normal[1].clamp(-1.0, 1.0).acos() / PI;
+                vertices.push(vertex(normal.map(|component| component * radius), [u, v], normal));
+                vertices.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    mesh("Icosphere", vertices, indices)
+}
+
+/// A flat grid in the xz plane facing +y, `width` along x and `depth` along z, split into `columns` by `rows` quads.
+/// Seen from above with -z up, the texture is upright and covers the whole plane once.
+///
+/// Panics if `columns` or `rows` is 0.
+pub fn plane(width: f32, depth: f32, columns: u32, rows: u32) -> MeshData {
+    assert!(columns > 0 && rows > 0, "a plane needs at least one column and one row");
+
+    let mut vertices = Vec::with_capacity(((columns + 1) * (rows + 1)) as usize);
+    for row in 0..=rows {
+        let v = row as f32 / rows as f32;
+        for column in 0..=columns {
+            let u = column as f32 / columns as f32;
+            vertices.push(vertex([(u - 0.5) * width, 0.0, (v - 0.5) * depth], [u, v], [0.0, 1.0, 0.0]));
+        }
+    }
+
+    let mut indices = Vec::with_capacity((6 * columns * rows) as usize);
+    for_each_quad(columns, rows, |_, [bottom_left, bottom_right, top_right, top_left]| {
+        indices.extend([bottom_left, bottom_right, top_right, bottom_left, top_right, top_left]);
+    });
+
+    mesh("Plane", vertices, indices)
+}
+
+/// A capped cylinder of `height` along y, with `sectors` flat sides around it. The side is textured like a
+/// [`uv_sphere`]'s belt and each cap shows the texture's inscribed circle.
+///
+/// Panics if `sectors` is less than 3.
+pub fn cylinder(radius: f32, height: f32, sectors: u32) -> MeshData {
+    assert!(sectors >= 3, "a cylinder needs at least 3 sectors");
+
+    let half = height * 0.5;
+    let mut vertices = Vec::new();
+    for (y, v) in [(half, 0.0), (-half, 1.0)] {
+        for sector in 0..=sectors {
+            let u = sector as f32 / sectors as f32;
+            let normal = sphere_direction(u, PI * 0.5);
+            vertices.push(vertex([normal[0] * radius, y, normal[2] * radius], [u, v], normal));
+        }
+    }
+    let mut indices = Vec::new();
+    for_each_quad(sectors, 1, |_, [bottom_left, bottom_right, top_right, top_left]| {
+        indices.extend([bottom_left, bottom_right, top_right, bottom_left, top_right, top_left]);
+    });
+
+    cap(&mut vertices, &mut indices, radius, half, sectors, true);
+    cap(&mut vertices, &mut indices, radius, -half, sectors, false);
+    mesh("Cylinder", vertices, indices)
+}
+
+/// A cone of `height` along y with its tip at the top and a capped base of `sectors` sides. The tip is repeated for
+/// every sector, so each side has a normal of its own there instead of a meaningless average.
+///
+/// Panics if `sectors` is less than 3.
+pub fn cone(radius: f32, height: f32, sectors: u32) -> MeshData {
+    assert!(sectors >= 3, "a cone needs at least 3 sectors");
+
+    let half = height * 0.5;
+    // The side leans in, so its normals lean up by the ratio of the radius to the height.
+    let side_normal = |u: f32| {
+        let outwards = sphere_direction(u, PI * 0.5);
+        normalize([outwards[0] * height, radius, outwards[2] * height])
+    };
+
+    let mut vertices = Vec::new();
+    for sector in 0..=sectors {
+        let u = sector as f32 / sectors as f32;
+        let outwards = sphere_direction(u, PI * 0.5);
+        vertices.push(vertex([outwards[0] * radius, -half, outwards[2] * radius], [u, 1.0], side_normal(u)));
+    }
+    let mut indices = Vec::new();
+    for sector in 0..sectors {
+        let u = (sector as f32 + 0.5) / sectors as f32;
+        vertices.push(vertex([0.0, half, 0.0], [u, 0.0], side_normal(u)));
+        indices.extend([sector, sector + 1, vertices.len() as u32 - 1]);
+    }
+
+    cap(&mut vertices, &mut indices, radius, -half, sectors, false);
+    mesh("Cone", vertices, indices)
+}
+
+/// A ring around the y axis, `major_radius` from the center to the middle of the tube and `minor_radius` thick, made
+/// of `major_segments` by `minor_segments` quads. u goes once around the ring like a [`uv_sphere`]'s and v once
+/// around the tube, starting on the outside and going down first.
+///
+/// Panics if either segment count is less than 3.
+pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> MeshData {
+    assert!(major_segments >= 3 && minor_segments >= 3, "a torus needs at least 3 segments each way");
+
+    let mut vertices = Vec::with_capacity(((major_segments + 1) * (minor_segments + 1)) as usize);
+    for minor in 0..=minor_segments {
+        let v = minor as f32 / minor_segments as f32;
+        let (sin, cos) = (v * TAU).sin_cos();
+        for major in 0..=major_segments {
+            let u = major as f32 / major_segments as f32;
+            let outwards = sphere_direction(u, PI * 0.5);
+            let normal = [outwards[0] * cos, -sin, outwards[2] * cos];
+            let position = [0, 1, 2].map(|axis| outwards[axis] * major_radius + normal[axis] * minor_radius);
+            vertices.push(vertex(position, [u, v], normal));
+        }
+    }
+
+    let mut indices = Vec::with_capacity((6 * major_segments * minor_segments) as usize);
+    for_each_quad(major_segments, minor_segments, |_, [bottom_left, bottom_right, top_right, top_left]| {
+        indices.extend([bottom_left, bottom_right, top_right, bottom_left, top_right, top_left]);
+    });
+
+    mesh("Torus", vertices, indices)
+}
+
+fn vertex(position: [f32; 3], texture_coords: [f32; 2], normal: [f32; 3]) -> ModelVertex {
+    ModelVertex {
+        position,
+        texture_coords,
+        normal,
+        ..Default::default()
+    }
+}
+
+fn mesh(name: &str, mut vertices: Vec<ModelVertex>, indices: Vec<u32>) -> MeshData {
+    compute_tangents(&mut vertices, &indices);
+    MeshData {
+        name: name.to_string(),
+        vertices,
+        indices,
+        material: None,
+    }
+}
+
+/// The unit vector `polar` radians down from +y, `u` of the way around from -z through -x, +z and +x.
+fn sphere_direction(u: f32, polar: f32) -> [f32; 3] {
+    let (sin_polar, cos_polar) = polar.sin_cos();
+    let (sin, cos) = (u * TAU).sin_cos();
+    [-sin * sin_polar, cos_polar, -cos * sin_polar]
+}
+
+/// Calls `quad` with the row and the bottom left, bottom right, top right and top left corners of every quad of a
+/// grid of `columns + 1` vertices per row, rows going down and columns going right as seen from the front.
+fn for_each_quad(columns: u32, rows: u32, mut quad: impl FnMut(u32, [u32; 4])) {
+    let stride = columns + 1;
+    for row in 0..rows {
+        for column in 0..columns {
+            let top_left = row * stride + column;
+            let bottom_left = top_left + stride;
+            quad(row, [bottom_left, bottom_left + 1, top_left + 1, top_left]);
+        }
+    }
+}
+
+/// A flat disc at height `y` facing up or down, fanned around a center vertex.
+fn cap(vertices: &mut Vec<ModelVertex>, indices: &mut Vec<u32>, radius: f32, y: f32, sectors: u32, up: bool) {
+    let normal = [0.0, if up { 1.0 } else { -1.0 }, 0.0];
+    let center = vertices.len() as u32;
+    vertices.push(vertex([0.0, y, 0.0], [0.5, 0.5], normal));
+    for sector in 0..sectors {
+        let [x, _, z] = sphere_direction(sector as f32 / sectors as f32, PI * 0.5);
+        // Seen from above with -z up, or from below with +z up.
+        let v = if up { 0.5 + z * 0.5 } else { 0.5 - z * 0.5 };
+        vertices.push(vertex([x * radius, y, z * radius], [0.5 + x * 0.5, v], normal));
+    }
+    for sector in 0..sectors {
+        let [a, b] = [center + 1 + sector, center + 1 + (sector + 1) % sectors];
+        indices.extend(if up { [center, a, b] } else { [center, b, a] });
+    }
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = (a[0] * a[0] + a[1] * a[1] + a[2] * a[2]).sqrt();
+    a.map(|component| component / length)
+}
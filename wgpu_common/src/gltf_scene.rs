@@ -0,0 +1,360 @@
+use std::convert::TryInto;
+use std::ops::Range;
+use std::path::Path;
+
+use anyhow::Context;
+use gltf::mesh::Mode;
+use wgpu::util::DeviceExt;
+
+use crate::model::{
+    compute_normals, compute_tangents, InstanceMatrix, MaterialData, MeshData, Model, ModelData, ModelVertex,
+    TextureSource,
+};
+
+/// A glTF 2.0 scene on the CPU: every primitive as a mesh, the base color materials, and where the nodes put the
+/// meshes.
+///
+/// glTF meshes can be used by several nodes, so instead of baking the transforms into the vertices each mesh is drawn
+/// once per node, with the node's transform in an instance buffer laid out like [`InstanceMatrix`] (and the
+/// instancing exercise's `InstanceRaw`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneData {
+    /// One mesh per primitive, with `u32` indices, and one material per glTF material.
+    pub model: ModelData,
+    /// For every glTF mesh, the range of `model.meshes` its primitives became.
+    pub meshes: Vec<Range<usize>>,
+    /// The nodes that draw a mesh, parents first.
+    pub nodes: Vec<SceneNode>,
+}
+
+/// A node with a mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneNode {
+    pub name: Option<String>,
+    /// Index into [`SceneData::meshes`].
+    pub mesh: usize,
+    /// Column major, relative to the scene root: the node's own transform after those of all its parents.
+    pub transform: [[f32; 4]; 4],
+}
+
+impl SceneData {
+    /// Loads a `.gltf` (with its external or embedded buffers and images) or `.glb` file and flattens its default
+    /// scene, or the first one when there is no default.
+    pub fn load_gltf(path: &Path) -> anyhow::Result<Self> {
+        let (document, buffers, images) =
+            gltf::import(path).with_context(|| format!("unable to load {}", path.display()))?;
+        Self::from_gltf(&document, &buffers, &images)
+    }
+
+    /// Like [`load_gltf`](SceneData::load_gltf), for a `.glb` or a `.gltf` that only uses embedded data.
+    pub fn parse_gltf(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (document, buffers, images) = gltf::import_slice(bytes)?;
+        Self::from_gltf(&document, &buffers, &images)
+    }
+
+    fn from_gltf(
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+    ) -> anyhow::Result<Self> {
+        let mut scene = SceneData::default();
+
+        for material in document.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let diffuse_texture = match pbr.base_color_texture() {
+                Some(info) => {
+                    if info.tex_coord() != 0 {
+                        log::warn!(
+                            "Material {:?} samples TEXCOORD_{}, only TEXCOORD_0 is loaded",
+                            material.name(),
+                            info.tex_coord()
+                        );
+                    }
+                    let image = &images[info.texture().source().index()];
+                    Some(TextureSource::Image(rgba_image(image)?))
+                },
+                None => None,
+            };
+            scene.model.materials.push(MaterialData {
+                name: material
+                    .name()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Material {}", scene.model.materials.len())),
+                diffuse_color: pbr.base_color_factor(),
+                diffuse_texture,
+            });
+        }
+
+        for mesh in document.meshes() {
+            let first = scene.model.meshes.len();
+            for (index, primitive) in mesh.primitives().enumerate() {
+                let name = format!("{} {}", mesh.name().unwrap_or("Mesh"), index);
+                match primitive_data(&primitive, buffers, name)? {
+                    Some(data) => scene.model.meshes.push(data),
+                    None => log::warn!(
+                        "Skipping a {:?} primitive of mesh {:?}, only triangles are drawn",
+                        primitive.mode(),
+                        mesh.name()
+                    ),
+                }
+            }
+            scene.meshes.push(first..scene.model.meshes.len());
+        }
+
+        let root = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| anyhow::anyhow!("the file has no scenes"))?;
+        let mut pending: Vec<(gltf::Node<'_>, [[f32; 4]; 4])> = root.nodes().map(|node| (node, IDENTITY)).collect();
+        pending.reverse();
+        while let Some((node, parent)) = pending.pop() {
+            let transform = multiply(&parent, &node.transform().matrix());
+            if let Some(mesh) = node.mesh() {
+                scene.nodes.push(SceneNode { name: node.name().map(str::to_string), mesh: mesh.index(), transform });
+            }
+            // Pushed last to first, so the first child is visited next.
+            let children: Vec<_> = node.children().map(|child| (child, transform)).collect();
+            pending.extend(children.into_iter().rev());
+        }
+
+        Ok(scene)
+    }
+
+    /// The transforms of the nodes that draw `mesh`, ready for an instance buffer.
+    pub fn instances(&self, mesh: usize) -> Vec<InstanceMatrix> {
+        self.nodes
+            .iter()
+            .filter(|node| node.mesh == mesh)
+            .map(|node| InstanceMatrix { model: node.transform })
+            .collect()
+    }
+}
+
+const IDENTITY: [[f32; 4]; 4] =
+    [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+// Column major, so `a * b` applies `b` first.
+fn multiply(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut product = [[0.0; 4]; 4];
+    for (column, b_column) in product.iter_mut().zip(b) {
+        for (row, value) in column.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[k][row] * b_column[k]).sum();
+        }
+    }
+    product
+}
+
+/// The vertices and triangle list indices of a primitive, or `None` for points and lines.
+fn primitive_data(
+    primitive: &gltf::Primitive<'_>,
+    buffers: &[gltf::buffer::Data],
+    name: String,
+) -> anyhow::Result<Option<MeshData>> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+    let positions: Vec<[f32; 3]> =
+        reader.read_positions().ok_or_else(|| anyhow::anyhow!("{} has no POSITION attribute", name))?.collect();
+
+    let mut vertices: Vec<ModelVertex> =
+        positions.iter().map(|&position| ModelVertex { position, ..Default::default() }).collect();
+    let normals = reader.read_normals().map(|normals| normals.collect::<Vec<_>>());
+    let tangents = reader.read_tangents().map(|tangents| tangents.collect::<Vec<_>>());
+    if let Some(texture_coords) = reader.read_tex_coords(0) {
+        for (vertex, uv) in vertices.iter_mut().zip(texture_coords.into_f32()) {
+            vertex.texture_coords = uv;
+        }
+    }
+    if let Some(normals) = &normals {
+        for (vertex, &normal) in vertices.iter_mut().zip(normals) {
+            vertex.normal = normal;
+        }
+    }
+    if let Some(tangents) = &tangents {
+        for (vertex, &tangent) in vertices.iter_mut().zip(tangents) {
+            vertex.tangent = tangent;
+        }
+    }
+
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..vertices.len() as u32).collect(),
+    };
+    let indices = match triangle_list(primitive.mode(), &indices) {
+        Some(indices) => indices,
+        None => return Ok(None),
+    };
+    anyhow::ensure!(
+        indices.iter().all(|&index| (index as usize) < vertices.len()),
+        "{} has indices past its {} vertices",
+        name,
+        vertices.len()
+    );
+
+    if normals.is_none() {
+        compute_normals(&mut vertices, &indices);
+    }
+    if tangents.is_none() {
+        compute_tangents(&mut vertices, &indices);
+    }
+
+    Ok(Some(MeshData { name, vertices, indices, material: primitive.material().index() }))
+}
+
+/// Turns strips and fans into lists, keeping every triangle counter-clockwise.
+fn triangle_list(mode: Mode, indices: &[u32]) -> Option<Vec<u32>> {
+    match mode {
+        Mode::Triangles => Some(indices.to_vec()),
+        // Every other triangle of a strip is wound the other way around.
+        Mode::TriangleStrip => Some(
+            (0..indices.len().saturating_sub(2))
+                .flat_map(|i| match i % 2 {
+                    0 => [indices[i], indices[i + 1], indices[i + 2]],
+                    _ => [indices[i + 1], indices[i], indices[i + 2]],
+                })
+                .collect(),
+        ),
+        Mode::TriangleFan => {
+            Some((1..indices.len().saturating_sub(1)).flat_map(|i| [indices[i], indices[i + 1], indices[0]]).collect())
+        },
+        Mode::Points | Mode::Lines | Mode::LineLoop | Mode::LineStrip => None,
+    }
+}
+
+/// glTF images come decoded in whatever channels and depth the file had.
+fn rgba_image(image: &gltf::image::Data) -> anyhow::Result<image::RgbaImage> {
+    use gltf::image::Format;
+
+    let (channels, bytes_per_channel) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    let channel = |bytes: &[u8]| -> u8 {
+        match bytes_per_channel {
+            1 => bytes[0],
+            2 => (u16::from_ne_bytes(bytes.try_into().unwrap()) >> 8) as u8,
+            _ => (f32::from_ne_bytes(bytes.try_into().unwrap()).clamp(0.0, 1.0) * 255.0).round() as u8,
+        }
+    };
+
+    let pixels = image
+        .pixels
+        .chunks_exact(channels * bytes_per_channel)
+        .flat_map(|pixel| {
+            let mut rgba = [0, 0, 0, 255];
+            for (index, bytes) in pixel.chunks_exact(bytes_per_channel).enumerate() {
+                rgba[index] = channel(bytes);
+            }
+            // Grey images repeat their one channel.
+            if channels <= 2 {
+                rgba = [rgba[0], rgba[0], rgba[0], if channels == 2 { rgba[1] } else { 255 }];
+            }
+            rgba
+        })
+        .collect();
+    image::RgbaImage::from_raw(image.width, image.height, pixels)
+        .ok_or_else(|| anyhow::anyhow!("the {}x{} image has the wrong amount of data", image.width, image.height))
+}
+
+/// A glTF scene on the GPU: the meshes and materials as a [`Model`], and for every glTF mesh an instance buffer with
+/// the transforms of the nodes that use it.
+///
+/// ```text
+/// let scene = Scene::load_gltf(&context.device, &context.queue, Path::new("scene.gltf"), &texture_bind_group_layout)?;
+///
+/// renderpass.set_bind_group(1, &camera_bind_group, &[]);
+/// scene.draw(&mut renderpass, 0, 1);
+/// ```
+pub struct Scene {
+    pub model: Model,
+    batches: Vec<Batch>,
+}
+
+struct Batch {
+    meshes: Range<usize>,
+    instance_buffer: wgpu::Buffer,
+    instance_count: u32,
+}
+
+impl Scene {
+    /// Loads a `.gltf` or `.glb` file with [`SceneData::load_gltf`] and uploads it.
+    pub fn load_gltf(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &Path,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let data = SceneData::load_gltf(path)?;
+        let scene = Self::upload(device, queue, &data, texture_layout)?;
+        log::info!(
+            "Loaded {}: {} node(s) drawing {} mesh(es), {} primitive(s), {} material(s)",
+            path.display(),
+            data.nodes.len(),
+            data.meshes.len(),
+            data.model.meshes.len(),
+            data.model.materials.len()
+        );
+        Ok(scene)
+    }
+
+    /// Uploads the meshes and materials like [`Model::upload`], plus one instance buffer per glTF mesh that is used
+    /// by at least one node.
+    pub fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &SceneData,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let batches = data
+            .meshes
+            .iter()
+            .enumerate()
+            .filter_map(|(mesh, meshes)| {
+                let instances = data.instances(mesh);
+                if instances.is_empty() {
+                    return None;
+                }
+                Some(Batch {
+                    meshes: meshes.clone(),
+                    instance_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Scene Instance Buffer"),
+                        contents: bytemuck::cast_slice(&instances),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    }),
+                    instance_count: instances.len() as u32,
+                })
+            })
+            .collect();
+
+        Ok(Self { model: Model::upload(device, queue, &data.model, texture_layout)?, batches })
+    }
+
+    /// Draws every node, binding the node transforms to vertex buffer `instance_slot` and each primitive's material
+    /// to `material_group`. The other bind groups have to be set already.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, material_group: u32, instance_slot: u32) {
+        for batch in &self.batches {
+            render_pass.set_vertex_buffer(instance_slot, batch.instance_buffer.slice(..));
+            for mesh in &self.model.meshes[batch.meshes.clone()] {
+                render_pass.set_bind_group(material_group, &self.model.material(mesh).bind_group, &[]);
+                mesh.draw_instanced(render_pass, 0..batch.instance_count);
+            }
+        }
+    }
+
+    /// Like [`draw`](Scene::draw), but from the deindexed vertices for the barycentric wireframe.
+    pub fn draw_deindexed(&self, render_pass: &mut wgpu::RenderPass<'_>, material_group: u32, instance_slot: u32) {
+        for batch in &self.batches {
+            render_pass.set_vertex_buffer(instance_slot, batch.instance_buffer.slice(..));
+            for mesh in &self.model.meshes[batch.meshes.clone()] {
+                render_pass.set_bind_group(material_group, &self.model.material(mesh).bind_group, &[]);
+                mesh.draw_deindexed_instanced(render_pass, 0..batch.instance_count);
+            }
+        }
+    }
+}
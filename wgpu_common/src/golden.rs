@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::Context;
+use image::{Rgba, RgbaImage};
+
+/// How two pixels are compared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    /// Largest difference of any single RGBA channel, 0-255.
+    Channel,
+    /// Weighted ("redmean") RGB distance, which tracks how different two colors look better than the raw channels.
+    /// Scaled to 0-255 like [`Metric::Channel`].
+    Perceptual,
+}
+
+/// How far a rendered image may drift from its golden before the check fails.
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+    pub metric: Metric,
+    /// Pixels whose difference is above this are counted as mismatched.
+    pub threshold: f32,
+    /// Fraction (0.0-1.0) of mismatched pixels that is still accepted, e.g. for rasterizer differences along edges.
+    pub max_mismatched: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            metric: Metric::Channel,
+            threshold: 2.0,
+            max_mismatched: 0.001,
+        }
+    }
+}
+
+/// Result of comparing a rendered image against its golden.
+pub struct Comparison {
+    pub mismatched: u64,
+    pub total: u64,
+    pub max_difference: f32,
+    /// Mismatched pixels in red on top of a faded copy of the golden.
+    pub diff: RgbaImage,
+}
+
+impl Comparison {
+    pub fn mismatched_fraction(&self) -> f32 {
+        self.mismatched as f32 / self.total.max(1) as f32
+    }
+
+    pub fn passes(&self, tolerance: &Tolerance) -> bool {
+        self.mismatched_fraction() <= tolerance.max_mismatched
+    }
+}
+
+/// Compares two images of the same size pixel by pixel.
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, tolerance: &Tolerance) -> anyhow::Result<Comparison> {
+    anyhow::ensure!(
+        actual.dimensions() == expected.dimensions(),
+        "image is {}x{} but the golden is {}x{}",
+        actual.width(),
+        actual.height(),
+        expected.width(),
+        expected.height()
+    );
+
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    let mut mismatched = 0;
+    let mut max_difference: f32 = 0.0;
+
+    for ((a, e), d) in actual.pixels().zip(expected.pixels()).zip(diff.pixels_mut()) {
+        let difference = pixel_difference(a, e, tolerance.metric);
+        max_difference = max_difference.max(difference);
+
+        *d = if difference > tolerance.threshold {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let luma = (0.299 * e[0] as f32 + 0.587 * e[1] as f32 + 0.114 * e[2] as f32) as u8;
+            let faded = 128 + luma / 2;
+            Rgba([faded, faded, faded, 255])
+        };
+    }
+
+    Ok(Comparison {
+        mismatched,
+        total: u64::from(expected.width()) * u64::from(expected.height()),
+        max_difference,
+        diff,
+    })
+}
+
+fn pixel_difference(a: &Rgba<u8>, b: &Rgba<u8>, metric: Metric) -> f32 {
+    match metric {
+        Metric::Channel => a
+            .0
+            .iter()
+            .zip(b.0.iter())
+            .map(|(a, b)| (*a as i16 - *b as i16).unsigned_abs() as f32)
+            .fold(0.0, f32::max),
+        Metric::Perceptual => {
+            let mean_red = (a[0] as f32 + b[0] as f32) / 2.0;
+            let dr = a[0] as f32 - b[0] as f32;
+            let dg = a[1] as f32 - b[1] as f32;
+            let db = a[2] as f32 - b[2] as f32;
+            let distance = ((2.0 + mean_red / 256.0) * dr * dr + 4.0 * dg * dg + (2.0 + (255.0 - mean_red) / 256.0) * db * db).sqrt();
+            // The largest possible redmean distance is 3 * 255, so this keeps the same 0-255 scale as Channel.
+            distance / 3.0
+        },
+    }
+}
+
+/// What happened when an image was checked against its golden.
+pub enum GoldenOutcome {
+    Passed(Comparison),
+    /// The golden was (re)written from the rendered image.
+    Blessed,
+    /// The image drifted too far. The rendered image and a diff were written next to the golden.
+    Failed {
+        comparison: Comparison,
+        actual_path: PathBuf,
+        diff_path: PathBuf,
+    },
+    /// There is no golden yet and `bless` was not set.
+    Missing,
+}
+
+/// Checks `actual` against the PNG at `golden_path`. With `bless` the golden is overwritten instead, which is how
+/// intentional changes are accepted.
+pub fn check_golden(actual: &RgbaImage, golden_path: &Path, tolerance: &Tolerance, bless: bool) -> anyhow::Result<GoldenOutcome> {
+    if bless {
+        if let Some(parent) = golden_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        save_png(actual, golden_path)?;
+        return Ok(GoldenOutcome::Blessed);
+    }
+
+    if !golden_path.exists() {
+        return Ok(GoldenOutcome::Missing);
+    }
+
+    let expected = image::open(golden_path)
+        .with_context(|| format!("unable to read golden {}", golden_path.display()))?
+        .to_rgba8();
+    let comparison = compare(actual, &expected, tolerance)?;
+
+    if comparison.passes(tolerance) {
+        return Ok(GoldenOutcome::Passed(comparison));
+    }
+
+    let actual_path = golden_path.with_extension("actual.png");
+    let diff_path = golden_path.with_extension("diff.png");
+    save_png(actual, &actual_path)?;
+    save_png(&comparison.diff, &diff_path)?;
+
+    Ok(GoldenOutcome::Failed {
+        comparison,
+        actual_path,
+        diff_path,
+    })
+}
+
+fn save_png(image: &RgbaImage, path: &Path) -> anyhow::Result<()> {
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .with_context(|| format!("unable to write {}", path.display()))
+}
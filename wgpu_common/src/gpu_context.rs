@@ -0,0 +1,360 @@
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::Context;
+use winit::window::Window;
+
+use crate::adapter::select_adapter;
+use crate::capture;
+use crate::disk_cache::DiskPipelineCache;
+use crate::frame::Frame;
+use crate::surface::{negotiate, offscreen_capabilities, FormatPreference, SurfaceChoice, SurfacePreferences};
+use crate::target::Target;
+
+/// Everything an exercise needs to talk to the GPU and present to a window (or an offscreen texture when headless).
+pub struct GpuContext {
+    /// `None` when rendering headless.
+    pub window: Option<Arc<Window>>,
+    presentation: Presentation,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub config: wgpu::SurfaceConfiguration,
+    surface_choice: SurfaceChoice,
+    is_surface_configured: bool,
+    pipeline_cache: Option<DiskPipelineCache>,
+    rebuild_pipelines: bool,
+}
+
+enum Presentation {
+    Surface(wgpu::Surface<'static>),
+    Offscreen(wgpu::Texture),
+}
+
+impl GpuContext {
+    pub fn builder() -> GpuContextBuilder {
+        GpuContextBuilder::default()
+    }
+
+    /// Bootstraps a context with the default builder settings.
+    pub async fn new(target: Target) -> anyhow::Result<Self> {
+        Self::builder().build(target).await
+    }
+
+    /// Reconfigures the surface (or recreates the offscreen texture) for the new size. Zero sized windows (e.g.
+    /// minimized) are ignored because wgpu refuses to configure a surface without any pixels.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.config.width = width;
+            self.config.height = height;
+
+            match &mut self.presentation {
+                Presentation::Surface(surface) => surface.configure(&self.device, &self.config),
+                Presentation::Offscreen(texture) => *texture = create_offscreen_texture(&self.device, &self.config),
+            }
+
+            self.is_surface_configured = true;
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self.presentation, Presentation::Offscreen(_))
+    }
+
+    /// The window surface, or `None` when rendering headless.
+    pub fn surface(&self) -> Option<&wgpu::Surface<'static>> {
+        match &self.presentation {
+            Presentation::Surface(surface) => Some(surface),
+            Presentation::Offscreen(_) => None,
+        }
+    }
+
+    /// Gets the texture to render the next frame into.
+    pub fn acquire_frame(&self) -> Result<Frame, wgpu::SurfaceError> {
+        match &self.presentation {
+            Presentation::Surface(surface) => Ok(Frame::surface(surface.get_current_texture()?)),
+            Presentation::Offscreen(texture) => Ok(Frame::offscreen(texture.clone())),
+        }
+    }
+
+    /// Lets the windowing system know a frame is about to be presented. Does nothing when headless.
+    pub fn pre_present_notify(&self) {
+        if let Some(window) = &self.window {
+            window.pre_present_notify();
+        }
+    }
+
+    /// Reads the last rendered headless frame back from the GPU.
+    pub fn capture(&self) -> anyhow::Result<image::RgbaImage> {
+        match &self.presentation {
+            Presentation::Offscreen(texture) => capture::read_texture(&self.device, &self.queue, texture),
+            Presentation::Surface(_) => anyhow::bail!("only headless contexts can be captured"),
+        }
+    }
+
+    /// Captures the last rendered headless frame and saves it as a PNG.
+    pub fn save_png(&self, path: &Path) -> anyhow::Result<()> {
+        self.capture()?
+            .save_with_format(path, image::ImageFormat::Png)
+            .with_context(|| format!("unable to write {}", path.display()))
+    }
+
+    /// We can't render until the surface has been configured at least once.
+    pub fn is_surface_configured(&self) -> bool {
+        self.is_surface_configured
+    }
+
+    /// The format, present mode and alpha mode the surface was negotiated to, and what was asked for.
+    pub fn surface_choice(&self) -> &SurfaceChoice {
+        &self.surface_choice
+    }
+
+    /// The driver pipeline cache to build pipelines with, when the target asked for one and the adapter supports it.
+    /// Pass it to [`RenderPipelineBuilder::cache`](crate::RenderPipelineBuilder::cache) or
+    /// `RenderPipelineDescriptor::cache`. It is written back to disk when the context is dropped.
+    pub fn pipeline_cache(&self) -> Option<&wgpu::PipelineCache> {
+        self.pipeline_cache.as_ref().map(DiskPipelineCache::cache)
+    }
+
+    /// The on-disk side of [`pipeline_cache`](GpuContext::pipeline_cache), e.g. to see whether it was a hit or to
+    /// save it early.
+    pub fn disk_pipeline_cache(&mut self) -> Option<&mut DiskPipelineCache> {
+        self.pipeline_cache.as_mut()
+    }
+
+    /// Whether the target asked for render pipelines to be built again every frame, see
+    /// [`Target::rebuild_pipelines`].
+    pub fn rebuilds_pipelines(&self) -> bool {
+        self.rebuild_pipelines
+    }
+
+    /// The format render pass views should be created with. This is the sRGB view format when one was requested
+    /// through [`GpuContextBuilder::srgb_view_format`], otherwise the surface format itself.
+    pub fn view_format(&self) -> wgpu::TextureFormat {
+        self.config.view_formats.first().copied().unwrap_or(self.config.format)
+    }
+}
+
+/// Configures how a [`GpuContext`] is bootstrapped.
+///
+/// The defaults match what the learn_wgpu exercises have always done: default instance, an adapter picked without
+/// regard to power usage and no extra features. The surface is negotiated to an sRGB format, Fifo and an opaque alpha
+/// mode, see [`negotiate`]. The [`AdapterSelection`] carried by the [`Target`] overrides the adapter settings, since it
+/// is what the user asked for on the command line.
+///
+/// [`AdapterSelection`]: crate::AdapterSelection
+pub struct GpuContextBuilder {
+    instance_descriptor: wgpu::InstanceDescriptor,
+    power_preference: wgpu::PowerPreference,
+    force_fallback_adapter: bool,
+    device_label: Option<String>,
+    required_features: wgpu::Features,
+    optional_features: wgpu::Features,
+    required_limits: wgpu::Limits,
+    memory_hints: wgpu::MemoryHints,
+    srgb_view_format: bool,
+    surface_preferences: SurfacePreferences,
+    desired_maximum_frame_latency: u32,
+}
+
+impl Default for GpuContextBuilder {
+    fn default() -> Self {
+        Self {
+            instance_descriptor: wgpu::InstanceDescriptor::default(),
+            power_preference: wgpu::PowerPreference::default(),
+            force_fallback_adapter: false,
+            device_label: None,
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+            memory_hints: wgpu::MemoryHints::default(),
+            srgb_view_format: false,
+            surface_preferences: SurfacePreferences::default(),
+            desired_maximum_frame_latency: 2,
+        }
+    }
+}
+
+impl GpuContextBuilder {
+    pub fn instance_descriptor(mut self, instance_descriptor: wgpu::InstanceDescriptor) -> Self {
+        self.instance_descriptor = instance_descriptor;
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn device_label(mut self, label: &str) -> Self {
+        self.device_label = Some(label.to_string());
+        self
+    }
+
+    pub fn required_features(mut self, required_features: wgpu::Features) -> Self {
+        self.required_features = required_features;
+        self
+    }
+
+    /// Features to enable when the adapter has them. Check `device.features()` to see which ones made it.
+    pub fn optional_features(mut self, optional_features: wgpu::Features) -> Self {
+        self.optional_features = optional_features;
+        self
+    }
+
+    pub fn required_limits(mut self, required_limits: wgpu::Limits) -> Self {
+        self.required_limits = required_limits;
+        self
+    }
+
+    pub fn memory_hints(mut self, memory_hints: wgpu::MemoryHints) -> Self {
+        self.memory_hints = memory_hints;
+        self
+    }
+
+    /// Adds the sRGB variant of the surface format as a view format, so render passes can write gamma correct colors
+    /// even when the surface itself is not sRGB.
+    pub fn srgb_view_format(mut self, srgb_view_format: bool) -> Self {
+        self.srgb_view_format = srgb_view_format;
+        self
+    }
+
+    /// Surface format to ask for. Defaults to the first sRGB format the surface supports.
+    pub fn surface_format(mut self, format: FormatPreference) -> Self {
+        self.surface_preferences.format = format;
+        self
+    }
+
+    /// Present mode to configure the surface with. Defaults to Fifo. Unsupported modes fall back to the closest
+    /// supported one, e.g. Mailbox -> Immediate -> Fifo.
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.surface_preferences.present_mode = Some(present_mode);
+        self
+    }
+
+    /// Alpha mode to configure the surface with. Defaults to Opaque when the surface supports it.
+    pub fn alpha_mode(mut self, alpha_mode: wgpu::CompositeAlphaMode) -> Self {
+        self.surface_preferences.alpha_mode = Some(alpha_mode);
+        self
+    }
+
+    pub fn desired_maximum_frame_latency(mut self, desired_maximum_frame_latency: u32) -> Self {
+        self.desired_maximum_frame_latency = desired_maximum_frame_latency;
+        self
+    }
+
+    pub async fn build(self, target: Target) -> anyhow::Result<GpuContext> {
+        let (width, height) = target.size();
+
+        // The instance is a handle to our GPU. It is responsible for creating the adapter and the surface.
+        let selection = &target.adapter;
+        let mut instance_descriptor = self.instance_descriptor;
+        if let Some(backends) = selection.backends {
+            instance_descriptor.backends = backends;
+        }
+        let instance = wgpu::Instance::new(&instance_descriptor);
+        let surface = match target.window() {
+            Some(window) => Some(instance.create_surface(window.clone())?),
+            None => None,
+        };
+
+        // Headless targets default to the fallback adapter so their output doesn't depend on the machine's GPU, unless
+        // the user picked an adapter.
+        let headless_fallback = target.is_headless() && selection.adapter.is_none() && selection.backends.is_none();
+        let request_adapter_options = wgpu::RequestAdapterOptions {
+            power_preference: selection.power_preference.unwrap_or(self.power_preference),
+            compatible_surface: surface.as_ref(),           // find an adapter that can present the supplied surface
+            force_fallback_adapter: self.force_fallback_adapter || selection.force_fallback_adapter || headless_fallback,
+        };
+        let adapter = select_adapter(&instance, instance_descriptor.backends, selection, &request_adapter_options).await?;
+
+        // Pipeline caches are only worth asking for when there is somewhere to keep them.
+        let cache_feature = match target.pipeline_cache {
+            Some(_) => wgpu::Features::PIPELINE_CACHE,
+            None => wgpu::Features::empty(),
+        };
+        let device_descriptor = wgpu::DeviceDescriptor {
+            label: self.device_label.as_deref(),
+            required_features: self.required_features | ((self.optional_features | cache_feature) & adapter.features()),
+            required_limits: self.required_limits,
+            memory_hints: self.memory_hints,
+            trace: wgpu::Trace::Off,
+        };
+        let (device, queue) = adapter.request_device(&device_descriptor).await?;
+
+        let pipeline_cache = target
+            .pipeline_cache
+            .as_deref()
+            .and_then(|directory| DiskPipelineCache::open(&device, &adapter.get_info(), directory));
+
+        let surface_capabilities = match &surface {
+            Some(surface) => surface.get_capabilities(&adapter),
+            None => offscreen_capabilities(),
+        };
+        let surface_choice = negotiate(&surface_capabilities, &self.surface_preferences)?;
+        let view_formats = if self.srgb_view_format {
+            vec![surface_choice.format.add_srgb_suffix()]
+        } else {
+            vec![]
+        };
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_choice.format,
+            width,
+            height,
+            present_mode: surface_choice.present_mode,
+            alpha_mode: surface_choice.alpha_mode,
+            view_formats,
+            desired_maximum_frame_latency: self.desired_maximum_frame_latency,
+        };
+
+        let presentation = match surface {
+            Some(surface) => Presentation::Surface(surface),
+            None => {
+                anyhow::ensure!(width > 0 && height > 0, "headless targets need a non-zero size, got {}x{}", width, height);
+                Presentation::Offscreen(create_offscreen_texture(&device, &config))
+            },
+        };
+
+        let mut context = GpuContext {
+            window: target.window().cloned(),
+            presentation,
+            adapter,
+            device,
+            queue,
+            config,
+            surface_choice,
+            is_surface_configured: false,
+            pipeline_cache,
+            rebuild_pipelines: target.rebuild_pipelines,
+        };
+
+        // Windows usually already have a size here. If not, the first resize event will configure the surface.
+        context.resize(width, height);
+
+        Ok(context)
+    }
+}
+
+// The offscreen stand-in for the swapchain. COPY_SRC lets frames be read back to the CPU.
+fn create_offscreen_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Frame"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &config.view_formats,
+    })
+}
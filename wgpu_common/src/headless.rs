@@ -0,0 +1,70 @@
+use crate::adapter::AdapterSelection;
+use crate::exercise::{ExerciseEntry, Registry};
+use crate::frame_timer::{FrameStats, FrameTimer};
+use crate::options::HeadlessOptions;
+use crate::target::Target;
+
+/// Renders `options.frames` frames of the exercise at `index` into an offscreen texture and saves the last one.
+pub fn run_headless(registry: &Registry, index: usize, options: &HeadlessOptions, adapter: &AdapterSelection) -> anyhow::Result<()> {
+    let entry = &registry.entries()[index];
+    log::info!("Rendering {} headless frame(s) of '{}' at {}x{}", options.frames, entry.name, options.width, options.height);
+
+    let target = Target::headless(options.width, options.height).with_adapter(adapter.clone());
+    let run = render_headless_timed(entry, target, options.frames)?;
+    log::info!("'{}' render time: {}", entry.name, run.stats);
+
+    let image = run
+        .image
+        .ok_or_else(|| anyhow::anyhow!("exercise '{}' does not render anything that can be captured", entry.name))?;
+    image.save_with_format(&options.out, image::ImageFormat::Png)?;
+
+    log::info!("Saved {}", options.out.display());
+    Ok(())
+}
+
+/// Updates and renders `frames` frames of an exercise into a headless `target` and reads back the last one. Exercises
+/// that don't render anything (e.g. the empty window ones) return `None`.
+pub fn render_headless(entry: &ExerciseEntry, target: Target, frames: u32) -> anyhow::Result<Option<image::RgbaImage>> {
+    render_headless_timed(entry, target, frames).map(|run| run.image)
+}
+
+/// What [`render_headless_timed`] rendered, how long it took and where.
+pub struct HeadlessRun {
+    /// The last frame, `None` for exercises that don't render anything.
+    pub image: Option<image::RgbaImage>,
+    pub stats: FrameStats,
+    /// The adapter the exercise rendered with, `None` when it has no [`GpuContext`](crate::GpuContext).
+    pub adapter: Option<wgpu::AdapterInfo>,
+}
+
+/// Same as [`render_headless`], but also reports how long each `render` call took and on which adapter.
+pub fn render_headless_timed(entry: &ExerciseEntry, target: Target, frames: u32) -> anyhow::Result<HeadlessRun> {
+    anyhow::ensure!(target.is_headless(), "render_headless needs a headless target");
+    let mut exercise = entry.init(target)?;
+    let mut timer = FrameTimer::new();
+
+    for frame in 0..frames {
+        exercise.update();
+        timer
+            .time(|| exercise.render())
+            .map_err(|e| anyhow::anyhow!("unable to render frame {}: {}", frame, e))?;
+    }
+
+    let image = exercise.context().map(|context| context.capture()).transpose()?;
+    Ok(HeadlessRun {
+        image,
+        stats: timer.stats(),
+        adapter: exercise.context().map(|context| context.adapter.get_info()),
+    })
+}
+
+/// Whether this machine has the fallback (software) adapter headless rendering needs.
+pub fn fallback_adapter_available() -> bool {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    let request_adapter_options = wgpu::RequestAdapterOptions {
+        force_fallback_adapter: true,
+        ..Default::default()
+    };
+
+    pollster::block_on(instance.request_adapter(&request_adapter_options)).is_ok()
+}
@@ -0,0 +1,92 @@
+use std::ops::Range;
+
+use wgpu::util::DeviceExt;
+
+/// Indices in the narrowest format that holds them: `u16` while every index is below `u16::MAX`, `u32` otherwise.
+/// `u16::MAX` itself is left out of the `u16` range since strips treat it as the primitive restart value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    pub fn narrowest<I: Copy + Into<u32>>(indices: &[I]) -> Self {
+        let fits_u16 = indices.iter().all(|&index| index.into() < u16::MAX as u32);
+        if fits_u16 {
+            Indices::U16(indices.iter().map(|&index| index.into() as u16).collect())
+        } else {
+            Indices::U32(indices.iter().map(|&index| index.into()).collect())
+        }
+    }
+
+    pub fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            Indices::U16(_) => wgpu::IndexFormat::Uint16,
+            Indices::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Indices::U16(indices) => bytemuck::cast_slice(indices),
+            Indices::U32(indices) => bytemuck::cast_slice(indices),
+        }
+    }
+}
+
+/// An index buffer that remembers its [`wgpu::IndexFormat`] and how many indices it holds, so meshes of any size are
+/// drawn without anyone having to pick `Uint16` or `Uint32` by hand.
+///
+/// ```text
+/// let index_buffer = IndexBuffer::new(&device, "Index Buffer", INDICES);
+///
+/// index_buffer.bind(&mut renderpass);
+/// index_buffer.draw(&mut renderpass, 0..1);
+/// ```
+pub struct IndexBuffer {
+    pub buffer: wgpu::Buffer,
+    pub format: wgpu::IndexFormat,
+    pub count: u32,
+}
+
+impl IndexBuffer {
+    /// Uploads `indices` as [`Indices::narrowest`].
+    pub fn new<I: Copy + Into<u32>>(device: &wgpu::Device, label: &str, indices: &[I]) -> Self {
+        Self::from_indices(device, label, &Indices::narrowest(indices))
+    }
+
+    pub fn from_indices(device: &wgpu::Device, label: &str, indices: &Indices) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(label),
+            contents: indices.as_bytes(),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        Self {
+            buffer,
+            format: indices.format(),
+            count: indices.len() as u32,
+        }
+    }
+
+    /// `set_index_buffer` with the whole buffer in its format.
+    pub fn bind(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_index_buffer(self.buffer.slice(..), self.format);
+    }
+
+    /// `draw_indexed` over every index, after [`bind`](IndexBuffer::bind).
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        render_pass.draw_indexed(0..self.count, 0, instances);
+    }
+}
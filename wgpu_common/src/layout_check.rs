@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use naga::{Binding, ScalarKind, ShaderStage, TypeInner};
+
+use crate::preprocess::Preprocessor;
+
+/// A WGSL shader together with the Rust vertex buffer layouts its pipeline is built with, so the two can be compared
+/// without a GPU. Exercises attach one to their registry entry with [`ExerciseEntry::with_shader_layouts`].
+///
+/// ```text
+/// ShaderLayouts {
+///     path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+///     source: include_str!("shader.wgsl"),
+///     entry_point: "vs_main",
+///     buffers: || vec![Vertex::desc(), InstanceRaw::desc()],
+/// }
+/// ```
+///
+/// [`ExerciseEntry::with_shader_layouts`]: crate::ExerciseEntry::with_shader_layouts
+#[derive(Clone, Copy)]
+pub struct ShaderLayouts {
+    /// Only used in messages.
+    pub path: &'static str,
+    pub source: &'static str,
+    pub entry_point: &'static str,
+    pub buffers: fn() -> Vec<wgpu::VertexBufferLayout<'static>>,
+}
+
+impl ShaderLayouts {
+    /// Preprocesses and parses the shader and compares the vertex entry point's `@location` inputs with the buffers.
+    pub fn check(&self) -> anyhow::Result<Vec<LayoutProblem>> {
+        let module = Preprocessor::default().process(self.path, self.source)?.validate()?;
+        Ok(check_vertex_layouts(&module, self.entry_point, &(self.buffers)()))
+    }
+}
+
+/// Something the vertex shader and the vertex buffer layouts disagree on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutProblem {
+    /// There is no `@vertex fn` with that name.
+    MissingEntryPoint { entry_point: String },
+    /// The shader reads a location that no buffer provides. wgpu rejects the pipeline.
+    MissingLocation { location: u32, shader_type: String },
+    /// A buffer provides the location in a format whose scalar type (float, signed or unsigned, and f16 or f64 width)
+    /// isn't the one the shader reads. wgpu rejects the pipeline.
+    TypeMismatch { location: u32, shader_type: String, buffer: usize, format: wgpu::VertexFormat },
+    /// The scalar types match but the number of components doesn't, e.g. `Float32x3` for a `vec4<f32>`. Allowed: wgpu
+    /// drops the extra components or fills in the missing ones with 0 (1 for w), but usually a mistake.
+    ComponentMismatch { location: u32, shader_type: String, buffer: usize, format: wgpu::VertexFormat },
+    /// Two attributes use the same location. wgpu rejects the pipeline.
+    DuplicateLocation { location: u32, buffers: (usize, usize) },
+    /// A buffer provides a location the shader never reads. Allowed, but usually a typo or leftover.
+    UnusedAttribute { location: u32, buffer: usize, format: wgpu::VertexFormat },
+}
+
+impl LayoutProblem {
+    /// Whether wgpu would refuse to build the pipeline (or build one that reads the wrong data), as opposed to a
+    /// warning such as an unused attribute or a different number of components.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, LayoutProblem::UnusedAttribute { .. } | LayoutProblem::ComponentMismatch { .. })
+    }
+}
+
+impl fmt::Display for LayoutProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutProblem::MissingEntryPoint { entry_point } => write!(f, "no @vertex entry point named '{}'", entry_point),
+            LayoutProblem::MissingLocation { location, shader_type } => write!(
+                f,
+                "@location({}): the shader reads {} but no vertex buffer provides it",
+                location, shader_type
+            ),
+            LayoutProblem::TypeMismatch {
+                location,
+                shader_type,
+                buffer,
+                format,
+            } => write!(
+                f,
+                "@location({}): the shader reads {} but vertex buffer {} provides {:?}, which the shader sees as {}",
+                location,
+                shader_type,
+                buffer,
+                format,
+                input_type_of(*format)
+            ),
+            LayoutProblem::ComponentMismatch {
+                location,
+                shader_type,
+                buffer,
+                format,
+            } => write!(
+                f,
+                "@location({}): the shader reads {} but vertex buffer {} provides {:?}, which the shader sees as {}; \
+                 wgpu drops the extra components or fills in the missing ones",
+                location,
+                shader_type,
+                buffer,
+                format,
+                input_type_of(*format)
+            ),
+            LayoutProblem::DuplicateLocation { location, buffers } if buffers.0 == buffers.1 => {
+                write!(f, "@location({}) is used twice in vertex buffer {}", location, buffers.0)
+            },
+            LayoutProblem::DuplicateLocation { location, buffers } => write!(
+                f,
+                "@location({}) is used by vertex buffer {} and vertex buffer {}",
+                location, buffers.0, buffers.1
+            ),
+            LayoutProblem::UnusedAttribute { location, buffer, format } => write!(
+                f,
+                "@location({}): vertex buffer {} provides {:?} but the shader never reads it",
+                location, buffer, format
+            ),
+        }
+    }
+}
+
+/// Compares the `@location` inputs of the `entry_point` vertex shader in `module` with `buffers`, in location order.
+pub fn check_vertex_layouts(
+    module: &naga::Module,
+    entry_point: &str,
+    buffers: &[wgpu::VertexBufferLayout<'_>],
+) -> Vec<LayoutProblem> {
+    let inputs = match vertex_inputs(module, entry_point) {
+        Some(inputs) => inputs,
+        None => {
+            return vec![LayoutProblem::MissingEntryPoint {
+                entry_point: entry_point.to_string(),
+            }]
+        },
+    };
+
+    let mut problems = Vec::new();
+    let mut attributes: BTreeMap<u32, (usize, wgpu::VertexFormat)> = BTreeMap::new();
+    for (buffer, layout) in buffers.iter().enumerate() {
+        for attribute in layout.attributes {
+            if let Some(&(other, _)) = attributes.get(&attribute.shader_location) {
+                problems.push(LayoutProblem::DuplicateLocation {
+                    location: attribute.shader_location,
+                    buffers: (other, buffer),
+                });
+                continue;
+            }
+            attributes.insert(attribute.shader_location, (buffer, attribute.format));
+        }
+    }
+
+    for (&location, input) in &inputs {
+        match attributes.get(&location) {
+            None => problems.push(LayoutProblem::MissingLocation {
+                location,
+                shader_type: input.to_string(),
+            }),
+            Some(&(buffer, format)) if !scalar_matches(format, input) => {
+                problems.push(LayoutProblem::TypeMismatch {
+                    location,
+                    shader_type: input.to_string(),
+                    buffer,
+                    format,
+                })
+            },
+            Some(&(buffer, format)) if input_type_of(format).components != input.components => {
+                problems.push(LayoutProblem::ComponentMismatch {
+                    location,
+                    shader_type: input.to_string(),
+                    buffer,
+                    format,
+                })
+            },
+            Some(_) => {},
+        }
+    }
+
+    for (&location, &(buffer, format)) in &attributes {
+        if !inputs.contains_key(&location) {
+            problems.push(LayoutProblem::UnusedAttribute { location, buffer, format });
+        }
+    }
+
+    problems.sort_by_key(|problem| match problem {
+        LayoutProblem::MissingEntryPoint { .. } => 0,
+        LayoutProblem::MissingLocation { location, .. }
+        | LayoutProblem::TypeMismatch { location, .. }
+        | LayoutProblem::ComponentMismatch { location, .. }
+        | LayoutProblem::DuplicateLocation { location, .. }
+        | LayoutProblem::UnusedAttribute { location, .. } => *location,
+    });
+    problems
+}
+
+/// The scalar or vector type of a vertex input, e.g. `vec3<f32>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct InputType {
+    kind: ScalarKind,
+    width: u8,
+    components: u8,
+}
+
+impl fmt::Display for InputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scalar = match (self.kind, self.width) {
+            (ScalarKind::Float, 2) => "f16",
+            (ScalarKind::Float, 8) => "f64",
+            (ScalarKind::Float, _) => "f32",
+            (ScalarKind::Uint, _) => "u32",
+            (ScalarKind::Sint, _) => "i32",
+            (ScalarKind::Bool, _) => "bool",
+            (ScalarKind::AbstractInt, _) | (ScalarKind::AbstractFloat, _) => "abstract",
+        };
+
+        if self.components == 1 {
+            write!(f, "{}", scalar)
+        } else {
+            write!(f, "vec{}<{}>", self.components, scalar)
+        }
+    }
+}
+
+/// The `@location` inputs of a vertex entry point, whether they are plain arguments or members of a struct argument.
+fn vertex_inputs(module: &naga::Module, entry_point: &str) -> Option<BTreeMap<u32, InputType>> {
+    let entry_point = module
+        .entry_points
+        .iter()
+        .find(|ep| ep.stage == ShaderStage::Vertex && ep.name == entry_point)?;
+
+    let mut inputs = BTreeMap::new();
+    for argument in &entry_point.function.arguments {
+        match (&argument.binding, &module.types[argument.ty].inner) {
+            (Some(Binding::Location { location, .. }), inner) => {
+                inputs.extend(input_type(inner).map(|ty| (*location, ty)));
+            },
+            (None, TypeInner::Struct { members, .. }) => {
+                for member in members {
+                    if let Some(Binding::Location { location, .. }) = member.binding {
+                        inputs.extend(input_type(&module.types[member.ty].inner).map(|ty| (location, ty)));
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    Some(inputs)
+}
+
+fn input_type(inner: &TypeInner) -> Option<InputType> {
+    match *inner {
+        TypeInner::Scalar(scalar) => Some(InputType {
+            kind: scalar.kind,
+            width: scalar.width,
+            components: 1,
+        }),
+        TypeInner::Vector { size, scalar } => Some(InputType {
+            kind: scalar.kind,
+            width: scalar.width,
+            components: size as u8,
+        }),
+        _ => None,
+    }
+}
+
+/// The type a vertex shader reads `format` as: normalized and half float formats turn into f32.
+fn input_type_of(format: wgpu::VertexFormat) -> InputType {
+    use wgpu::VertexFormat::*;
+
+    let (kind, width) = match format {
+        Uint8 | Uint8x2 | Uint8x4 | Uint16 | Uint16x2 | Uint16x4 | Uint32 | Uint32x2 | Uint32x3 | Uint32x4 => {
+            (ScalarKind::Uint, 4)
+        },
+        Sint8 | Sint8x2 | Sint8x4 | Sint16 | Sint16x2 | Sint16x4 | Sint32 | Sint32x2 | Sint32x3 | Sint32x4 => {
+            (ScalarKind::Sint, 4)
+        },
+        Float64 | Float64x2 | Float64x3 | Float64x4 => (ScalarKind::Float, 8),
+        _ => (ScalarKind::Float, 4),
+    };
+    let components = match format {
+        Uint8 | Sint8 | Unorm8 | Snorm8 | Uint16 | Sint16 | Unorm16 | Snorm16 | Float16 | Float32 | Uint32 | Sint32
+        | Float64 => 1,
+        Uint8x2 | Sint8x2 | Unorm8x2 | Snorm8x2 | Uint16x2 | Sint16x2 | Unorm16x2 | Snorm16x2 | Float16x2 | Float32x2
+        | Uint32x2 | Sint32x2 | Float64x2 => 2,
+        Float32x3 | Uint32x3 | Sint32x3 | Float64x3 => 3,
+        _ => 4,
+    };
+
+    InputType { kind, width, components }
+}
+
+/// Whether the shader can read `format` as `input` whatever the number of components, which is all wgpu checks. With
+/// `shader-f16`, half float formats can also be read as f16.
+fn scalar_matches(format: wgpu::VertexFormat, input: &InputType) -> bool {
+    let provided = input_type_of(format);
+    let half = matches!(
+        format,
+        wgpu::VertexFormat::Float16 | wgpu::VertexFormat::Float16x2 | wgpu::VertexFormat::Float16x4
+    );
+    provided.kind == input.kind && (provided.width == input.width || half && input.width == 2)
+}
@@ -0,0 +1,90 @@
+//! Shared building blocks for the wgpu exercises.
+//!
+//! Every exercise used to repeat the same Instance -> Surface -> Adapter -> Device/Queue -> SurfaceConfiguration
+//! bootstrap at the top of `State::new`. That sequence now lives in [`GpuContext`], and an exercise only has to say
+//! what it wants to be different through the [`GpuContextBuilder`].
+//!
+//! Exercises plug into the shared window/event loop by implementing [`Exercise`] and registering themselves in a
+//! [`Registry`], which is what both the per-exercise binaries and the `exercises` runner hand to [`run`].
+//!
+//! Passing `--headless` renders into an offscreen texture instead of a window (see [`Target`]) and writes the last
+//! frame to a PNG, which is handy for CI machines without a display. Passing `--hot-reload` loads WGSL from disk
+//! instead and swaps the pipeline whenever the file is saved and still compiles (see [`Shader`]).
+
+mod adapter;
+mod app;
+mod bind_groups;
+mod capture;
+mod disk_cache;
+mod exercise;
+mod frame;
+mod frame_timer;
+mod geometry;
+mod golden;
+mod gltf_scene;
+mod gpu_context;
+mod headless;
+mod index_buffer;
+mod layout_check;
+mod model;
+mod options;
+mod pipeline_builder;
+mod pipeline_cache;
+mod point_sprite;
+mod polyline;
+mod preprocess;
+mod screen_quads;
+mod shader;
+mod surface;
+mod target;
+mod texture;
+mod topology;
+mod triangulate;
+mod vertex_layout;
+mod wireframe;
+
+pub use crate::adapter::{AdapterChoice, AdapterSelection};
+pub use crate::app::{run, App};
+pub use crate::bind_groups::{BindGroupLayouts, BindingProblem};
+pub use crate::capture::read_texture;
+pub use crate::disk_cache::{CacheFileError, CacheStatus, DiskPipelineCache};
+pub use crate::exercise::{Exercise, ExerciseEntry, InitFn, Registry};
+pub use crate::frame::Frame;
+pub use crate::frame_timer::{FrameStats, FrameTimer};
+pub use crate::golden::{check_golden, compare, Comparison, GoldenOutcome, Metric, Tolerance};
+pub use crate::geometry::{cone, cube, cylinder, icosphere, plane, torus, uv_sphere};
+pub use crate::gltf_scene::{Scene, SceneData, SceneNode};
+pub use crate::gpu_context::{GpuContext, GpuContextBuilder};
+pub use crate::headless::{
+    fallback_adapter_available, render_headless, render_headless_timed, run_headless, HeadlessRun,
+};
+pub use crate::index_buffer::{IndexBuffer, Indices};
+pub use crate::layout_check::{check_vertex_layouts, LayoutProblem, ShaderLayouts};
+pub use crate::model::{
+    compute_normals, compute_tangents, InstanceMatrix, Material, MaterialData, Mesh, MeshData, Model, ModelData,
+    ModelVertex, TextureSource,
+};
+pub use crate::options::{HeadlessOptions, RunOptions};
+pub use crate::pipeline_builder::{Blend, RenderPipelineBuilder};
+pub use crate::pipeline_cache::{PipelineKey, RenderPipelineCache};
+pub use crate::point_sprite::{PointShape, PointSprite, PointSpriteRenderer, PointSprites, PointStyle, SizeUnit};
+pub use crate::polyline::{LineCap, LineJoin, LineRenderer, LineSegment, LineStyle, Lines};
+pub use crate::preprocess::{Preprocessed, Preprocessor};
+pub use crate::shader::{validate_wgsl, Shader, ShaderError};
+pub use crate::surface::{negotiate, FormatPreference, SurfaceChoice, SurfacePreferences};
+pub use crate::target::{Target, TargetKind};
+pub use crate::texture::Texture;
+pub use crate::topology::{
+    parse_user_input, primitive_restart_index, strip_ranges, supports_primitive_restart, Topology, TopologyControls,
+};
+pub use crate::triangulate::Polygon;
+pub use crate::vertex_layout::VertexLayout;
+pub use crate::wireframe::{deindex, PipelineStyle, Wireframe, WireframeMode};
+pub use wgpu_common_derive::VertexLayout;
+
+// The derive macro refers to `wgpu_common`, including from inside this crate (see `ModelVertex`).
+extern crate self as wgpu_common;
+
+// The derive macro refers to wgpu through here, so exercises don't have to match versions themselves.
+#[doc(hidden)]
+pub use wgpu;
@@ -0,0 +1,435 @@
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use wgpu::util::DeviceExt;
+
+use crate::index_buffer::IndexBuffer;
+use crate::texture::Texture;
+use crate::wireframe::deindex;
+
+/// The vertex every loaded mesh is made of: `@location(0)` position, `@location(1)` texture coordinates,
+/// `@location(2)` normal and `@location(3)` tangent. Texture coordinates have their origin in the top left, like
+/// wgpu's (and glTF's).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable, crate::VertexLayout)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub texture_coords: [f32; 2],
+    pub normal: [f32; 3],
+    /// `xyz` points along +u on the surface and `w` is the handedness of the bitangent (`cross(normal, tangent.xyz) *
+    /// w`), like glTF's tangents.
+    pub tangent: [f32; 4],
+}
+
+/// A per-instance model matrix at `@location(5)` to `@location(8)`, laid out like the instancing exercise's
+/// `InstanceRaw` so it fits `instance.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, crate::VertexLayout)]
+#[vertex(step_mode = "instance")]
+pub struct InstanceMatrix {
+    /// Column major.
+    #[vertex(location = 5, matrix)]
+    pub model: [[f32; 4]; 4],
+}
+
+/// The geometry of one mesh on the CPU, ready to be uploaded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshData {
+    pub name: String,
+    pub vertices: Vec<ModelVertex>,
+    /// Three per triangle, counter-clockwise when seen from the front.
+    pub indices: Vec<u32>,
+    /// Index into [`ModelData::materials`].
+    pub material: Option<usize>,
+}
+
+/// What a mesh is painted with: the diffuse part of an MTL material, or the base color of a glTF one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialData {
+    pub name: String,
+    /// `Kd` or `baseColorFactor`, white when the material doesn't set it. Only used when there is no texture.
+    pub diffuse_color: [f32; 4],
+    /// `map_Kd` or `baseColorTexture`.
+    pub diffuse_texture: Option<TextureSource>,
+}
+
+/// Where a material's texture comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextureSource {
+    /// An image file, loaded when the material is uploaded. OBJ loading makes these relative to the OBJ file.
+    File(PathBuf),
+    /// Already decoded, e.g. embedded in a glTF file.
+    Image(image::RgbaImage),
+}
+
+/// Every mesh and material of a model file, before anything is on the GPU.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelData {
+    pub meshes: Vec<MeshData>,
+    pub materials: Vec<MaterialData>,
+}
+
+impl ModelData {
+    /// Reads a Wavefront OBJ file and the MTL files it refers to. Polygons are triangulated, every `o`/`g` becomes its
+    /// own mesh, and meshes without normals get smooth ones computed from their faces. A missing or broken MTL file is
+    /// logged and leaves the meshes without materials rather than failing the load.
+    pub fn load_obj(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path).with_context(|| format!("unable to open {}", path.display()))?;
+        let directory = path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+
+        let mut data = Self::parse_obj(&mut BufReader::new(file), |mtl| {
+            std::fs::read_to_string(directory.join(mtl)).ok()
+        })
+        .with_context(|| format!("unable to load {}", path.display()))?;
+
+        for material in &mut data.materials {
+            if let Some(TextureSource::File(texture)) = &mut material.diffuse_texture {
+                *texture = directory.join(&*texture);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Parses OBJ source. `load_mtl` returns the contents of an MTL file named by `mtllib`, or `None` if there is no
+    /// such file. Texture paths are left as written in the MTL file.
+    pub fn parse_obj<R, F>(reader: &mut R, load_mtl: F) -> anyhow::Result<Self>
+    where
+        R: std::io::BufRead,
+        F: Fn(&Path) -> Option<String>,
+    {
+        let (models, materials) = tobj::load_obj_buf(reader, &tobj::GPU_LOAD_OPTIONS, |mtl| match load_mtl(mtl) {
+            Some(source) => tobj::load_mtl_buf(&mut source.as_bytes()),
+            None => Err(tobj::LoadError::OpenFileFailed),
+        })?;
+
+        let materials = match materials {
+            Ok(materials) => materials,
+            Err(e) => {
+                log::warn!("Unable to load the materials: {}", e);
+                Vec::new()
+            },
+        };
+
+        Ok(Self {
+            meshes: models.into_iter().map(mesh_data).collect(),
+            materials: materials
+                .into_iter()
+                .map(|material| MaterialData {
+                    name: material.name,
+                    diffuse_color: match material.diffuse {
+                        Some([r, g, b]) => [r, g, b, 1.0],
+                        None => [1.0; 4],
+                    },
+                    diffuse_texture: material.diffuse_texture.map(|path| TextureSource::File(PathBuf::from(path))),
+                })
+                .collect(),
+        })
+    }
+}
+
+fn mesh_data(model: tobj::Model) -> MeshData {
+    let mesh = model.mesh;
+    let mut vertices: Vec<ModelVertex> = (0..mesh.positions.len() / 3)
+        .map(|i| ModelVertex {
+            position: [mesh.positions[i * 3], mesh.positions[i * 3 + 1], mesh.positions[i * 3 + 2]],
+            // OBJ puts v = 0 at the bottom of the image, wgpu at the top.
+            texture_coords: match mesh.texcoords.get(i * 2..i * 2 + 2) {
+                Some(uv) => [uv[0], 1.0 - uv[1]],
+                None => [0.0, 0.0],
+            },
+            normal: match mesh.normals.get(i * 3..i * 3 + 3) {
+                Some(normal) => [normal[0], normal[1], normal[2]],
+                None => [0.0, 0.0, 0.0],
+            },
+            tangent: [0.0; 4],
+        })
+        .collect();
+
+    if mesh.normals.is_empty() {
+        compute_normals(&mut vertices, &mesh.indices);
+    }
+    compute_tangents(&mut vertices, &mesh.indices);
+
+    MeshData {
+        name: model.name,
+        vertices,
+        indices: mesh.indices,
+        material: mesh.material_id,
+    }
+}
+
+/// Smooth normals: every vertex gets the sum of the normals of the triangles around it, weighted by their area.
+pub fn compute_normals(vertices: &mut [ModelVertex], indices: &[u32]) {
+    let mut sums = vec![[0.0f32; 3]; vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize].position);
+        let normal = cross(sub(b, a), sub(c, a));
+        for &index in triangle {
+            add_assign(&mut sums[index as usize], normal);
+        }
+    }
+
+    for (vertex, sum) in vertices.iter_mut().zip(sums) {
+        if dot(sum, sum) > 0.0 {
+            vertex.normal = normalize(sum);
+        }
+    }
+}
+
+/// Tangents from the texture coordinates: every vertex gets the sum of the `u` directions of the triangles around it,
+/// made perpendicular to its normal. Vertices whose triangles have no usable texture coordinates get any tangent
+/// perpendicular to the normal, so shaders never see a zero vector.
+pub fn compute_tangents(vertices: &mut [ModelVertex], indices: &[u32]) {
+    let mut tangents = vec![[0.0f32; 3]; vertices.len()];
+    let mut bitangents = vec![[0.0f32; 3]; vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| vertices[triangle[i] as usize]);
+        let edge1 = sub(b.position, a.position);
+        let edge2 = sub(c.position, a.position);
+        let (du1, dv1) = (b.texture_coords[0] - a.texture_coords[0], b.texture_coords[1] - a.texture_coords[1]);
+        let (du2, dv2) = (c.texture_coords[0] - a.texture_coords[0], c.texture_coords[1] - a.texture_coords[1]);
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() < f32::EPSILON {
+            continue;
+        }
+
+        // The surface directions in which u and v grow, from how they change along the two edges.
+        let r = 1.0 / determinant;
+        let tangent = [0, 1, 2].map(|axis| (edge1[axis] * dv2 - edge2[axis] * dv1) * r);
+        let bitangent = [0, 1, 2].map(|axis| (edge2[axis] * du1 - edge1[axis] * du2) * r);
+        for &index in triangle {
+            add_assign(&mut tangents[index as usize], tangent);
+            add_assign(&mut bitangents[index as usize], bitangent);
+        }
+    }
+
+    for ((vertex, tangent), bitangent) in vertices.iter_mut().zip(tangents).zip(bitangents) {
+        let normal = vertex.normal;
+        // Gram-Schmidt: remove the part along the normal.
+        let along = dot(tangent, normal);
+        let mut tangent = [0, 1, 2].map(|axis| tangent[axis] - normal[axis] * along);
+        if dot(tangent, tangent) < 1e-12 {
+            tangent = any_perpendicular(normal);
+        }
+        let tangent = normalize(tangent);
+        let handedness = if dot(cross(normal, tangent), bitangent) < 0.0 { -1.0 } else { 1.0 };
+        vertex.tangent = [tangent[0], tangent[1], tangent[2], handedness];
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add_assign(a: &mut [f32; 3], b: [f32; 3]) {
+    for (a, b) in a.iter_mut().zip(b) {
+        *a += b;
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    [a[0] / length, a[1] / length, a[2] / length]
+}
+
+fn any_perpendicular(normal: [f32; 3]) -> [f32; 3] {
+    let axis = if normal[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    let perpendicular = cross(normal, axis);
+    if dot(perpendicular, perpendicular) < 1e-12 {
+        [1.0, 0.0, 0.0]
+    } else {
+        cross(perpendicular, normal)
+    }
+}
+
+/// A mesh in GPU buffers, with `u16` indices when it has few enough vertices.
+pub struct Mesh {
+    pub name: String,
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: IndexBuffer,
+    /// The triangles expanded by [`deindex`], for the barycentric wireframe.
+    pub deindexed_vertex_buffer: wgpu::Buffer,
+    pub material: Option<usize>,
+}
+
+impl Mesh {
+    pub fn upload(device: &wgpu::Device, data: &MeshData) -> Self {
+        let buffer = |label: &str, contents: &[u8], usage| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{} {}", data.name, label)),
+                contents,
+                usage,
+            })
+        };
+
+        Self {
+            name: data.name.clone(),
+            vertex_buffer: buffer("Vertex Buffer", bytemuck::cast_slice(&data.vertices), wgpu::BufferUsages::VERTEX),
+            index_buffer: IndexBuffer::new(device, &format!("{} Index Buffer", data.name), &data.indices),
+            deindexed_vertex_buffer: buffer(
+                "Deindexed Vertex Buffer",
+                bytemuck::cast_slice(&deindex(&data.vertices, &data.indices)),
+                wgpu::BufferUsages::VERTEX,
+            ),
+            material: data.material,
+        }
+    }
+
+    /// Binds the vertex buffer to slot 0 and the index buffer, and draws `instances` copies of the mesh.
+    pub fn draw_instanced(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        self.index_buffer.bind(render_pass);
+        self.index_buffer.draw(render_pass, instances);
+    }
+
+    /// Like [`draw_instanced`](Mesh::draw_instanced), but from the deindexed vertices.
+    pub fn draw_deindexed_instanced(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        render_pass.set_vertex_buffer(0, self.deindexed_vertex_buffer.slice(..));
+        render_pass.draw(0..self.index_buffer.count, instances);
+    }
+}
+
+/// A material's diffuse texture and the bind group it is sampled through.
+pub struct Material {
+    pub name: String,
+    pub diffuse_texture: Texture,
+    /// `@binding(0)` the diffuse texture view and `@binding(1)` its sampler, in the layout given to
+    /// [`Model::upload`].
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Material {
+    /// Loads the diffuse texture, or makes a 1x1 texture of the diffuse color when the material has none. Textures
+    /// repeat, since models often rely on texture coordinates outside 0..1.
+    pub fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &MaterialData,
+        layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let sampler = wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        };
+        let label = Some(data.name.as_str());
+        let diffuse_texture = match &data.diffuse_texture {
+            Some(TextureSource::File(path)) => {
+                let image = image::open(path)
+                    .with_context(|| format!("unable to load the texture of material '{}'", data.name))?;
+                Texture::from_rgba(device, queue, &image.to_rgba8(), label, &sampler)
+            },
+            Some(TextureSource::Image(image)) => Texture::from_rgba(device, queue, image, label, &sampler),
+            None => Texture::from_color(device, queue, data.diffuse_color, label),
+        };
+        let bind_group = diffuse_texture.bind_group(device, layout, label);
+
+        Ok(Self {
+            name: data.name.clone(),
+            diffuse_texture,
+            bind_group,
+        })
+    }
+}
+
+/// Meshes and their materials on the GPU.
+///
+/// ```text
+/// let model = Model::load_obj(&context.device, &context.queue, Path::new("cube.obj"), &texture_bind_group_layout)?;
+///
+/// renderpass.set_vertex_buffer(1, instance_buffer.slice(..));
+/// model.draw_instanced(&mut renderpass, 0..instances.len() as u32, 0);
+/// ```
+pub struct Model {
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<Material>,
+    /// Bound for meshes without a material, white unless the model says otherwise.
+    pub default_material: Material,
+}
+
+impl Model {
+    /// Loads an OBJ file with [`ModelData::load_obj`] and uploads it.
+    pub fn load_obj(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: &Path,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let data = ModelData::load_obj(path)?;
+        let model = Self::upload(device, queue, &data, texture_layout)?;
+        log::info!(
+            "Loaded {}: {} mesh(es), {} triangles, {} material(s)",
+            path.display(),
+            data.meshes.len(),
+            data.meshes.iter().map(|mesh| mesh.indices.len() / 3).sum::<usize>(),
+            data.materials.len()
+        );
+        Ok(model)
+    }
+
+    /// Uploads every mesh and material. Material bind groups are made with `texture_layout`, which needs a
+    /// `texture_2d<f32>` at `@binding(0)` and a filtering sampler at `@binding(1)`.
+    pub fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &ModelData,
+        texture_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let default_material = MaterialData {
+            name: "Default Material".to_string(),
+            diffuse_color: [1.0; 4],
+            diffuse_texture: None,
+        };
+
+        Ok(Self {
+            meshes: data.meshes.iter().map(|mesh| Mesh::upload(device, mesh)).collect(),
+            materials: data
+                .materials
+                .iter()
+                .map(|material| Material::upload(device, queue, material, texture_layout))
+                .collect::<anyhow::Result<_>>()?,
+            default_material: Material::upload(device, queue, &default_material, texture_layout)?,
+        })
+    }
+
+    /// The material `mesh` is drawn with.
+    pub fn material(&self, mesh: &Mesh) -> &Material {
+        mesh.material
+            .and_then(|index| self.materials.get(index))
+            .unwrap_or(&self.default_material)
+    }
+
+    /// Draws every mesh `instances` times, binding its material to `material_group`. The instance buffer (if any)
+    /// and the other bind groups have to be set already.
+    pub fn draw_instanced(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>, material_group: u32) {
+        for mesh in &self.meshes {
+            render_pass.set_bind_group(material_group, &self.material(mesh).bind_group, &[]);
+            mesh.draw_instanced(render_pass, instances.clone());
+        }
+    }
+
+    /// Like [`draw_instanced`](Model::draw_instanced), but from the deindexed vertices.
+    pub fn draw_deindexed_instanced(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        instances: Range<u32>,
+        material_group: u32,
+    ) {
+        for mesh in &self.meshes {
+            render_pass.set_bind_group(material_group, &self.material(mesh).bind_group, &[]);
+            mesh.draw_deindexed_instanced(render_pass, instances.clone());
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::env;
+use std::path::PathBuf;
+
+use crate::adapter::AdapterSelection;
+use crate::disk_cache::DiskPipelineCache;
+
+/// Flags shared by every binary that calls [`run`](crate::run). Unknown `--` flags are an error, other arguments are
+/// left for the binary itself, so exercises can keep their own positional arguments.
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    /// Set by `--headless`. Renders offscreen and writes the last frame to a PNG instead of opening a window.
+    pub headless: Option<HeadlessOptions>,
+    /// Set by `--backend`, `--power`, `--adapter` and `--fallback-adapter`, or the matching `WGPU_*` variables.
+    pub adapter: AdapterSelection,
+    /// Set by `--hot-reload` or `SHADER_HOT_RELOAD=1`. Shaders are loaded from disk and reloaded when they change.
+    pub hot_reload: bool,
+    /// Where windowed runs keep the driver pipeline cache, `WGPU_PIPELINE_CACHE_DIR` or the user's cache directory.
+    /// `--no-pipeline-cache` or `WGPU_PIPELINE_CACHE=0` turn it off.
+    pub pipeline_cache: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+pub struct HeadlessOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Number of frames to update and render before the capture is taken.
+    pub frames: u32,
+    pub out: PathBuf,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            frames: 1,
+            out: PathBuf::from("frame.png"),
+        }
+    }
+}
+
+impl RunOptions {
+    /// Parses the shared flags from the process arguments.
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::parse(env::args().skip(1))
+    }
+
+    /// Parses the shared flags from `args`. Adapter settings start from the environment and flags override them.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Self> {
+        let mut adapter = AdapterSelection::from_env()?;
+        let mut headless = false;
+        let mut headless_options = HeadlessOptions::default();
+        let mut headless_flag = None;
+        let mut hot_reload = env::var("SHADER_HOT_RELOAD").is_ok_and(|value| matches!(value.as_str(), "1" | "true" | "yes"));
+        let mut pipeline_cache = !env::var("WGPU_PIPELINE_CACHE").is_ok_and(|value| matches!(value.as_str(), "0" | "false" | "no"));
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => headless = true,
+                "--hot-reload" => hot_reload = true,
+                "--no-pipeline-cache" => pipeline_cache = false,
+                "--frames" => {
+                    let value = next_value(&mut args, &arg)?;
+                    headless_options.frames = value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("--frames expects a number of frames, got '{}'", value))?;
+                    anyhow::ensure!(headless_options.frames > 0, "--frames must be at least 1");
+                    headless_flag = Some(arg);
+                },
+                "--out" => {
+                    headless_options.out = PathBuf::from(next_value(&mut args, &arg)?);
+                    headless_flag = Some(arg);
+                },
+                "--size" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let (width, height) = parse_size(&value)?;
+                    headless_options.width = width;
+                    headless_options.height = height;
+                    headless_flag = Some(arg);
+                },
+                // Anything else that looks like a flag is a typo, positional arguments are the binary's own.
+                _ => {
+                    if !adapter.apply_flag(&arg, &mut args)? && arg.starts_with("--") {
+                        anyhow::bail!("unknown option '{}'", arg);
+                    }
+                },
+            }
+        }
+
+        if let (false, Some(flag)) = (headless, headless_flag) {
+            anyhow::bail!("{} only applies together with --headless", flag);
+        }
+
+        Ok(Self {
+            headless: if headless { Some(headless_options) } else { None },
+            adapter,
+            hot_reload,
+            pipeline_cache: if pipeline_cache { DiskPipelineCache::default_directory() } else { None },
+        })
+    }
+}
+
+fn next_value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> anyhow::Result<String> {
+    args.next().ok_or_else(|| anyhow::anyhow!("{} is missing its value", flag))
+}
+
+// Sizes are written as WIDTHxHEIGHT, e.g. 800x600.
+fn parse_size(value: &str) -> anyhow::Result<(u32, u32)> {
+    let parsed = value
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+
+    match parsed {
+        Some((width, height)) if width > 0 && height > 0 => Ok((width, height)),
+        _ => anyhow::bail!("--size expects WIDTHxHEIGHT, got '{}'", value),
+    }
+}
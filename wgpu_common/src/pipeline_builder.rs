@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+
+use crate::wireframe::PipelineStyle;
+
+/// The blend states the exercises use, by name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blend {
+    /// Overwrite whatever is in the target, the learn_wgpu and Dr Wu default.
+    Replace,
+    /// Regular "over" blending with straight alpha.
+    Alpha,
+    PremultipliedAlpha,
+    /// Adds the fragment color to the target, e.g. for particles or glow.
+    Additive,
+    /// No blend state at all. Needed for targets that can't blend, such as integer formats.
+    Disabled,
+}
+
+impl Blend {
+    pub fn state(self) -> Option<wgpu::BlendState> {
+        match self {
+            Blend::Replace => Some(wgpu::BlendState::REPLACE),
+            Blend::Alpha => Some(wgpu::BlendState::ALPHA_BLENDING),
+            Blend::PremultipliedAlpha => Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            Blend::Additive => Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::OVER,
+            }),
+            Blend::Disabled => None,
+        }
+    }
+}
+
+/// Builds a render pipeline from the handful of things that differ between the exercises, instead of spelling out a
+/// whole `wgpu::RenderPipelineDescriptor`.
+///
+/// ```text
+/// let render_pipeline = RenderPipelineBuilder::new(&shader)
+///     .label("Render Pipeline")
+///     .vertex_buffer(Vertex::desc())
+///     .vertex_buffer(InstanceRaw::desc())
+///     .bind_group_layout(&texture_bind_group_layout)
+///     .bind_group_layout(&camera_bind_group_layout)
+///     .color_target(context.config.format)
+///     .cull_mode(Some(wgpu::Face::Back))
+///     .build(&context.device)?;
+/// ```
+///
+/// The defaults are `vs_main` and `fs_main`, a triangle list with counter-clockwise front faces and no culling,
+/// [`Blend::Replace`], no depth buffer and one sample per pixel. `build` checks the usual mistakes first (no color
+/// target, a sample count the formats don't support, clashing vertex locations, ...) and reports all of them in one
+/// error, rather than letting wgpu panic on the first.
+pub struct RenderPipelineBuilder<'a> {
+    label: Option<&'a str>,
+    shader: &'a wgpu::ShaderModule,
+    vertex_entry_point: &'a str,
+    fragment_entry_point: Option<&'a str>,
+    constants: &'a [(&'a str, f64)],
+    vertex_buffers: Vec<wgpu::VertexBufferLayout<'a>>,
+    layout: Option<&'a wgpu::PipelineLayout>,
+    bind_group_layouts: Vec<&'a wgpu::BindGroupLayout>,
+    color_targets: Vec<wgpu::TextureFormat>,
+    blend: Option<wgpu::BlendState>,
+    write_mask: wgpu::ColorWrites,
+    primitive: wgpu::PrimitiveState,
+    depth_format: Option<wgpu::TextureFormat>,
+    depth_compare: wgpu::CompareFunction,
+    depth_write: bool,
+    sample_count: u32,
+    alpha_to_coverage: bool,
+    cache: Option<&'a wgpu::PipelineCache>,
+}
+
+impl<'a> RenderPipelineBuilder<'a> {
+    pub fn new(shader: &'a wgpu::ShaderModule) -> Self {
+        Self {
+            label: None,
+            shader,
+            vertex_entry_point: "vs_main",
+            fragment_entry_point: Some("fs_main"),
+            constants: &[],
+            vertex_buffers: Vec::new(),
+            layout: None,
+            bind_group_layouts: Vec::new(),
+            color_targets: Vec::new(),
+            blend: Blend::Replace.state(),
+            write_mask: wgpu::ColorWrites::ALL,
+            primitive: wgpu::PrimitiveState::default(),
+            depth_format: None,
+            depth_compare: wgpu::CompareFunction::Less,
+            depth_write: true,
+            sample_count: 1,
+            alpha_to_coverage: false,
+            cache: None,
+        }
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn vertex_entry_point(mut self, entry_point: &'a str) -> Self {
+        self.vertex_entry_point = entry_point;
+        self
+    }
+
+    /// `None` builds a pipeline without a fragment stage, e.g. for a depth-only pass.
+    pub fn fragment_entry_point(mut self, entry_point: Option<&'a str>) -> Self {
+        self.fragment_entry_point = entry_point;
+        self
+    }
+
+    /// Values for the shader's `override` constants, by name.
+    pub fn constants(mut self, constants: &'a [(&'a str, f64)]) -> Self {
+        self.constants = constants;
+        self
+    }
+
+    /// Adds the layout of the vertex buffer bound to the next slot.
+    pub fn vertex_buffer(mut self, layout: wgpu::VertexBufferLayout<'a>) -> Self {
+        self.vertex_buffers.push(layout);
+        self
+    }
+
+    /// Adds several vertex buffer layouts, in slot order.
+    pub fn vertex_buffers(mut self, layouts: impl IntoIterator<Item = wgpu::VertexBufferLayout<'a>>) -> Self {
+        self.vertex_buffers.extend(layouts);
+        self
+    }
+
+    /// Uses an existing pipeline layout. Leave it out to have one made from the [`bind_group_layout`]s instead.
+    ///
+    /// [`bind_group_layout`]: RenderPipelineBuilder::bind_group_layout
+    pub fn layout(mut self, layout: &'a wgpu::PipelineLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Adds the layout for the next bind group, `@group(0)` first.
+    pub fn bind_group_layout(mut self, layout: &'a wgpu::BindGroupLayout) -> Self {
+        self.bind_group_layouts.push(layout);
+        self
+    }
+
+    /// Adds a color target, `@location(0)` first. Usually the surface format.
+    pub fn color_target(mut self, format: wgpu::TextureFormat) -> Self {
+        self.color_targets.push(format);
+        self
+    }
+
+    /// The blend preset for every color target.
+    pub fn blend(mut self, blend: Blend) -> Self {
+        self.blend = blend.state();
+        self
+    }
+
+    /// A blend state that isn't one of the [`Blend`] presets.
+    pub fn blend_state(mut self, blend: Option<wgpu::BlendState>) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn write_mask(mut self, write_mask: wgpu::ColorWrites) -> Self {
+        self.write_mask = write_mask;
+        self
+    }
+
+    /// Replaces the whole primitive state, e.g. with [`Topology::primitive_state`](crate::Topology::primitive_state).
+    pub fn primitive(mut self, primitive: wgpu::PrimitiveState) -> Self {
+        self.primitive = primitive;
+        self
+    }
+
+    pub fn topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.primitive.topology = topology;
+        self
+    }
+
+    pub fn strip_index_format(mut self, format: Option<wgpu::IndexFormat>) -> Self {
+        self.primitive.strip_index_format = format;
+        self
+    }
+
+    pub fn front_face(mut self, front_face: wgpu::FrontFace) -> Self {
+        self.primitive.front_face = front_face;
+        self
+    }
+
+    pub fn cull_mode(mut self, cull_mode: Option<wgpu::Face>) -> Self {
+        self.primitive.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.primitive.polygon_mode = polygon_mode;
+        self
+    }
+
+    /// Sets the fragment entry point, override constants, polygon mode and blend state for a wireframe
+    /// [`PipelineStyle`]. Call it after [`blend`](RenderPipelineBuilder::blend), which `Fill` keeps.
+    pub fn style(mut self, style: PipelineStyle) -> Self {
+        self.fragment_entry_point = Some(style.fragment_entry_point());
+        self.constants = style.constants();
+        self.primitive.polygon_mode = style.polygon_mode();
+        self.blend = style.blend(self.blend);
+        self
+    }
+
+    /// Adds a depth buffer with `format`, testing with `Less` and writing depth.
+    pub fn depth(mut self, format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(format);
+        self
+    }
+
+    pub fn depth_compare(mut self, compare: wgpu::CompareFunction) -> Self {
+        self.depth_compare = compare;
+        self
+    }
+
+    pub fn depth_write(mut self, depth_write: bool) -> Self {
+        self.depth_write = depth_write;
+        self
+    }
+
+    /// Samples per pixel. Has to match the sample count of the textures the render pass draws into.
+    pub fn sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub fn alpha_to_coverage(mut self, alpha_to_coverage: bool) -> Self {
+        self.alpha_to_coverage = alpha_to_coverage;
+        self
+    }
+
+    /// A driver pipeline cache to compile with, usually [`GpuContext::pipeline_cache`](crate::GpuContext::pipeline_cache).
+    /// `None` (the default) compiles from scratch.
+    pub fn cache(mut self, cache: Option<&'a wgpu::PipelineCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Checks the settings against `device` and creates the pipeline.
+    pub fn build(self, device: &wgpu::Device) -> anyhow::Result<wgpu::RenderPipeline> {
+        let problems = self.problems(device);
+        if !problems.is_empty() {
+            let name = self.label.map(|label| format!(" '{}'", label)).unwrap_or_default();
+            anyhow::bail!("invalid render pipeline{}:\n  - {}", name, problems.join("\n  - "));
+        }
+
+        let created_layout;
+        let layout = match self.layout {
+            Some(layout) => Some(layout),
+            None if self.bind_group_layouts.is_empty() => None,
+            None => {
+                created_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: self.label,
+                    bind_group_layouts: &self.bind_group_layouts,
+                    push_constant_ranges: &[],
+                });
+                Some(&created_layout)
+            },
+        };
+
+        let targets: Vec<_> = self
+            .color_targets
+            .iter()
+            .map(|&format| {
+                Some(wgpu::ColorTargetState {
+                    format,
+                    blend: self.blend,
+                    write_mask: self.write_mask,
+                })
+            })
+            .collect();
+
+        Ok(device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: self.label,
+            layout,
+            vertex: wgpu::VertexState {
+                module: self.shader,
+                entry_point: Some(self.vertex_entry_point),
+                buffers: &self.vertex_buffers,
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: self.constants,
+                    ..Default::default()
+                },
+            },
+            fragment: self.fragment_entry_point.map(|entry_point| wgpu::FragmentState {
+                module: self.shader,
+                entry_point: Some(entry_point),
+                targets: &targets,
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: self.constants,
+                    ..Default::default()
+                },
+            }),
+            primitive: self.primitive,
+            depth_stencil: self.depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: self.depth_write,
+                depth_compare: self.depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: self.sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: self.alpha_to_coverage,
+            },
+            multiview: None,
+            cache: self.cache,
+        }))
+    }
+
+    /// Everything wrong with the settings, in words.
+    fn problems(&self, device: &wgpu::Device) -> Vec<String> {
+        let features = device.features();
+        let limits = device.limits();
+        let mut problems = Vec::new();
+
+        if self.layout.is_some() && !self.bind_group_layouts.is_empty() {
+            problems.push("both a pipeline layout and bind group layouts were given, use one or the other".to_string());
+        }
+        if self.bind_group_layouts.len() > limits.max_bind_groups as usize {
+            problems.push(format!(
+                "{} bind group layouts, but the device only supports {}",
+                self.bind_group_layouts.len(),
+                limits.max_bind_groups
+            ));
+        }
+
+        // Fragment stage and color targets.
+        match self.fragment_entry_point {
+            Some(entry_point) if self.color_targets.is_empty() && self.depth_format.is_none() => problems.push(format!(
+                "the fragment stage '{}' has no color target to write to, add one with .color_target(format)",
+                entry_point
+            )),
+            None if !self.color_targets.is_empty() => problems.push(format!(
+                "{} color target(s) but no fragment stage to write them, set .fragment_entry_point(Some(..))",
+                self.color_targets.len()
+            )),
+            None if self.depth_format.is_none() => {
+                problems.push("no fragment stage and no depth buffer, the pipeline wouldn't write anything".to_string())
+            },
+            _ => {},
+        }
+        if self.color_targets.len() > limits.max_color_attachments as usize {
+            problems.push(format!(
+                "{} color targets, but the device only supports {}",
+                self.color_targets.len(),
+                limits.max_color_attachments
+            ));
+        }
+        for (location, &format) in self.color_targets.iter().enumerate() {
+            let flags = format.guaranteed_format_features(features).flags;
+            let allowed_usages = format.guaranteed_format_features(features).allowed_usages;
+            if format.has_depth_aspect() || format.has_stencil_aspect() {
+                problems.push(format!(
+                    "color target {} is {:?}, which is a depth/stencil format, use .depth(format) for it",
+                    location, format
+                ));
+            } else if !allowed_usages.contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+                problems.push(format!("color target {} is {:?}, which can't be rendered to", location, format));
+            } else if self.blend.is_some() && !flags.contains(wgpu::TextureFormatFeatureFlags::BLENDABLE) {
+                problems.push(format!(
+                    "color target {} is {:?}, which can't be blended, use .blend(Blend::Disabled)",
+                    location, format
+                ));
+            }
+        }
+
+        if let Some(format) = self.depth_format {
+            if !format.has_depth_aspect() {
+                problems.push(format!("depth format {:?} has no depth aspect, try Depth32Float", format));
+            }
+        }
+
+        // Sample count. With adapter specific format features the adapter decides, which the device can't tell us.
+        if !self.sample_count.is_power_of_two() || self.sample_count > 16 {
+            problems.push(format!("sample count {} has to be 1, 2, 4, 8 or 16", self.sample_count));
+        } else if !features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+            for &format in self.color_targets.iter().chain(self.depth_format.iter()) {
+                let flags = format.guaranteed_format_features(features).flags;
+                if !flags.sample_count_supported(self.sample_count) {
+                    let supported: Vec<_> = flags.supported_sample_counts().iter().map(|count| count.to_string()).collect();
+                    problems.push(format!(
+                        "sample count {} isn't supported by {:?} (supported: {}), the render pass textures have to use the same count",
+                        self.sample_count,
+                        format,
+                        supported.join(", ")
+                    ));
+                }
+            }
+        }
+        if self.alpha_to_coverage && self.sample_count == 1 {
+            problems.push("alpha to coverage needs a sample count above 1".to_string());
+        }
+
+        // Primitive state.
+        if let Some(format) = self.primitive.strip_index_format.filter(|_| !self.primitive.topology.is_strip()) {
+            problems.push(format!(
+                "strip index format {:?} set for {:?}, only strip topologies take one",
+                format, self.primitive.topology
+            ));
+        }
+        let polygon_feature = match self.primitive.polygon_mode {
+            wgpu::PolygonMode::Fill => None,
+            wgpu::PolygonMode::Line => Some((wgpu::Features::POLYGON_MODE_LINE, "POLYGON_MODE_LINE")),
+            wgpu::PolygonMode::Point => Some((wgpu::Features::POLYGON_MODE_POINT, "POLYGON_MODE_POINT")),
+        };
+        if let Some((_, name)) = polygon_feature.filter(|&(feature, _)| !features.contains(feature)) {
+            problems.push(format!(
+                "PolygonMode::{:?} needs Features::{}, which the device doesn't have",
+                self.primitive.polygon_mode, name
+            ));
+        }
+
+        self.vertex_problems(&limits, &mut problems);
+        problems
+    }
+
+    fn vertex_problems(&self, limits: &wgpu::Limits, problems: &mut Vec<String>) {
+        if self.vertex_buffers.len() > limits.max_vertex_buffers as usize {
+            problems.push(format!(
+                "{} vertex buffers, but the device only supports {}",
+                self.vertex_buffers.len(),
+                limits.max_vertex_buffers
+            ));
+        }
+
+        let mut locations = HashMap::new();
+        for (slot, buffer) in self.vertex_buffers.iter().enumerate() {
+            for attribute in buffer.attributes {
+                match locations.insert(attribute.shader_location, slot) {
+                    Some(other) if other == slot => problems.push(format!(
+                        "@location({}) is used twice in vertex buffer {}",
+                        attribute.shader_location, slot
+                    )),
+                    Some(other) => problems.push(format!(
+                        "@location({}) is used by vertex buffer {} and vertex buffer {}",
+                        attribute.shader_location, other, slot
+                    )),
+                    None => {},
+                }
+                if attribute.shader_location >= limits.max_vertex_attributes {
+                    problems.push(format!(
+                        "@location({}) in vertex buffer {} is past the device's {} vertex attributes",
+                        attribute.shader_location, slot, limits.max_vertex_attributes
+                    ));
+                }
+
+                let end = attribute.offset + attribute.format.size();
+                if buffer.array_stride != 0 && end > buffer.array_stride {
+                    problems.push(format!(
+                        "@location({}) in vertex buffer {} ends at byte {}, past the {} byte stride",
+                        attribute.shader_location, slot, end, buffer.array_stride
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+/// What a cached render pipeline depends on. Everything else about the pipeline is fixed per exercise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub topology: wgpu::PrimitiveTopology,
+    pub format: wgpu::TextureFormat,
+}
+
+/// Render pipelines built once and reused every frame.
+///
+/// Pipelines are keyed by topology and surface format. Switching topology back and forth reuses what was built
+/// before, while a new surface format drops every pipeline since none of them can render to it anymore. Not to be
+/// confused with the driver's `wgpu::PipelineCache` from
+/// [`GpuContext::pipeline_cache`](crate::GpuContext::pipeline_cache), which pipelines built here can be compiled with.
+#[derive(Default)]
+pub struct RenderPipelineCache {
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+    format: Option<wgpu::TextureFormat>,
+    builds: u32,
+    rebuild_always: bool,
+}
+
+impl RenderPipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// With `true`, `get_or_create` builds the pipeline again on every call, like rendering without a cache did.
+    /// `exercises -- bench` uses it to compare the two.
+    pub fn rebuild_always(mut self, rebuild_always: bool) -> Self {
+        self.rebuild_always = rebuild_always;
+        self
+    }
+
+    /// Returns the pipeline for `key`, calling `create` only when it hasn't been built yet.
+    pub fn get_or_create<F>(&mut self, key: PipelineKey, create: F) -> &wgpu::RenderPipeline
+    where
+        F: FnOnce(&PipelineKey) -> wgpu::RenderPipeline,
+    {
+        if self.format != Some(key.format) {
+            if !self.pipelines.is_empty() {
+                log::info!("Surface format changed to {:?}, dropping {} cached pipeline(s)", key.format, self.pipelines.len());
+            }
+            self.pipelines.clear();
+            self.format = Some(key.format);
+        }
+
+        if self.rebuild_always {
+            self.pipelines.remove(&key);
+        }
+
+        let builds = &mut self.builds;
+        self.pipelines.entry(key).or_insert_with(|| {
+            log::info!("Building render pipeline for {:?} / {:?}", key.topology, key.format);
+            *builds += 1;
+            create(&key)
+        })
+    }
+
+    /// Drops every cached pipeline, e.g. after the shader changed.
+    pub fn clear(&mut self) {
+        self.pipelines.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// How many pipelines have been built since the cache was created.
+    pub fn builds(&self) -> u32 {
+        self.builds
+    }
+}
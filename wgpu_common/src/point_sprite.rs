@@ -0,0 +1,214 @@
+use crate::screen_quads::{QuadInstances, QuadRenderer, QuadUniform};
+
+/// What a [`PointSprite`] looks like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointShape {
+    Square,
+    Circle,
+    /// A circle that fades out from its center.
+    SoftDisc,
+}
+
+impl PointShape {
+    pub fn next(self) -> Self {
+        match self {
+            PointShape::Square => PointShape::Circle,
+            PointShape::Circle => PointShape::SoftDisc,
+            PointShape::SoftDisc => PointShape::Square,
+        }
+    }
+
+    /// The value of [`PointSprite::shape`], and of the `SHAPE_` constants in `point_sprite.wgsl`.
+    pub fn id(self) -> u32 {
+        match self {
+            PointShape::Square => 0,
+            PointShape::Circle => 1,
+            PointShape::SoftDisc => 2,
+        }
+    }
+}
+
+/// What [`PointSprite::size`] is measured in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SizeUnit {
+    /// The same number of pixels at any distance.
+    Pixels,
+    /// Units of the space the positions are in, so sprites further from a perspective camera get smaller. Measured
+    /// vertically, in NDC that is half the height of the view per unit.
+    World,
+}
+
+/// The size, color and shape sprites get unless they are given their own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointStyle {
+    /// The width of the square or the diameter of the circle, in the [`SizeUnit`] the sprites are drawn with.
+    pub size: f32,
+    /// Straight (not premultiplied) RGBA.
+    pub color: [f32; 4],
+    pub shape: PointShape,
+}
+
+impl Default for PointStyle {
+    fn default() -> Self {
+        Self {
+            size: 10.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            shape: PointShape::Circle,
+        }
+    }
+}
+
+/// One point drawn by [`PointSpriteRenderer`], as an instance of its quad.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, crate::VertexLayout)]
+#[vertex(step_mode = "instance")]
+pub struct PointSprite {
+    pub position: [f32; 3],
+    pub size: f32,
+    pub color: [f32; 4],
+    /// [`PointShape::id`].
+    pub shape: u32,
+}
+
+impl PointSprite {
+    pub fn new(position: [f32; 3], style: &PointStyle) -> Self {
+        Self {
+            position,
+            size: style.size,
+            color: style.color,
+            shape: style.shape.id(),
+        }
+    }
+
+    /// A sprite for every point `PointList` would draw from `positions`.
+    pub fn from_positions(positions: &[[f32; 3]], style: &PointStyle) -> Vec<Self> {
+        positions.iter().map(|&position| Self::new(position, style)).collect()
+    }
+
+    /// A sprite for every point an indexed `PointList` draw of `positions` would draw.
+    pub fn from_indices<I: Copy + Into<u32>>(positions: &[[f32; 3]], indices: &[I], style: &PointStyle) -> Vec<Self> {
+        indices.iter().map(|&index| Self::new(positions[index.into() as usize], style)).collect()
+    }
+}
+
+// `Sprites` in point_sprite.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteUniform {
+    view_proj: [[f32; 4]; 4],
+    viewport: [f32; 2],
+    unit: u32,
+    _padding: u32,
+}
+
+impl SpriteUniform {
+    fn unit(unit: SizeUnit) -> u32 {
+        match unit {
+            SizeUnit::Pixels => 0,
+            SizeUnit::World => 1,
+        }
+    }
+}
+
+impl QuadUniform for SpriteUniform {
+    fn set_view_proj(&mut self, view_proj: [[f32; 4]; 4]) {
+        self.view_proj = view_proj;
+    }
+
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        self.viewport = [width as f32, height as f32];
+    }
+}
+
+/// Draws [`PointSprites`] as squares, circles or soft discs of any size, where `PointList` only ever draws single
+/// pixels.
+///
+/// Each [`PointSprite`] is an instance of a quad that the vertex shader places around the point in screen space, with
+/// its own size, color and shape. Sizes are in pixels or in world units ([`SizeUnit`]) and edges are anti-aliased,
+/// see `point_sprite.wgsl`. Positions go through a view-projection matrix, identity for 2D points in NDC.
+///
+/// ```text
+/// let sprite_renderer = PointSpriteRenderer::new(&device, context.view_format(), None, context.pipeline_cache())?;
+/// let sprites = PointSprite::from_positions(&positions, &PointStyle::default());
+/// let mut point_sprites = sprite_renderer.sprites(&device, &sprites, SizeUnit::Pixels, (width, height));
+/// point_sprites.set_view_proj(&queue, camera_uniform.view_proj);     // 3D only
+///
+/// sprite_renderer.draw(&mut renderpass, &point_sprites);
+/// ```
+pub struct PointSpriteRenderer {
+    quads: QuadRenderer<PointSprite, SpriteUniform>,
+}
+
+impl PointSpriteRenderer {
+    pub const SHADER: &'static str = include_str!("point_sprite.wgsl");
+
+    /// A pipeline for `format` color targets, blending over what is there. With a `depth_format` the sprites are depth
+    /// tested and written at the depth of their point.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        cache: Option<&wgpu::PipelineCache>,
+    ) -> anyhow::Result<Self> {
+        let quads = QuadRenderer::new(device, "Point Sprite", "point_sprite.wgsl", Self::SHADER, format, depth_format, cache)?;
+        Ok(Self { quads })
+    }
+
+    /// Uploads `sprites`, sized in `unit`, to draw into a `viewport` of (width, height) pixels.
+    pub fn sprites(&self, device: &wgpu::Device, sprites: &[PointSprite], unit: SizeUnit, viewport: (u32, u32)) -> PointSprites {
+        let uniform = SpriteUniform {
+            unit: SpriteUniform::unit(unit),
+            ..bytemuck::Zeroable::zeroed()
+        };
+
+        PointSprites {
+            quads: self.quads.instances(device, sprites, uniform, viewport),
+            unit,
+        }
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, sprites: &PointSprites) {
+        self.quads.draw(render_pass, &sprites.quads);
+    }
+}
+
+/// Point sprites on the GPU, made by [`PointSpriteRenderer::sprites`].
+pub struct PointSprites {
+    quads: QuadInstances<PointSprite, SpriteUniform>,
+    unit: SizeUnit,
+}
+
+impl PointSprites {
+    /// How many sprites are drawn.
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn unit(&self) -> SizeUnit {
+        self.unit
+    }
+
+    pub fn set_unit(&mut self, queue: &wgpu::Queue, unit: SizeUnit) {
+        self.unit = unit;
+        self.quads.update(queue, |uniform| uniform.unit = SpriteUniform::unit(unit));
+    }
+
+    /// The size of the target in pixels, after a resize.
+    pub fn set_viewport(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
+        self.quads.set_viewport(queue, width, height);
+    }
+
+    /// Column major, like the learn_wgpu `CameraUniform`.
+    pub fn set_view_proj(&mut self, queue: &wgpu::Queue, view_proj: [[f32; 4]; 4]) {
+        self.quads.set_view_proj(queue, view_proj);
+    }
+
+    /// Replaces the sprites, keeping the unit and view.
+    pub fn set_sprites(&mut self, device: &wgpu::Device, sprites: &[PointSprite]) {
+        self.quads.set_instances(device, sprites);
+    }
+}
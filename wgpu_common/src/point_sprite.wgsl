@@ -0,0 +1,102 @@
+// Sized, shaped points for PointSpriteRenderer (point_sprite.rs).
+//
+// Every PointSprite instance is drawn as a screen aligned quad around its point, six vertices per instance, and the
+// fragment shader cuts the shape out of it with anti-aliased edges.
+
+const SHAPE_SQUARE: u32 = 0u;
+const SHAPE_CIRCLE: u32 = 1u;
+const SHAPE_SOFT_DISC: u32 = 2u;
+
+const UNIT_PIXELS: u32 = 0u;
+const UNIT_WORLD: u32 = 1u;
+
+// How many pixels the edges fade out over.
+const FEATHER: f32 = 1.0;
+// Points closer to the camera plane than this can't be projected and aren't drawn.
+const NEAR_W: f32 = 1e-5;
+
+// SpriteUniform in point_sprite.rs.
+struct Sprites {
+    view_proj: mat4x4<f32>,
+    viewport: vec2<f32>,
+    unit: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> sprites: Sprites;
+
+struct Sprite {
+    @location(0) position: vec3<f32>,
+    @location(1) size: f32,
+    @location(2) color: vec4<f32>,
+    @location(3) shape: u32,
+};
+
+struct VOutput {
+    @builtin(position) position: vec4<f32>,
+    // Pixels from the center of the sprite, y down.
+    @location(0) offset: vec2<f32>,
+    @location(1) @interpolate(flat) radius: f32,
+    @location(2) @interpolate(flat) color: vec4<f32>,
+    @location(3) @interpolate(flat) shape: u32,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, sprite: Sprite) -> VOutput {
+    var out: VOutput;
+    let center = sprites.view_proj * vec4<f32>(sprite.position, 1.0);
+
+    // Behind the camera: every vertex at the same point, so nothing is rasterized.
+    if (center.w < NEAR_W) {
+        out.position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        return out;
+    }
+
+    var radius = sprite.size * 0.5;
+    if (sprites.unit == UNIT_WORLD) {
+        // A world unit across the view is as long as a row of the view-projection matrix in clip space, which the
+        // perspective divide then shrinks with the distance. The vertical one keeps sprites round in NDC too.
+        let m = sprites.view_proj;
+        let scale = length(vec3<f32>(m[0].y, m[1].y, m[2].y));
+        radius = radius * scale / center.w * sprites.viewport.y * 0.5;
+    }
+
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    out.offset = corners[vertex_index] * (max(radius, 0.5) + FEATHER);
+
+    // Offsetting in pixels times w keeps the size the same at any depth, the pixel radius already shrank above.
+    let ndc_offset = out.offset / sprites.viewport * vec2<f32>(2.0, -2.0);
+    out.position = vec4<f32>(center.xy + ndc_offset * center.w, center.zw);
+    out.radius = radius;
+    out.color = sprite.color;
+    out.shape = sprite.shape;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VOutput) -> @location(0) vec4<f32> {
+    // Sprites smaller than a pixel are drawn a pixel wide and fainter instead.
+    let radius = max(in.radius, 0.5);
+
+    var coverage: f32;
+    if (in.shape == SHAPE_SQUARE) {
+        coverage = clamp(radius - max(abs(in.offset.x), abs(in.offset.y)) + 0.5, 0.0, 1.0);
+    } else if (in.shape == SHAPE_SOFT_DISC) {
+        coverage = 1.0 - smoothstep(0.0, radius, length(in.offset));
+    } else {
+        coverage = clamp(radius - length(in.offset) + 0.5, 0.0, 1.0);
+    }
+    coverage *= min(in.radius * 2.0, 1.0);
+
+    if (coverage <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
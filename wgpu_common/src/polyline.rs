@@ -0,0 +1,296 @@
+use crate::screen_quads::{QuadInstances, QuadRenderer, QuadUniform};
+use crate::topology::{primitive_restart_index, Topology};
+
+/// How two segments of a strip meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet, or beveled when that point is further than
+    /// [`LineStyle::miter_limit`] half widths away.
+    Miter,
+    Round,
+    /// The outer corners are connected with a straight edge.
+    Bevel,
+}
+
+impl LineJoin {
+    pub fn next(self) -> Self {
+        match self {
+            LineJoin::Miter => LineJoin::Round,
+            LineJoin::Round => LineJoin::Bevel,
+            LineJoin::Bevel => LineJoin::Miter,
+        }
+    }
+}
+
+/// What the open ends of a line look like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// Cut off square at the end point.
+    Butt,
+    /// Cut off square half a width past the end point.
+    Square,
+    Round,
+}
+
+impl LineCap {
+    pub fn next(self) -> Self {
+        match self {
+            LineCap::Butt => LineCap::Square,
+            LineCap::Square => LineCap::Round,
+            LineCap::Round => LineCap::Butt,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineStyle {
+    /// In pixels, whatever the distance to the camera. Lines thinner than a pixel are drawn a pixel wide and fainter.
+    pub width: f32,
+    /// Straight (not premultiplied) RGBA.
+    pub color: [f32; 4],
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// How long a miter may get, in half line widths, before it is beveled instead. 4 like SVG's default, which
+    /// bevels corners sharper than about 29 degrees.
+    pub miter_limit: f32,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self {
+            width: 8.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+/// One segment of a thick line, drawn as an instance of [`LineRenderer`]'s quad. `prev` and `next` are the points
+/// before `start` and after `end`, for the joins. An end without a neighbour repeats its own point and gets a cap.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, crate::VertexLayout)]
+#[vertex(step_mode = "instance")]
+pub struct LineSegment {
+    pub prev: [f32; 3],
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    pub next: [f32; 3],
+}
+
+impl LineSegment {
+    /// A segment on its own, capped at both ends.
+    pub fn new(start: [f32; 3], end: [f32; 3]) -> Self {
+        Self {
+            prev: start,
+            start,
+            end,
+            next: end,
+        }
+    }
+
+    pub fn joins_prev(&self) -> bool {
+        self.prev != self.start
+    }
+
+    pub fn joins_next(&self) -> bool {
+        self.next != self.end
+    }
+
+    /// What `LineList` draws: a segment for every pair of points, each capped at both ends. A last odd point is left
+    /// out.
+    pub fn list(positions: &[[f32; 3]]) -> Vec<Self> {
+        positions.chunks_exact(2).map(|pair| Self::new(pair[0], pair[1])).collect()
+    }
+
+    /// What `LineStrip` draws: one line through every point, joined at each of them and capped at the two ends.
+    /// Repeated points are skipped, and a strip that ends where it started is closed with a join instead of caps.
+    pub fn strip(positions: &[[f32; 3]]) -> Vec<Self> {
+        let mut points = positions.to_vec();
+        points.dedup();
+        if points.len() < 2 {
+            return Vec::new();
+        }
+
+        let last = points.len() - 1;
+        let closed = points.len() > 3 && points[0] == points[last];
+        (0..last)
+            .map(|i| Self {
+                prev: match i {
+                    0 if closed => points[last - 1],
+                    0 => points[0],
+                    _ => points[i - 1],
+                },
+                start: points[i],
+                end: points[i + 1],
+                next: match points.get(i + 2) {
+                    Some(&next) => next,
+                    None if closed => points[1],
+                    None => points[last],
+                },
+            })
+            .collect()
+    }
+
+    /// [`list`](Self::list) or [`strip`](Self::strip) for the line topologies. Points and triangles have no segments.
+    pub fn from_topology(positions: &[[f32; 3]], topology: Topology) -> Vec<Self> {
+        match topology {
+            Topology::LineList => Self::list(positions),
+            Topology::LineStrip => Self::strip(positions),
+            Topology::PointList | Topology::TriangleList | Topology::TriangleStrip => Vec::new(),
+        }
+    }
+
+    /// The segments an indexed draw of `positions` makes. Strips are split at the primitive restart value of
+    /// `format`, like the GPU does.
+    pub fn from_indices<I: Copy + Into<u32>>(
+        positions: &[[f32; 3]],
+        indices: &[I],
+        format: wgpu::IndexFormat,
+        topology: Topology,
+    ) -> Vec<Self> {
+        let restart = primitive_restart_index(format);
+        let lookup = |indices: &[I]| -> Vec<[f32; 3]> { indices.iter().map(|&index| positions[index.into() as usize]).collect() };
+
+        if !topology.is_strip() {
+            return Self::from_topology(&lookup(indices), topology);
+        }
+        indices
+            .split(|&index| index.into() == restart)
+            .flat_map(|strip| Self::from_topology(&lookup(strip), topology))
+            .collect()
+    }
+}
+
+// `Lines` in polyline.wgsl.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineUniform {
+    view_proj: [[f32; 4]; 4],
+    color: [f32; 4],
+    viewport: [f32; 2],
+    width: f32,
+    miter_limit: f32,
+    join_style: u32,
+    cap_style: u32,
+    _padding: [u32; 2],
+}
+
+impl LineUniform {
+    fn set_style(&mut self, style: &LineStyle) {
+        self.color = style.color;
+        self.width = style.width;
+        self.miter_limit = style.miter_limit;
+        self.join_style = match style.join {
+            LineJoin::Miter => 0,
+            LineJoin::Round => 1,
+            LineJoin::Bevel => 2,
+        };
+        self.cap_style = match style.cap {
+            LineCap::Butt => 0,
+            LineCap::Square => 1,
+            LineCap::Round => 2,
+        };
+    }
+}
+
+impl QuadUniform for LineUniform {
+    fn set_view_proj(&mut self, view_proj: [[f32; 4]; 4]) {
+        self.view_proj = view_proj;
+    }
+
+    fn set_viewport(&mut self, width: u32, height: u32) {
+        self.viewport = [width as f32, height as f32];
+    }
+}
+
+/// Draws [`Lines`] of any width with miter, round or bevel joins, caps and anti-aliased edges, where `LineList` and
+/// `LineStrip` only ever draw aliased lines one pixel wide.
+///
+/// Each [`LineSegment`] is an instance of a quad that the vertex shader places around the segment in screen space, so
+/// the width stays in pixels under any projection. The fragment shader cuts the joins and caps out of the quads and
+/// fades the edges, see `polyline.wgsl`. Positions go through a view-projection matrix, identity for 2D lines in NDC.
+///
+/// ```text
+/// let line_renderer = LineRenderer::new(&device, context.view_format(), None, context.pipeline_cache())?;
+/// let mut lines = line_renderer.lines(&device, &LineSegment::strip(&positions), &LineStyle::default(), (width, height));
+/// lines.set_view_proj(&queue, camera_uniform.view_proj);     // 3D only
+///
+/// line_renderer.draw(&mut renderpass, &lines);
+/// ```
+pub struct LineRenderer {
+    quads: QuadRenderer<LineSegment, LineUniform>,
+}
+
+impl LineRenderer {
+    pub const SHADER: &'static str = include_str!("polyline.wgsl");
+
+    /// A pipeline for `format` color targets, blending over what is there. With a `depth_format` the lines are depth
+    /// tested and written like any other geometry.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        cache: Option<&wgpu::PipelineCache>,
+    ) -> anyhow::Result<Self> {
+        let quads = QuadRenderer::new(device, "Line", "polyline.wgsl", Self::SHADER, format, depth_format, cache)?;
+        Ok(Self { quads })
+    }
+
+    /// Uploads `segments` to draw with `style` into a `viewport` of (width, height) pixels.
+    pub fn lines(&self, device: &wgpu::Device, segments: &[LineSegment], style: &LineStyle, viewport: (u32, u32)) -> Lines {
+        let mut uniform: LineUniform = bytemuck::Zeroable::zeroed();
+        uniform.set_style(style);
+
+        Lines {
+            quads: self.quads.instances(device, segments, uniform, viewport),
+            style: *style,
+        }
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, lines: &Lines) {
+        self.quads.draw(render_pass, &lines.quads);
+    }
+}
+
+/// Line segments on the GPU with their style, made by [`LineRenderer::lines`].
+pub struct Lines {
+    quads: QuadInstances<LineSegment, LineUniform>,
+    style: LineStyle,
+}
+
+impl Lines {
+    /// How many segments are drawn.
+    pub fn len(&self) -> usize {
+        self.quads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn style(&self) -> &LineStyle {
+        &self.style
+    }
+
+    pub fn set_style(&mut self, queue: &wgpu::Queue, style: &LineStyle) {
+        self.style = *style;
+        self.quads.update(queue, |uniform| uniform.set_style(style));
+    }
+
+    /// The size of the target in pixels, after a resize.
+    pub fn set_viewport(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
+        self.quads.set_viewport(queue, width, height);
+    }
+
+    /// Column major, like the learn_wgpu `CameraUniform`.
+    pub fn set_view_proj(&mut self, queue: &wgpu::Queue, view_proj: [[f32; 4]; 4]) {
+        self.quads.set_view_proj(queue, view_proj);
+    }
+
+    /// Replaces the segments, keeping the style and view.
+    pub fn set_segments(&mut self, device: &wgpu::Device, segments: &[LineSegment]) {
+        self.quads.set_instances(device, segments);
+    }
+}
@@ -0,0 +1,217 @@
+// Thick, anti-aliased lines for LineRenderer (polyline.rs).
+//
+// Every LineSegment instance is drawn as a quad around its segment in screen space, six vertices per instance. The
+// fragment shader then cuts the line out of that quad with a signed distance in pixels: the segment's body, its caps
+// and its half of each join. Joins are split between the two segments along the bisector of the corner, so no pixel
+// is blended twice.
+
+const JOIN_MITER: u32 = 0u;
+const JOIN_ROUND: u32 = 1u;
+const JOIN_BEVEL: u32 = 2u;
+
+const CAP_BUTT: u32 = 0u;
+const CAP_SQUARE: u32 = 1u;
+const CAP_ROUND: u32 = 2u;
+
+// How many pixels the edges fade out over.
+const FEATHER: f32 = 1.0;
+// Points closer to the camera plane than this can't be projected, segments are clipped there.
+const NEAR_W: f32 = 1e-5;
+// The distance returned for pixels that belong to the neighbouring segment.
+const OUTSIDE: f32 = 1e9;
+
+// LineUniform in polyline.rs.
+struct Lines {
+    view_proj: mat4x4<f32>,
+    color: vec4<f32>,
+    viewport: vec2<f32>,
+    width: f32,
+    miter_limit: f32,
+    join_style: u32,
+    cap_style: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> lines: Lines;
+
+struct Segment {
+    @location(0) prev: vec3<f32>,
+    @location(1) start: vec3<f32>,
+    @location(2) end: vec3<f32>,
+    @location(3) next: vec3<f32>,
+};
+
+struct VOutput {
+    @builtin(position) position: vec4<f32>,
+    // The segment and its neighbours in framebuffer pixels, the space @builtin(position) is in for fs_main.
+    @location(0) @interpolate(flat) prev: vec2<f32>,
+    @location(1) @interpolate(flat) start: vec2<f32>,
+    @location(2) @interpolate(flat) end: vec2<f32>,
+    @location(3) @interpolate(flat) next: vec2<f32>,
+    // 1 where the start / end joins another segment, 0 where it is capped.
+    @location(4) @interpolate(flat) joined: vec2<u32>,
+};
+
+fn to_pixels(clip: vec4<f32>) -> vec2<f32> {
+    return (clip.xy / clip.w * vec2<f32>(0.5, -0.5) + 0.5) * lines.viewport;
+}
+
+// Lines thinner than a pixel are drawn a pixel wide and fainter instead.
+fn half_width() -> f32 {
+    return max(lines.width, 1.0) * 0.5;
+}
+
+fn direction(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    let delta = b - a;
+    if (dot(delta, delta) < 1e-12) {
+        return vec2<f32>(1.0, 0.0);
+    }
+    return normalize(delta);
+}
+
+// How far the quad has to reach past an end, along the segment, to hold its join or cap.
+fn reach(joined: bool) -> f32 {
+    let half = half_width();
+    if (joined && lines.join_style == JOIN_MITER) {
+        return half * max(lines.miter_limit, 1.0) + FEATHER;
+    }
+    if (!joined && lines.cap_style == CAP_BUTT) {
+        return FEATHER;
+    }
+    return half + FEATHER;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, segment: Segment) -> VOutput {
+    var out: VOutput;
+    var start = lines.view_proj * vec4<f32>(segment.start, 1.0);
+    var end = lines.view_proj * vec4<f32>(segment.end, 1.0);
+    let prev = lines.view_proj * vec4<f32>(segment.prev, 1.0);
+    let next = lines.view_proj * vec4<f32>(segment.next, 1.0);
+
+    // Entirely behind the camera: every vertex at the same point, so nothing is rasterized.
+    if (start.w < NEAR_W && end.w < NEAR_W) {
+        out.position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        return out;
+    }
+
+    // A segment's neighbour is itself at the ends of a line.
+    var joined = vec2<bool>(
+        any(segment.prev != segment.start) && prev.w >= NEAR_W,
+        any(segment.next != segment.end) && next.w >= NEAR_W,
+    );
+    if (start.w < NEAR_W) {
+        start = mix(start, end, (NEAR_W - start.w) / (end.w - start.w));
+        joined.x = false;
+    }
+    if (end.w < NEAR_W) {
+        end = mix(end, start, (NEAR_W - end.w) / (start.w - end.w));
+        joined.y = false;
+    }
+
+    let a = to_pixels(start);
+    let b = to_pixels(end);
+    let along = direction(a, b);
+    let across = vec2<f32>(-along.y, along.x);
+
+    // x picks the end, y the side of the line.
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+    let side = across * corner.y * (half_width() + FEATHER);
+
+    // Offsetting in pixels and multiplying back by w keeps the width the same at any depth.
+    var pixel = a - along * reach(joined.x) + side;
+    var clip = start;
+    if (corner.x > 0.5) {
+        pixel = b + along * reach(joined.y) + side;
+        clip = end;
+    }
+    let ndc = (pixel / lines.viewport - 0.5) * vec2<f32>(2.0, -2.0);
+    out.position = vec4<f32>(ndc * clip.w, clip.z, clip.w);
+
+    out.start = a;
+    out.end = b;
+    out.prev = a;
+    out.next = b;
+    if (joined.x) {
+        out.prev = to_pixels(prev);
+    }
+    if (joined.y) {
+        out.next = to_pixels(next);
+    }
+    out.joined = select(vec2<u32>(0u), vec2<u32>(1u), joined);
+    return out;
+}
+
+// Signed distance from `p` to the line around the end at `joint`, negative inside. `inward` points from the joint
+// into this segment and `body` is the distance to its two long edges. `other` is the far end of the neighbouring
+// segment when `joined`, whose side of the corner is left to it. Pixels right on the bisector go to the segment that
+// starts at the joint, `at_start`.
+fn end_distance(p: vec2<f32>, joint: vec2<f32>, inward: vec2<f32>, other: vec2<f32>, joined: bool, at_start: bool, body: f32) -> f32 {
+    let half = half_width();
+    let along = dot(p - joint, inward);
+    let rounded = length(p - joint) - half;
+
+    if (!joined) {
+        if (lines.cap_style == CAP_SQUARE) {
+            return max(body, -along - half);
+        }
+        if (lines.cap_style == CAP_ROUND) {
+            return select(body, rounded, along < 0.0);
+        }
+        return max(body, -along);
+    }
+
+    let arriving = direction(other, joint);
+    var tangent = arriving + inward;
+    if (dot(tangent, tangent) < 1e-6) {
+        // The line folds straight back on itself.
+        tangent = inward;
+    }
+    let side = dot(p - joint, tangent);
+    if (side < 0.0 || (side == 0.0 && !at_start)) {
+        return OUTSIDE;
+    }
+
+    if (lines.join_style == JOIN_ROUND) {
+        return select(body, rounded, along < 0.0);
+    }
+
+    // The miter is 1 / cosine half widths long.
+    let cosine = dot(normalize(tangent), inward);
+    if (lines.join_style == JOIN_MITER && cosine * lines.miter_limit >= 1.0) {
+        return body;
+    }
+
+    // Bevel, also what a miter longer than the limit falls back to: cut off straight between the outer corners.
+    let outward = arriving - inward;
+    if (dot(outward, outward) < 1e-6) {
+        return body;
+    }
+    return max(body, dot(p - joint, normalize(outward)) - half * cosine);
+}
+
+@fragment
+fn fs_main(in: VOutput) -> @location(0) vec4<f32> {
+    let p = in.position.xy;
+    let along = direction(in.start, in.end);
+    let across = vec2<f32>(-along.y, along.x);
+    let body = abs(dot(p - in.start, across)) - half_width();
+
+    let distance = max(
+        end_distance(p, in.start, along, in.prev, in.joined.x != 0u, true, body),
+        end_distance(p, in.end, -along, in.next, in.joined.y != 0u, false, body),
+    );
+    let coverage = clamp(0.5 - distance / FEATHER, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    return vec4<f32>(lines.color.rgb, lines.color.a * coverage * min(lines.width, 1.0));
+}
@@ -0,0 +1,371 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::shader::{validate_wgsl, ShaderError};
+
+/// Shared snippets under `wgpu_common/shaders`, available to `#include` from every exercise.
+const SNIPPETS: &[(&str, &str)] = &[
+    ("camera.wgsl", include_str!("../shaders/camera.wgsl")),
+    ("instance.wgsl", include_str!("../shaders/instance.wgsl")),
+    ("wireframe.wgsl", include_str!("../shaders/wireframe.wgsl")),
+];
+
+/// A small C-style preprocessor for WGSL, so snippets such as the camera uniform don't have to be copy-pasted into
+/// every shader.
+///
+/// - `#include "camera.wgsl"` pastes in another file. Every file is included at most once, so including the same
+///   snippet twice (or two snippets including each other) is harmless.
+/// - `#define NAME` sets a flag and `#define NAME value` also replaces `NAME` with `value` in the lines after it.
+///   `#undef NAME` removes it again.
+/// - `#ifdef NAME` / `#ifndef NAME` / `#else` / `#endif` keep or drop lines, and can be nested.
+///
+/// Directives have to be on their own line. Defines can also be set from Rust, e.g. to build one shader with and
+/// without a feature:
+///
+/// ```text
+/// let preprocessor = Preprocessor::default().define("INSTANCED", "");
+/// let shader = Shader::preprocessed(SHADER_PATH, include_str!("shader.wgsl"), preprocessor, target.hot_reload)?;
+/// ```
+///
+/// [`Preprocessor::default`] knows the shared snippets in `wgpu_common/shaders` (`camera.wgsl`, `instance.wgsl`,
+/// `wireframe.wgsl`). [`process`](Self::process) resolves includes from the embedded snippets only, so it works without
+/// the sources around; [`process_file`](Self::process_file) looks next to the including file and in the include
+/// directories first, so edits to a snippet are picked up by hot reload.
+#[derive(Clone, Debug)]
+pub struct Preprocessor {
+    defines: BTreeMap<String, String>,
+    embedded: BTreeMap<String, Snippet>,
+    include_dirs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug)]
+struct Snippet {
+    path: PathBuf,
+    source: Cow<'static, str>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        let shared = Path::new(env!("CARGO_MANIFEST_DIR")).join("shaders");
+        let mut preprocessor = Self::empty().include_dir(&shared);
+
+        for (name, source) in SNIPPETS {
+            preprocessor.embedded.insert(
+                name.to_string(),
+                Snippet {
+                    path: shared.join(name),
+                    source: Cow::Borrowed(source),
+                },
+            );
+        }
+        preprocessor
+    }
+}
+
+impl Preprocessor {
+    /// A preprocessor without the shared snippets or include directories.
+    pub fn empty() -> Self {
+        Self {
+            defines: BTreeMap::new(),
+            embedded: BTreeMap::new(),
+            include_dirs: Vec::new(),
+        }
+    }
+
+    /// Defines `name` as if the shader started with `#define name value`. Use an empty value for a flag.
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    /// Makes `source` available to `#include "name"`.
+    pub fn embed(mut self, name: impl Into<String>, source: impl Into<Cow<'static, str>>) -> Self {
+        let name = name.into();
+        self.embedded.insert(
+            name.clone(),
+            Snippet {
+                path: PathBuf::from(name),
+                source: source.into(),
+            },
+        );
+        self
+    }
+
+    /// Another directory [`process_file`](Self::process_file) looks for includes in.
+    pub fn include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.include_dirs.push(dir.into());
+        self
+    }
+
+    /// Preprocesses an embedded shader. `path` is only used in messages.
+    pub fn process(&self, path: impl AsRef<Path>, source: &str) -> Result<Preprocessed, ShaderError> {
+        self.run(path.as_ref(), source, false)
+    }
+
+    /// Reads the shader at `path` and preprocesses it, reading includes from disk where they can be found.
+    pub fn process_file(&self, path: impl AsRef<Path>) -> Result<Preprocessed, ShaderError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| ShaderError {
+            path: path.to_path_buf(),
+            location: None,
+            message: format!("unable to read the shader: {}", e),
+            report: String::new(),
+        })?;
+        self.run(path, &source, true)
+    }
+
+    fn run(&self, path: &Path, source: &str, from_disk: bool) -> Result<Preprocessed, ShaderError> {
+        let mut run = Run {
+            preprocessor: self,
+            from_disk,
+            defines: self.defines.clone(),
+            included: HashSet::new(),
+            output: Preprocessed {
+                source: String::new(),
+                lines: Vec::new(),
+                files: Vec::new(),
+            },
+        };
+        run.included.insert(path.to_path_buf());
+        run.file(path, source)?;
+        Ok(run.output)
+    }
+
+    /// Where `#include "name"` from `from` comes from, and its source.
+    fn resolve(&self, name: &str, from: &Path, from_disk: bool) -> Option<(PathBuf, Cow<'static, str>)> {
+        if from_disk {
+            let dirs = from.parent().into_iter().chain(self.include_dirs.iter().map(PathBuf::as_path));
+            for dir in dirs {
+                let candidate = dir.join(name);
+                if let Ok(source) = std::fs::read_to_string(&candidate) {
+                    return Some((candidate, Cow::Owned(source)));
+                }
+            }
+        }
+
+        self.embedded
+            .get(name)
+            .map(|snippet| (snippet.path.clone(), snippet.source.clone()))
+    }
+}
+
+/// WGSL with the directives resolved, and for every line of it the file and line it came from.
+#[derive(Clone, Debug)]
+pub struct Preprocessed {
+    pub source: String,
+    /// Index into `files` and the 1-based line in that file, for every line of `source`.
+    lines: Vec<(usize, u32)>,
+    files: Vec<PathBuf>,
+}
+
+impl Preprocessed {
+    /// The shader and every file it included, in the order they were first read.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The file and 1-based line that line `line` (1-based) of the output came from.
+    pub fn origin(&self, line: u32) -> Option<(&Path, u32)> {
+        let &(file, line) = self.lines.get(line.checked_sub(1)? as usize)?;
+        Some((&self.files[file], line))
+    }
+
+    /// Parses and validates the output with naga. Errors point at the file and line the offending code came from.
+    pub fn validate(&self) -> Result<naga::Module, ShaderError> {
+        let root = self.files.first().cloned().unwrap_or_default();
+        validate_wgsl(&root, &self.source).map_err(|error| self.map_error(error))
+    }
+
+    fn map_error(&self, mut error: ShaderError) -> ShaderError {
+        let location = match error.location.as_mut() {
+            Some(location) => location,
+            None => return error,
+        };
+        let (file, line) = match self.origin(location.line_number) {
+            Some((file, line)) => (file.to_path_buf(), line),
+            None => return error,
+        };
+
+        // naga's report quotes the preprocessed source with its line numbers, which would be confusing. Quote the
+        // line where it came from instead. Columns still refer to the line after defines were replaced.
+        let text = self.source.lines().nth(location.line_number as usize - 1).unwrap_or_default();
+        error.report = format!(
+            "error: {}\n  --> {}:{}:{}\n   |\n{:>4} | {}\n   |",
+            error.message,
+            file.display(),
+            line,
+            location.line_position,
+            line,
+            text
+        );
+        location.line_number = line;
+        error.path = file;
+        error
+    }
+}
+
+struct Run<'a> {
+    preprocessor: &'a Preprocessor,
+    from_disk: bool,
+    defines: BTreeMap<String, String>,
+    included: HashSet<PathBuf>,
+    output: Preprocessed,
+}
+
+/// One `#ifdef`/`#ifndef` being processed.
+struct Condition {
+    /// Where the `#ifdef` is, for the error when its `#endif` is missing.
+    line: u32,
+    /// Whether the enclosing lines are kept at all.
+    parent_active: bool,
+    /// Whether the branch we're in is taken.
+    taken: bool,
+    seen_else: bool,
+}
+
+impl Run<'_> {
+    fn file(&mut self, path: &Path, source: &str) -> Result<(), ShaderError> {
+        let file = self.output.files.len();
+        self.output.files.push(path.to_path_buf());
+
+        let mut conditions: Vec<Condition> = Vec::new();
+        let error = |line: u32, message: String| ShaderError {
+            path: path.to_path_buf(),
+            location: Some(naga::SourceLocation {
+                line_number: line,
+                line_position: 1,
+                offset: 0,
+                length: 0,
+            }),
+            message,
+            report: String::new(),
+        };
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index as u32 + 1;
+            let active = conditions.last().is_none_or(|condition| condition.parent_active && condition.taken);
+
+            let directive = match text.trim_start().strip_prefix('#') {
+                Some(directive) => directive.trim(),
+                None => {
+                    if active {
+                        let expanded = self.expand(text);
+                        self.output.source.push_str(&expanded);
+                        self.output.source.push('\n');
+                        self.output.lines.push((file, line));
+                    }
+                    continue;
+                },
+            };
+            let (keyword, argument) = match directive.find(char::is_whitespace) {
+                Some(split) => (&directive[..split], directive[split..].trim()),
+                None => (directive, ""),
+            };
+
+            match keyword {
+                "ifdef" | "ifndef" => {
+                    let name = identifier(argument).ok_or_else(|| error(line, format!("#{} needs a name", keyword)))?;
+                    let defined = self.defines.contains_key(name);
+                    conditions.push(Condition {
+                        line,
+                        parent_active: active,
+                        taken: defined == (keyword == "ifdef"),
+                        seen_else: false,
+                    });
+                },
+                "else" => {
+                    let condition = conditions.last_mut().ok_or_else(|| error(line, "#else without #ifdef".to_string()))?;
+                    if condition.seen_else {
+                        return Err(error(line, "second #else for the same #ifdef".to_string()));
+                    }
+                    condition.taken = !condition.taken;
+                    condition.seen_else = true;
+                },
+                "endif" => {
+                    conditions.pop().ok_or_else(|| error(line, "#endif without #ifdef".to_string()))?;
+                },
+                _ if !active => {},
+                "define" => {
+                    let (name, value) = match argument.find(char::is_whitespace) {
+                        Some(split) => (&argument[..split], argument[split..].trim()),
+                        None => (argument, ""),
+                    };
+                    let name = identifier(name).ok_or_else(|| error(line, "#define needs a name".to_string()))?;
+                    self.defines.insert(name.to_string(), self.expand(value));
+                },
+                "undef" => {
+                    let name = identifier(argument).ok_or_else(|| error(line, "#undef needs a name".to_string()))?;
+                    self.defines.remove(name);
+                },
+                "include" => {
+                    let name = argument
+                        .strip_prefix('"')
+                        .and_then(|rest| rest.strip_suffix('"'))
+                        .ok_or_else(|| error(line, format!("expected #include \"file.wgsl\", got '#include {}'", argument)))?;
+                    let (include_path, include_source) = self
+                        .preprocessor
+                        .resolve(name, path, self.from_disk)
+                        .ok_or_else(|| error(line, format!("unable to find '{}' to include", name)))?;
+
+                    if self.included.insert(include_path.clone()) {
+                        self.file(&include_path, &include_source)?;
+                    }
+                },
+                _ => return Err(error(line, format!("unknown directive '#{}'", keyword))),
+            }
+        }
+
+        match conditions.first() {
+            Some(condition) => Err(error(condition.line, "#ifdef without #endif".to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces every identifier in `text` that has a non-empty define. Comments are left alone.
+    fn expand(&self, text: &str) -> String {
+        if self.defines.values().all(String::is_empty) {
+            return text.to_string();
+        }
+
+        let (code, comment) = match text.find("//") {
+            Some(start) => text.split_at(start),
+            None => (text, ""),
+        };
+
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = code;
+        while let Some(start) = rest.find(is_identifier_start) {
+            let (before, from_start) = rest.split_at(start);
+            let end = from_start
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(from_start.len());
+            let (word, after) = from_start.split_at(end);
+            // Don't touch the tail of a number such as 1u or 2.0f.
+            let after_digit = before.ends_with(|c: char| c.is_ascii_digit() || c == '.');
+
+            expanded.push_str(before);
+            match self.defines.get(word) {
+                Some(value) if !value.is_empty() && !after_digit => expanded.push_str(value),
+                _ => expanded.push_str(word),
+            }
+            rest = after;
+        }
+        expanded.push_str(rest);
+        expanded.push_str(comment);
+        expanded
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn identifier(text: &str) -> Option<&str> {
+    let valid = text.starts_with(is_identifier_start) && text.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if valid {
+        Some(text)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,157 @@
+use std::marker::PhantomData;
+
+use wgpu::util::DeviceExt;
+
+use crate::bind_groups::BindGroupLayouts;
+use crate::pipeline_builder::{Blend, RenderPipelineBuilder};
+use crate::vertex_layout::VertexLayout;
+
+const IDENTITY: [[f32; 4]; 4] = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+/// What every screen-space quad shader's uniform has, whatever else it holds: the view-projection matrix the instances
+/// go through and the size of the target, to turn pixels into clip space.
+pub(crate) trait QuadUniform: bytemuck::Pod {
+    fn set_view_proj(&mut self, view_proj: [[f32; 4]; 4]);
+    fn set_viewport(&mut self, width: u32, height: u32);
+}
+
+/// Draws every instance of `I` as a quad that the vertex shader places in screen space, with one `U` uniform at
+/// `@group(0) @binding(0)`. [`LineRenderer`](crate::LineRenderer) and
+/// [`PointSpriteRenderer`](crate::PointSpriteRenderer) are this with their own shader, instance and uniform.
+pub(crate) struct QuadRenderer<I, U> {
+    label: &'static str,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    _types: PhantomData<(I, U)>,
+}
+
+impl<I: bytemuck::Pod + VertexLayout, U: QuadUniform> QuadRenderer<I, U> {
+    /// A pipeline for `format` color targets that blends over what is there, from the WGSL `source` of the file at
+    /// `path`. With a `depth_format` the quads are depth tested and written.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        label: &'static str,
+        path: &str,
+        source: &str,
+        format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        cache: Option<&wgpu::PipelineCache>,
+    ) -> anyhow::Result<Self> {
+        let bind_group_layout =
+            BindGroupLayouts::from_wgsl(path, source)?.create(device, 0, &format!("{} Bind Group Layout", label))?;
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(path),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        let pipeline_label = format!("{} Pipeline", label);
+        let mut builder = RenderPipelineBuilder::new(&shader)
+            .label(&pipeline_label)
+            .vertex_buffer(I::desc())
+            .bind_group_layout(&bind_group_layout)
+            .color_target(format)
+            .blend(Blend::Alpha)
+            .cache(cache);
+        if let Some(depth_format) = depth_format {
+            builder = builder.depth(depth_format);
+        }
+        let pipeline = builder.build(device)?;
+
+        Ok(Self {
+            label,
+            pipeline,
+            bind_group_layout,
+            _types: PhantomData,
+        })
+    }
+
+    /// Uploads `instances` and `uniform`, with an identity view-projection and a `viewport` of (width, height) pixels.
+    pub(crate) fn instances(&self, device: &wgpu::Device, instances: &[I], mut uniform: U, viewport: (u32, u32)) -> QuadInstances<I, U> {
+        uniform.set_view_proj(IDENTITY);
+        uniform.set_viewport(viewport.0, viewport.1);
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Uniform Buffer", self.label)),
+            contents: bytemuck::bytes_of(&uniform),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{} Bind Group", self.label)),
+            layout: &self.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        QuadInstances {
+            label: self.label,
+            instance_buffer: instance_buffer(device, self.label, instances),
+            count: instances.len() as u32,
+            uniform,
+            uniform_buffer,
+            bind_group,
+            _instances: PhantomData,
+        }
+    }
+
+    pub(crate) fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, quads: &QuadInstances<I, U>) {
+        if quads.count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &quads.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, quads.instance_buffer.slice(..));
+        // Two triangles per instance, the vertex shader works out the corners from the vertex index.
+        render_pass.draw(0..6, 0..quads.count);
+    }
+}
+
+/// Instances and their uniform on the GPU, made by [`QuadRenderer::instances`].
+pub(crate) struct QuadInstances<I, U> {
+    label: &'static str,
+    instance_buffer: wgpu::Buffer,
+    count: u32,
+    uniform: U,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    _instances: PhantomData<I>,
+}
+
+impl<I: bytemuck::Pod, U: QuadUniform> QuadInstances<I, U> {
+    pub(crate) fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Changes the uniform and uploads it.
+    pub(crate) fn update(&mut self, queue: &wgpu::Queue, change: impl FnOnce(&mut U)) {
+        change(&mut self.uniform);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&self.uniform));
+    }
+
+    pub(crate) fn set_viewport(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
+        self.update(queue, |uniform| uniform.set_viewport(width, height));
+    }
+
+    pub(crate) fn set_view_proj(&mut self, queue: &wgpu::Queue, view_proj: [[f32; 4]; 4]) {
+        self.update(queue, |uniform| uniform.set_view_proj(view_proj));
+    }
+
+    /// Replaces the instances, keeping the uniform.
+    pub(crate) fn set_instances(&mut self, device: &wgpu::Device, instances: &[I]) {
+        self.instance_buffer = instance_buffer(device, self.label, instances);
+        self.count = instances.len() as u32;
+    }
+}
+
+fn instance_buffer<I: bytemuck::Pod>(device: &wgpu::Device, label: &str, instances: &[I]) -> wgpu::Buffer {
+    // Never empty, so there is always something to bind. `draw` skips sets without instances.
+    let placeholder = [I::zeroed()];
+    let contents = if instances.is_empty() { &placeholder[..] } else { instances };
+
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(&format!("{} Instance Buffer", label)),
+        contents: bytemuck::cast_slice(contents),
+        usage: wgpu::BufferUsages::VERTEX,
+    })
+}
@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::preprocess::Preprocessor;
+
+/// Editors often write a file in several steps, so wait for the events to settle before reading it.
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// A WGSL shader that is baked into the binary, or in hot-reload mode loaded from disk and watched for changes.
+///
+/// ```text
+/// let shader = Shader::new(
+///     wgpu::include_wgsl!("shader.wgsl"),
+///     concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+///     target.hot_reload,
+/// )?;
+/// let render_pipeline = shader.build(&context.device, |module| RenderPipelineBuilder::new(module).build(&context.device))?;
+///
+/// // Every frame, e.g. in `update`:
+/// if let Some(render_pipeline) = self.shader.reload(&self.context.device, |module| ...) {
+///     self.render_pipeline = render_pipeline;
+/// }
+/// ```
+pub struct Shader {
+    embedded: wgpu::ShaderModuleDescriptor<'static>,
+    path: PathBuf,
+    preprocessor: Option<Preprocessor>,
+    watch: Option<Watch>,
+}
+
+struct Watch {
+    watcher: RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    changed_at: Option<Instant>,
+    /// The shader and everything it includes.
+    files: Vec<PathBuf>,
+    directories: HashSet<PathBuf>,
+}
+
+impl Shader {
+    /// `path` is where `embedded` was included from. It is only read when `hot_reload` is set.
+    pub fn new(embedded: wgpu::ShaderModuleDescriptor<'static>, path: impl Into<PathBuf>, hot_reload: bool) -> anyhow::Result<Self> {
+        let path = path.into();
+        let watch = if hot_reload { Some(Watch::new(&path)?) } else { None };
+
+        Ok(Self {
+            embedded,
+            path,
+            preprocessor: None,
+            watch,
+        })
+    }
+
+    /// Like [`new`](Self::new) for a shader with `#include`/`#define` directives, see [`Preprocessor`]. `source` is
+    /// the embedded copy of the file at `path`, e.g. `include_str!("shader.wgsl")`.
+    ///
+    /// In hot-reload mode the included files are watched too.
+    pub fn preprocessed(
+        path: &'static str,
+        source: &'static str,
+        preprocessor: Preprocessor,
+        hot_reload: bool,
+    ) -> anyhow::Result<Self> {
+        let embedded = wgpu::ShaderModuleDescriptor {
+            label: Path::new(path).file_name().and_then(|name| name.to_str()),
+            source: wgpu::ShaderSource::Wgsl(preprocessor.process(path, source)?.source.into()),
+        };
+
+        let mut watch = if hot_reload { Some(Watch::new(Path::new(path))?) } else { None };
+        if let (Some(watch), Ok(preprocessed)) = (watch.as_mut(), preprocessor.process_file(path)) {
+            watch.watch_files(preprocessed.files());
+        }
+
+        Ok(Self {
+            embedded,
+            path: PathBuf::from(path),
+            preprocessor: Some(preprocessor),
+            watch,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_hot_reloading(&self) -> bool {
+        self.watch.is_some()
+    }
+
+    /// Creates the shader module and hands it to `create` (which usually builds the render pipeline).
+    ///
+    /// In hot-reload mode the shader comes from disk, so a bad edit made while the app wasn't running is reported
+    /// as an error instead of a panic.
+    pub fn build<T, F>(&self, device: &wgpu::Device, create: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&wgpu::ShaderModule) -> anyhow::Result<T>,
+    {
+        if self.watch.is_none() {
+            let module = device.create_shader_module(self.embedded.clone());
+            return create(&module);
+        }
+
+        let (source, _) = self.read()?;
+        self.compile(device, source, create)
+    }
+
+    /// Rebuilds with `create` when the shader file changed on disk since the last call.
+    ///
+    /// Returns `None` when nothing changed, when hot reload is off, or when the new shader doesn't compile or `create`
+    /// fails. Errors are logged as `file:line:column: message` so the caller can keep using what it built before.
+    pub fn reload<T, F>(&mut self, device: &wgpu::Device, create: F) -> Option<T>
+    where
+        F: FnOnce(&wgpu::ShaderModule) -> anyhow::Result<T>,
+    {
+        if !self.watch.as_mut()?.changed() {
+            return None;
+        }
+
+        let built = self.read().and_then(|(source, files)| {
+            // An edit can add or remove includes.
+            if let Some(watch) = self.watch.as_mut() {
+                watch.watch_files(&files);
+            }
+            self.compile(device, source, create)
+        });
+        match built {
+            Ok(built) => {
+                log::info!("Reloaded {}", self.path.display());
+                Some(built)
+            },
+            Err(e) => {
+                log::error!("{:#}", e);
+                log::warn!("Keeping the previous pipeline until {} compiles again", self.path.display());
+                None
+            }
+        }
+    }
+
+    /// Reads and validates the shader from disk. Returns the source to compile and the files it was read from.
+    fn read(&self) -> anyhow::Result<(String, Vec<PathBuf>)> {
+        match &self.preprocessor {
+            Some(preprocessor) => {
+                let preprocessed = preprocessor.process_file(&self.path)?;
+                preprocessed.validate()?;
+                let files = preprocessed.files().to_vec();
+                Ok((preprocessed.source, files))
+            },
+            None => {
+                let source = std::fs::read_to_string(&self.path)
+                    .map_err(|e| anyhow::anyhow!("unable to read {}: {}", self.path.display(), e))?;
+                validate_wgsl(&self.path, &source)?;
+                Ok((source, vec![self.path.clone()]))
+            },
+        }
+    }
+
+    fn compile<T, F>(&self, device: &wgpu::Device, source: String, create: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&wgpu::ShaderModule) -> anyhow::Result<T>,
+    {
+        // naga catches almost everything, but the pipeline can still disagree with the shader (entry points,
+        // vertex layouts, bind groups). Catch that too instead of letting wgpu panic.
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: self.embedded.label,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let built = create(&module);
+
+        match pollster::block_on(device.pop_error_scope()) {
+            None => built,
+            Some(error) => Err(anyhow::anyhow!("{}: {}", self.path.display(), error)),
+        }
+    }
+}
+
+impl Watch {
+    fn new(path: &Path) -> anyhow::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender)?;
+
+        let mut watch = Self {
+            watcher,
+            events,
+            changed_at: None,
+            files: Vec::new(),
+            directories: HashSet::new(),
+        };
+        watch.watch_file(path)?;
+        Ok(watch)
+    }
+
+    fn watch_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let path = &canonical(path);
+        if self.files.contains(path) {
+            return Ok(());
+        }
+
+        // Watch the directory rather than the file: a lot of editors save by replacing the file, which would end a
+        // watch on the file itself.
+        let directory = path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("{} has no parent directory to watch", path.display()))?;
+        if !self.directories.contains(directory) {
+            self.watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .map_err(|e| anyhow::anyhow!("unable to watch {}: {}", directory.display(), e))?;
+            self.directories.insert(directory.to_path_buf());
+        }
+
+        log::info!("Watching {} for changes", path.display());
+        self.files.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Also watches the files a shader includes. Embedded snippets that aren't on disk are skipped.
+    fn watch_files(&mut self, files: &[PathBuf]) {
+        for file in files.iter().filter(|file| file.is_file()) {
+            if let Err(e) = self.watch_file(file) {
+                log::warn!("{:#}", e);
+            }
+        }
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        self.files.contains(&canonical(path))
+    }
+
+    /// Whether one of the watched files changed and has been left alone for [`SETTLE_TIME`] since.
+    fn changed(&mut self) -> bool {
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) if is_write(&event.kind) && event.paths.iter().any(|path| self.is_watched(path)) => {
+                    self.changed_at = Some(Instant::now());
+                },
+                Ok(_) => {},
+                Err(e) => log::warn!("Error while watching {}: {}", self.files[0].display(), e),
+            }
+        }
+
+        match self.changed_at {
+            Some(changed_at) if changed_at.elapsed() >= SETTLE_TIME => {
+                self.changed_at = None;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// `path` with symlinks and `..` resolved, so the same file always compares equal. A file that is gone (e.g. an editor's
+/// temporary file) is resolved through its directory instead, and left as it is when that is gone too.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .or_else(|_| match (path.parent(), path.file_name()) {
+            (Some(directory), Some(name)) => directory.canonicalize().map(|directory| directory.join(name)),
+            _ => Err(std::io::ErrorKind::NotFound.into()),
+        })
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn is_write(kind: &notify::EventKind) -> bool {
+    matches!(kind, notify::EventKind::Create(_) | notify::EventKind::Modify(_))
+}
+
+/// A WGSL parse or validation error, pointing at where in the file it happened.
+#[derive(Debug)]
+pub struct ShaderError {
+    pub path: PathBuf,
+    pub location: Option<naga::SourceLocation>,
+    pub message: String,
+    /// naga's full report, with the offending source lines underlined.
+    pub report: String,
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some(location) => write!(
+                f,
+                "{}:{}:{}: {}",
+                self.path.display(),
+                location.line_number,
+                location.line_position,
+                self.message
+            )?,
+            None => write!(f, "{}: {}", self.path.display(), self.message)?,
+        }
+
+        if f.alternate() && !self.report.is_empty() {
+            write!(f, "\n{}", self.report.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// Parses and validates WGSL with naga, without needing a device.
+pub fn validate_wgsl(path: &Path, source: &str) -> Result<naga::Module, ShaderError> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|e| ShaderError {
+        path: path.to_path_buf(),
+        location: e.location(source),
+        message: e.message().to_string(),
+        report: e.emit_to_string_with_path(source, path),
+    })?;
+
+    // Capabilities are checked by wgpu against the real device, here we only want to catch mistakes in the shader.
+    let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all());
+    validator.validate(&module).map_err(|e| ShaderError {
+        path: path.to_path_buf(),
+        location: e.location(source),
+        message: error_chain(e.as_inner()),
+        report: e.emit_to_string_with_path(source, path.display().to_string()),
+    })?;
+
+    Ok(module)
+}
+
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+
+    message
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+/// Which surface format to ask for.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum FormatPreference {
+    /// The first sRGB format the surface supports, so colors written by the shaders are gamma correct.
+    #[default] Srgb,
+    /// This exact format, falling back to [`FormatPreference::Srgb`] when the surface doesn't support it.
+    Exact(wgpu::TextureFormat),
+    /// `Rgba16Float` for HDR output when available, otherwise [`FormatPreference::Srgb`].
+    Hdr,
+}
+
+/// What an exercise would like its surface to be configured with. Anything the surface doesn't support falls back
+/// gracefully, see [`negotiate`].
+#[derive(Clone, Debug, Default)]
+pub struct SurfacePreferences {
+    pub format: FormatPreference,
+    /// `None` means Fifo (vsync), the only mode every surface has to support.
+    pub present_mode: Option<wgpu::PresentMode>,
+    /// `None` means Opaque when supported.
+    pub alpha_mode: Option<wgpu::CompositeAlphaMode>,
+}
+
+/// The configuration that came out of [`negotiate`], along with what was asked for.
+#[derive(Clone, Debug)]
+pub struct SurfaceChoice {
+    pub format: wgpu::TextureFormat,
+    pub present_mode: wgpu::PresentMode,
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    pub preferences: SurfacePreferences,
+}
+
+impl fmt::Display for SurfaceChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "format {:?} (wanted {:?}), present mode {:?} (wanted {:?}), alpha mode {:?} (wanted {:?})",
+            self.format,
+            self.preferences.format,
+            self.present_mode,
+            self.preferences.present_mode.unwrap_or(wgpu::PresentMode::Fifo),
+            self.alpha_mode,
+            self.preferences.alpha_mode.unwrap_or(wgpu::CompositeAlphaMode::Opaque),
+        )
+    }
+}
+
+/// Picks the format, present mode and alpha mode to configure a surface with, instead of blindly taking the first
+/// entry of each capability list.
+pub fn negotiate(capabilities: &wgpu::SurfaceCapabilities, preferences: &SurfacePreferences) -> anyhow::Result<SurfaceChoice> {
+    anyhow::ensure!(
+        !capabilities.formats.is_empty() && !capabilities.present_modes.is_empty(),
+        "the surface is not supported by this adapter"
+    );
+
+    let choice = SurfaceChoice {
+        format: choose_format(&capabilities.formats, preferences.format),
+        present_mode: choose_present_mode(&capabilities.present_modes, preferences.present_mode),
+        alpha_mode: choose_alpha_mode(&capabilities.alpha_modes, preferences.alpha_mode),
+        preferences: preferences.clone(),
+    };
+
+    log::info!("Surface configured with {}", choice);
+    Ok(choice)
+}
+
+fn choose_format(formats: &[wgpu::TextureFormat], preference: FormatPreference) -> wgpu::TextureFormat {
+    let requested = match preference {
+        FormatPreference::Srgb => None,
+        FormatPreference::Exact(format) => Some(format),
+        FormatPreference::Hdr => Some(wgpu::TextureFormat::Rgba16Float),
+    };
+
+    if let Some(format) = requested {
+        if formats.contains(&format) {
+            return format;
+        }
+        log::info!("Surface doesn't support {:?}, falling back to an sRGB format", format);
+    }
+
+    match formats.iter().find(|format| format.is_srgb()) {
+        Some(format) => *format,
+        None => {
+            // Shaders will have to do their own gamma correction (or render through an sRGB view format).
+            log::warn!("Surface has no sRGB format, using {:?} so colors may not be gamma correct", formats[0]);
+            formats[0]
+        }
+    }
+}
+
+fn choose_present_mode(present_modes: &[wgpu::PresentMode], preference: Option<wgpu::PresentMode>) -> wgpu::PresentMode {
+    use wgpu::PresentMode::*;
+
+    // Each mode falls back to the closest behavior: low latency modes try each other before settling on vsync.
+    let candidates: &[wgpu::PresentMode] = match preference.unwrap_or(Fifo) {
+        Fifo => &[Fifo],
+        FifoRelaxed | AutoVsync => &[FifoRelaxed, Fifo],
+        Mailbox => &[Mailbox, Immediate, Fifo],
+        Immediate | AutoNoVsync => &[Immediate, Mailbox, Fifo],
+    };
+
+    let chosen = candidates
+        .iter()
+        .copied()
+        .find(|mode| present_modes.contains(mode))
+        .unwrap_or(present_modes[0]);
+
+    if let Some(requested) = preference {
+        if chosen != requested && !matches!(requested, AutoVsync | AutoNoVsync) {
+            log::info!("Surface doesn't support present mode {:?}, falling back to {:?}", requested, chosen);
+        }
+    }
+
+    chosen
+}
+
+fn choose_alpha_mode(alpha_modes: &[wgpu::CompositeAlphaMode], preference: Option<wgpu::CompositeAlphaMode>) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode::*;
+
+    match preference {
+        // wgpu resolves Auto to Opaque or Inherit itself, so it is always accepted.
+        Some(Auto) => return Auto,
+        Some(mode) if alpha_modes.contains(&mode) => return mode,
+        Some(mode) => log::info!("Surface doesn't support alpha mode {:?}, falling back", mode),
+        None => {}
+    }
+
+    if alpha_modes.contains(&Opaque) {
+        Opaque
+    } else {
+        alpha_modes.first().copied().unwrap_or(Auto)
+    }
+}
+
+/// The capabilities of the offscreen texture headless targets render into, so they go through the same negotiation.
+pub(crate) fn offscreen_capabilities() -> wgpu::SurfaceCapabilities {
+    wgpu::SurfaceCapabilities {
+        // RGBA so captures can be saved without any swizzling.
+        formats: vec![wgpu::TextureFormat::Rgba8UnormSrgb, wgpu::TextureFormat::Rgba8Unorm],
+        present_modes: vec![wgpu::PresentMode::Fifo],
+        alpha_modes: vec![wgpu::CompositeAlphaMode::Opaque],
+        usages: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use winit::window::Window;
+
+use crate::adapter::AdapterSelection;
+
+/// Where an exercise renders its frames, and on which adapter.
+#[derive(Clone, Debug)]
+pub struct Target {
+    kind: TargetKind,
+    pub adapter: AdapterSelection,
+    /// Load shaders from disk and rebuild pipelines when they change, see [`Shader`](crate::Shader).
+    pub hot_reload: bool,
+    /// Directory to keep a driver pipeline cache in, see [`DiskPipelineCache`](crate::DiskPipelineCache). `None`
+    /// compiles every pipeline from scratch.
+    pub pipeline_cache: Option<PathBuf>,
+    /// Build every render pipeline again each frame instead of reusing it, see
+    /// [`RenderPipelineCache::rebuild_always`](crate::RenderPipelineCache::rebuild_always). Only for measuring what
+    /// the cache saves.
+    pub rebuild_pipelines: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum TargetKind {
+    /// Present to the swapchain of an on-screen window.
+    Window(Arc<Window>),
+    /// Render into an offscreen texture of the given size that can be read back with
+    /// [`GpuContext::capture`](crate::GpuContext::capture).
+    Headless { width: u32, height: u32 },
+}
+
+impl Target {
+    pub fn for_window(window: Arc<Window>) -> Self {
+        Self {
+            kind: TargetKind::Window(window),
+            adapter: AdapterSelection::default(),
+            hot_reload: false,
+            pipeline_cache: None,
+            rebuild_pipelines: false,
+        }
+    }
+
+    pub fn headless(width: u32, height: u32) -> Self {
+        Self {
+            kind: TargetKind::Headless { width, height },
+            adapter: AdapterSelection::default(),
+            hot_reload: false,
+            pipeline_cache: None,
+            rebuild_pipelines: false,
+        }
+    }
+
+    pub fn with_adapter(mut self, adapter: AdapterSelection) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    pub fn with_hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+
+    pub fn with_pipeline_cache(mut self, directory: Option<PathBuf>) -> Self {
+        self.pipeline_cache = directory;
+        self
+    }
+
+    pub fn with_rebuild_pipelines(mut self, rebuild_pipelines: bool) -> Self {
+        self.rebuild_pipelines = rebuild_pipelines;
+        self
+    }
+
+    pub fn kind(&self) -> &TargetKind {
+        &self.kind
+    }
+
+    pub fn window(&self) -> Option<&Arc<Window>> {
+        match &self.kind {
+            TargetKind::Window(window) => Some(window),
+            TargetKind::Headless { .. } => None,
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self.kind, TargetKind::Headless { .. })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        match &self.kind {
+            TargetKind::Window(window) => {
+                let size = window.inner_size();
+                (size.width, size.height)
+            },
+            TargetKind::Headless { width, height } => (*width, *height),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use image::GenericImageView;
+
+/// A 2D RGBA texture with its view and sampler, what a `texture_2d<f32>` and `sampler` binding pair needs. This is
+/// the `Texture` from the learn_wgpu texture exercises, shared so the model loaders can produce it too.
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl Texture {
+    /// Decodes a PNG or JPEG image.
+    pub fn from_bytes(device: &wgpu::Device, queue: &wgpu::Queue, bytes: &[u8], label: &str) -> anyhow::Result<Self> {
+        let texture_image = image::load_from_memory(bytes)?;
+        Self::from_image(device, queue, &texture_image, Some(label))
+    }
+
+    /// Clamps to the edges, like the exercises always have.
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture_image: &image::DynamicImage,
+        label: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let (width, height) = texture_image.dimensions();
+        anyhow::ensure!(width > 0 && height > 0, "the image is empty");
+
+        let sampler = wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        };
+        Ok(Self::from_rgba(device, queue, &texture_image.to_rgba8(), label, &sampler))
+    }
+
+    /// Uploads `rgba` as an sRGB texture and creates a sampler from `sampler`, e.g. one that repeats for models.
+    pub fn from_rgba(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        rgba: &image::RgbaImage,
+        label: Option<&str>,
+        sampler: &wgpu::SamplerDescriptor<'_>,
+    ) -> Self {
+        let size = wgpu::Extent3d { width: rgba.width(), height: rgba.height(), depth_or_array_layers: 1 };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size.width),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(sampler);
+
+        Self { texture, view, sampler }
+    }
+
+    /// A 1x1 texture of one color, for materials without an image.
+    pub fn from_color(device: &wgpu::Device, queue: &wgpu::Queue, color: [f32; 4], label: Option<&str>) -> Self {
+        let [r, g, b, a] = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+        let rgba = image::RgbaImage::from_pixel(1, 1, image::Rgba([r, g, b, a]));
+        Self::from_rgba(device, queue, &rgba, label, &wgpu::SamplerDescriptor::default())
+    }
+
+    /// `@binding(0)` the view and `@binding(1)` the sampler.
+    pub fn bind_group(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        label: Option<&str>,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label,
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&self.view) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+            ],
+        })
+    }
+}
@@ -0,0 +1,270 @@
+use std::env;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::Window;
+
+/// The five primitive topologies wgpu supports, with the names the exercises use for them on the command line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Topology {
+    PointList,
+    LineList,
+    LineStrip,
+    TriangleList,
+    TriangleStrip,
+}
+
+impl Topology {
+    /// Every topology, in the order the exercises cycle through them.
+    pub const ALL: [Topology; 5] = [
+        Topology::PointList,
+        Topology::LineList,
+        Topology::LineStrip,
+        Topology::TriangleList,
+        Topology::TriangleStrip,
+    ];
+
+    /// The command line name, e.g. `line-strip`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Topology::PointList => "point-list",
+            Topology::LineList => "line-list",
+            Topology::LineStrip => "line-strip",
+            Topology::TriangleList => "triangle-list",
+            Topology::TriangleStrip => "triangle-strip",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|topology| topology.name() == name)
+    }
+
+    pub fn primitive_topology(self) -> wgpu::PrimitiveTopology {
+        match self {
+            Topology::PointList => wgpu::PrimitiveTopology::PointList,
+            Topology::LineList => wgpu::PrimitiveTopology::LineList,
+            Topology::LineStrip => wgpu::PrimitiveTopology::LineStrip,
+            Topology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+            Topology::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
+        }
+    }
+
+    pub fn is_strip(self) -> bool {
+        self.primitive_topology().is_strip()
+    }
+
+    /// The `strip_index_format` a pipeline drawing this topology with `index_format` indices needs.
+    ///
+    /// Strips have to know the index format up front so the GPU can recognise the primitive restart value
+    /// (`0xFFFF` or `0xFFFFFFFF`) that ends one strip and starts the next. wgpu rejects a strip index format on list
+    /// topologies, so those always get `None`.
+    pub fn strip_index_format(self, index_format: wgpu::IndexFormat) -> Option<wgpu::IndexFormat> {
+        if self.is_strip() {
+            Some(index_format)
+        } else {
+            None
+        }
+    }
+
+    /// A `PrimitiveState` for this topology, ready for indexed draws with `index_format` indices. The strip index
+    /// format is ignored by non-indexed draws, so the same pipeline works for both.
+    pub fn primitive_state(self, index_format: wgpu::IndexFormat) -> wgpu::PrimitiveState {
+        wgpu::PrimitiveState {
+            topology: self.primitive_topology(),
+            strip_index_format: self.strip_index_format(index_format),
+            ..Default::default()
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = self.index();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        let index = self.index();
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&topology| topology == self).unwrap()
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.primitive_topology())
+    }
+}
+
+impl FromStr for Topology {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> anyhow::Result<Self> {
+        Self::from_name(name).ok_or_else(|| {
+            let names: Vec<_> = Self::ALL.iter().map(|topology| topology.name()).collect();
+            anyhow::anyhow!("unknown topology '{}', expected one of {}", name, names.join(", "))
+        })
+    }
+}
+
+/// The index value that ends the current strip when drawing with `format` indices.
+pub fn primitive_restart_index(format: wgpu::IndexFormat) -> u32 {
+    match format {
+        wgpu::IndexFormat::Uint16 => u16::MAX as u32,
+        wgpu::IndexFormat::Uint32 => u32::MAX,
+    }
+}
+
+/// Whether strips drawn on `backend` are split at the primitive restart value.
+///
+/// wgpu's GL backend never enables `GL_PRIMITIVE_RESTART_FIXED_INDEX`, so there the restart value is drawn like any
+/// other index. Use [`strip_ranges`] to draw each strip on its own instead.
+pub fn supports_primitive_restart(backend: wgpu::Backend) -> bool {
+    backend != wgpu::Backend::Gl
+}
+
+/// The index ranges to draw `indices` with on `backend`: the whole buffer when it supports primitive restart,
+/// otherwise one range per strip with the restart values left out.
+pub fn strip_ranges<I: Copy + Into<u32>>(indices: &[I], format: wgpu::IndexFormat, backend: wgpu::Backend) -> Vec<Range<u32>> {
+    if supports_primitive_restart(backend) {
+        return std::iter::once(0..indices.len() as u32).collect();
+    }
+
+    let restart = primitive_restart_index(format);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    for (i, &index) in indices.iter().enumerate() {
+        if index.into() == restart {
+            if start < i {
+                ranges.push(start as u32..i as u32);
+            }
+            start = i + 1;
+        }
+    }
+    if start < indices.len() {
+        ranges.push(start as u32..indices.len() as u32);
+    }
+
+    ranges
+}
+
+impl From<wgpu::PrimitiveTopology> for Topology {
+    fn from(topology: wgpu::PrimitiveTopology) -> Self {
+        match topology {
+            wgpu::PrimitiveTopology::PointList => Topology::PointList,
+            wgpu::PrimitiveTopology::LineList => Topology::LineList,
+            wgpu::PrimitiveTopology::LineStrip => Topology::LineStrip,
+            wgpu::PrimitiveTopology::TriangleList => Topology::TriangleList,
+            wgpu::PrimitiveTopology::TriangleStrip => Topology::TriangleStrip,
+        }
+    }
+}
+
+/// The topology an exercise draws with, switchable from the keyboard while it runs.
+///
+/// - RIGHT / LEFT cycle through [`Topology::ALL`], `1`-`5` jump straight to one.
+/// - `I` toggles between drawing the vertices directly and through an index buffer with primitive restarts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TopologyControls {
+    pub topology: Topology,
+    pub indexed: bool,
+}
+
+impl TopologyControls {
+    pub fn new(topology: Topology) -> Self {
+        Self {
+            topology,
+            indexed: false,
+        }
+    }
+
+    /// Applies a key press. Returns true when the event changed the topology or the indexed mode.
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        let code = match event {
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    physical_key: PhysicalKey::Code(code),
+                    state: ElementState::Pressed,
+                    repeat: false,
+                    ..
+                },
+                ..
+            } => *code,
+            _ => return false,
+        };
+
+        match code {
+            KeyCode::ArrowRight => self.topology = self.topology.next(),
+            KeyCode::ArrowLeft => self.topology = self.topology.previous(),
+            KeyCode::Digit1 => self.topology = Topology::PointList,
+            KeyCode::Digit2 => self.topology = Topology::LineList,
+            KeyCode::Digit3 => self.topology = Topology::LineStrip,
+            KeyCode::Digit4 => self.topology = Topology::TriangleList,
+            KeyCode::Digit5 => self.topology = Topology::TriangleStrip,
+            KeyCode::KeyI => self.indexed = !self.indexed,
+            _ => return false,
+        }
+
+        log::info!("Switched to {}", self.title());
+        true
+    }
+
+    /// What the window title shows, e.g. `LineStrip (indexed with primitive restart)`.
+    pub fn title(&self) -> String {
+        if self.indexed && self.topology.is_strip() {
+            format!("{} (indexed with primitive restart)", self.topology)
+        } else if self.indexed {
+            format!("{} (indexed)", self.topology)
+        } else {
+            self.topology.to_string()
+        }
+    }
+
+    /// Shows the current mode in the title of `window`, after the exercise name.
+    pub fn show_in(&self, window: Option<&Arc<Window>>, exercise: &str) {
+        if let Some(window) = window {
+            window.set_title(&format!("{} - {} - LEFT/RIGHT: topology, I: indexed", exercise, self.title()));
+        }
+    }
+}
+
+/// Parses the topology the primitive exercises start with from the first command line argument. Prints the help and
+/// returns `None` for `help`, a missing argument or a mode that isn't one of `modes`.
+pub fn parse_user_input(modes: &[Topology]) -> Option<Topology> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 || args[1] == "help" {
+        print_help(modes);
+        return None;
+    }
+
+    match Topology::from_name(&args[1]).filter(|topology| modes.contains(topology)) {
+        Some(topology) => Some(topology),
+        None => {
+            eprintln!("Unknown mode: '{}'", args[1]);
+            print_help(modes);
+            None
+        }
+    }
+}
+
+fn print_help(modes: &[Topology]) {
+    println!("Usage: cargo run -- <mode>");
+    println!("Supported modes:");
+    for mode in modes {
+        println!("  {:<15} - Start with {}", mode.name(), mode);
+    }
+    println!("  {:<15} - Show this help message", "help");
+    println!();
+    println!("While running, LEFT/RIGHT (or 1-5) switch between all five topologies and I toggles indexed drawing,");
+    println!("which splits the strips with a primitive restart index.");
+    println!();
+    println!("Add --headless [--frames N] [--out frame.png] [--size WxH] after the mode to render offscreen to a PNG.");
+    println!("Add --backend <list>, --power <pref>, --adapter <name or index> or --fallback-adapter to pick the adapter.");
+}
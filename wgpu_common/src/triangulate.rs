@@ -0,0 +1,381 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{compute_tangents, MeshData, ModelVertex};
+
+/// A simple 2D polygon with optional holes, to be cut into triangles for an index buffer.
+///
+/// Points are numbered in the order they were given: the outline first, then each hole. Either winding is accepted
+/// for both, and repeated or collinear points are fine. The triangles always come out counter-clockwise with +y up,
+/// which is what `FrontFace::Ccw` keeps when the polygon faces the camera.
+///
+/// ```text
+/// let pentagon = Polygon::new(VERTICES.iter().map(|vertex| [vertex.position[0], vertex.position[1]]));
+/// let index_buffer = IndexBuffer::new(&device, "Index Buffer", &pentagon.triangulate());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon {
+    pub outline: Vec<[f32; 2]>,
+    pub holes: Vec<Vec<[f32; 2]>>,
+}
+
+impl Polygon {
+    pub fn new(outline: impl IntoIterator<Item = [f32; 2]>) -> Self {
+        Self {
+            outline: outline.into_iter().collect(),
+            holes: Vec::new(),
+        }
+    }
+
+    /// Cuts a hole out of the polygon. Holes have to be inside the outline and must not touch each other.
+    pub fn with_hole(mut self, hole: impl IntoIterator<Item = [f32; 2]>) -> Self {
+        self.holes.push(hole.into_iter().collect());
+        self
+    }
+
+    /// The outline followed by every hole, which is what the indices refer to.
+    pub fn points(&self) -> Vec<[f32; 2]> {
+        self.outline.iter().chain(self.holes.iter().flatten()).copied().collect()
+    }
+
+    /// Ear clipping: three indices per triangle into [`points`](Polygon::points). Holes are joined to the outline by a
+    /// pair of edges first, so the result is one ring that is clipped like any other polygon. Triangles without area
+    /// are left out, so a polygon with fewer than three distinct points or no area gets none.
+    pub fn triangulate(&self) -> Vec<u32> {
+        let points = self.points_f64();
+        let ring = match self.ring(&points) {
+            Some(ring) => ring,
+            None => return Vec::new(),
+        };
+        ear_clip(&points, ring)
+    }
+
+    /// Like [`triangulate`](Polygon::triangulate), then flips the diagonals until every triangle's circumcircle is
+    /// free of the points its neighbours across a diagonal add. The outline and holes are never flipped, so this is
+    /// the constrained Delaunay triangulation: the same area covered with fewer slivers.
+    pub fn triangulate_delaunay(&self) -> Vec<u32> {
+        let points = self.points_f64();
+        let mut indices = self.triangulate();
+
+        let mut constraints = HashSet::new();
+        for (start, len) in self.rings() {
+            for i in 0..len {
+                constraints.insert(edge(start + i, start + (i + 1) % len));
+            }
+        }
+        flip_to_delaunay(&points, &mut indices, &constraints);
+        indices
+    }
+
+    /// Texture coordinates for every point, stretching the texture over the outline's bounding box with its top left
+    /// corner at the top left of the box, the way exercise 5 maps the happy tree onto the pentagon.
+    pub fn texture_coords(&self) -> Vec<[f32; 2]> {
+        let (min, max) = self.outline.iter().fold(([f32::MAX; 2], [f32::MIN; 2]), |(min, max), point| {
+            ([min[0].min(point[0]), min[1].min(point[1])], [max[0].max(point[0]), max[1].max(point[1])])
+        });
+        let scale = |value: f32, from: f32, size: f32| if size > 0.0 { (value - from) / size } else { 0.0 };
+        self.points()
+            .iter()
+            .map(|&[x, y]| [scale(x, min[0], max[0] - min[0]), scale(max[1] - y, 0.0, max[1] - min[1])])
+            .collect()
+    }
+
+    /// The polygon as a flat mesh at z = 0 facing +z, with [`texture_coords`](Polygon::texture_coords) and the
+    /// [`triangulate_delaunay`](Polygon::triangulate_delaunay) triangles.
+    pub fn mesh(&self, name: &str) -> MeshData {
+        let mut vertices: Vec<ModelVertex> = self
+            .points()
+            .iter()
+            .zip(self.texture_coords())
+            .map(|(&[x, y], texture_coords)| ModelVertex {
+                position: [x, y, 0.0],
+                texture_coords,
+                normal: [0.0, 0.0, 1.0],
+                ..Default::default()
+            })
+            .collect();
+        let indices = self.triangulate_delaunay();
+        compute_tangents(&mut vertices, &indices);
+
+        MeshData {
+            name: name.to_string(),
+            vertices,
+            indices,
+            material: None,
+        }
+    }
+
+    fn points_f64(&self) -> Vec<[f64; 2]> {
+        self.points().iter().map(|&[x, y]| [x as f64, y as f64]).collect()
+    }
+
+    /// Where each ring starts in `points()` and how many points it has, the outline first.
+    fn rings(&self) -> Vec<(usize, usize)> {
+        let mut start = 0;
+        std::iter::once(self.outline.len())
+            .chain(self.holes.iter().map(Vec::len))
+            .map(|len| {
+                start += len;
+                (start - len, len)
+            })
+            .collect()
+    }
+
+    /// The outline counter-clockwise, with every hole spliced in clockwise. `None` if the outline has no area.
+    fn ring(&self, points: &[[f64; 2]]) -> Option<Vec<usize>> {
+        let rings = self.rings();
+        let mut outline = clean(points, rings[0].0..rings[0].0 + rings[0].1);
+        let area = signed_area(points, &outline);
+        if area.abs() <= EPSILON * scale(points) {
+            return None;
+        }
+        if area < 0.0 {
+            outline.reverse();
+        }
+
+        let mut holes: Vec<Vec<usize>> = rings[1..]
+            .iter()
+            .filter_map(|&(start, len)| {
+                let mut hole = clean(points, start..start + len);
+                let area = signed_area(points, &hole);
+                if area.abs() <= EPSILON * scale(points) {
+                    return None;
+                }
+                if area > 0.0 {
+                    hole.reverse();
+                }
+                Some(hole)
+            })
+            .collect();
+
+        // Rightmost holes first, so every bridge can go right without crossing a hole that isn't joined yet.
+        let rightmost = |hole: &Vec<usize>| hole.iter().map(|&i| points[i][0]).fold(f64::MIN, f64::max);
+        holes.sort_by(|a, b| rightmost(b).total_cmp(&rightmost(a)));
+        for hole in holes {
+            if !bridge(points, &mut outline, &hole) {
+                log::warn!("Ignoring a hole that isn't inside the polygon's outline");
+            }
+        }
+        Some(outline)
+    }
+}
+
+const EPSILON: f64 = 1e-10;
+
+/// How big the polygon is, so the tolerances work the same for any size.
+fn scale(points: &[[f64; 2]]) -> f64 {
+    points.iter().flatten().fold(0.0f64, |largest, value| largest.max(value.abs())).max(1.0).powi(2)
+}
+
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// The indices in `range`, without points that repeat the one before them (including the last repeating the first).
+fn clean(points: &[[f64; 2]], range: std::ops::Range<usize>) -> Vec<usize> {
+    let mut ring: Vec<usize> = Vec::new();
+    for i in range {
+        if ring.last().is_none_or(|&last| points[last] != points[i]) {
+            ring.push(i);
+        }
+    }
+    while ring.len() > 1 && points[ring[0]] == points[ring[ring.len() - 1]] {
+        ring.pop();
+    }
+    ring
+}
+
+/// Positive when the ring is counter-clockwise.
+fn signed_area(points: &[[f64; 2]], ring: &[usize]) -> f64 {
+    (0..ring.len())
+        .map(|i| {
+            let [a, b] = [points[ring[i]], points[ring[(i + 1) % ring.len()]]];
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+        * 0.5
+}
+
+/// Twice the signed area of the triangle, positive when it is counter-clockwise.
+fn orient(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+/// Inside or on the edge of the counter-clockwise triangle `a`, `b`, `c`.
+fn in_triangle(p: [f64; 2], a: [f64; 2], b: [f64; 2], c: [f64; 2], tolerance: f64) -> bool {
+    orient(a, b, p) >= -tolerance && orient(b, c, p) >= -tolerance && orient(c, a, p) >= -tolerance
+}
+
+/// Joins `hole` to `ring` with two edges between the hole's rightmost point and a point of the ring it can see,
+/// following David Eberly's "Triangulation by Ear Clipping". Returns false if nothing of the ring is to the right of
+/// the hole.
+fn bridge(points: &[[f64; 2]], ring: &mut Vec<usize>, hole: &[usize]) -> bool {
+    let start = (0..hole.len())
+        .max_by(|&a, &b| {
+            let [pa, pb] = [points[hole[a]], points[hole[b]]];
+            pa[0].total_cmp(&pb[0]).then(pb[1].total_cmp(&pa[1]))
+        })
+        .unwrap();
+    let m = points[hole[start]];
+
+    // The closest ring edge a ray from the hole's rightmost point towards +x hits.
+    let mut hit: Option<(f64, usize)> = None;
+    for i in 0..ring.len() {
+        let [a, b] = [points[ring[i]], points[ring[(i + 1) % ring.len()]]];
+        // The outline is counter-clockwise, so edges with the inside on their left cross the ray going up.
+        if a[1] > m[1] || b[1] < m[1] || a[1] == b[1] {
+            continue;
+        }
+        let x = a[0] + (m[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+        if x >= m[0] && hit.is_none_or(|(closest, _)| x < closest) {
+            hit = Some((x, i));
+        }
+    }
+    let (x, edge) = match hit {
+        Some(hit) => hit,
+        None => return false,
+    };
+
+    // The edge's end furthest right is a candidate, but a reflex point inside the triangle between the hole, the hit
+    // and that end would be in the way. The one of those closest in angle to the ray is visible.
+    let [a, b] = [edge, (edge + 1) % ring.len()];
+    let mut visible = if points[ring[a]][0] > points[ring[b]][0] { a } else { b };
+    let i = [x, m[1]];
+    let p = points[ring[visible]];
+    let triangle = if orient(m, i, p) >= 0.0 { [m, i, p] } else { [m, p, i] };
+    let angle = |point: [f64; 2]| {
+        let d = [point[0] - m[0], point[1] - m[1]];
+        (d[1].abs() / d[0].hypot(d[1]), d[0].hypot(d[1]))
+    };
+    let mut best = angle(p);
+    for candidate in 0..ring.len() {
+        let point = points[ring[candidate]];
+        let n = ring.len();
+        let reflex = orient(points[ring[(candidate + n - 1) % n]], point, points[ring[(candidate + 1) % n]]) <= 0.0;
+        if candidate == visible
+            || !reflex
+            || point == m
+            || !in_triangle(point, triangle[0], triangle[1], triangle[2], 0.0)
+        {
+            continue;
+        }
+        let candidate_angle = angle(point);
+        if candidate_angle < best {
+            best = candidate_angle;
+            visible = candidate;
+        }
+    }
+
+    // ..., visible, hole from its rightmost point all the way round back to it, visible, ...
+    let p = ring[visible];
+    let spliced: Vec<usize> = (0..=hole.len()).map(|k| hole[(start + k) % hole.len()]).chain([p]).collect();
+    ring.splice(visible + 1..visible + 1, spliced);
+    true
+}
+
+fn ear_clip(points: &[[f64; 2]], mut ring: Vec<usize>) -> Vec<u32> {
+    let tolerance = EPSILON * scale(points);
+    let mut indices = Vec::with_capacity(ring.len().saturating_sub(2) * 3);
+    let corners = |ring: &[usize], i: usize| {
+        let n = ring.len();
+        [ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]]
+    };
+
+    while ring.len() > 3 {
+        let ear = (0..ring.len()).find(|&i| {
+            let [a, b, c] = corners(&ring, i).map(|index| points[index]);
+            if orient(a, b, c) <= tolerance {
+                return false;
+            }
+            // No other point may be in the way. Bridges visit the same places twice, which doesn't count.
+            ring.iter().all(|&other| {
+                let point = points[other];
+                point == a || point == b || point == c || !in_triangle(point, a, b, c, tolerance)
+            })
+        });
+
+        match ear {
+            Some(i) => {
+                indices.extend(corners(&ring, i).map(|index| index as u32));
+                ring.remove(i);
+            },
+            None => {
+                // Only collinear or numerically awkward points are left to clip. Drop the one whose triangle has the
+                // least area, keeping the triangle if it is a real one.
+                let i = (0..ring.len())
+                    .min_by(|&a, &b| {
+                        let area = |i| {
+                            let [a, b, c] = corners(&ring, i).map(|index| points[index]);
+                            orient(a, b, c).abs()
+                        };
+                        area(a).total_cmp(&area(b))
+                    })
+                    .unwrap();
+                let triangle = corners(&ring, i);
+                let [a, b, c] = triangle.map(|index| points[index]);
+                if orient(a, b, c) > tolerance {
+                    indices.extend(triangle.map(|index| index as u32));
+                }
+                ring.remove(i);
+            },
+        }
+    }
+
+    if ring.len() == 3 && orient(points[ring[0]], points[ring[1]], points[ring[2]]) > tolerance {
+        indices.extend(ring.iter().map(|&index| index as u32));
+    }
+    indices
+}
+
+/// Lawson's flips: any diagonal whose two triangles have the other one's far corner inside their circumcircle is
+/// swapped for the other diagonal of their quad, until none is left.
+fn flip_to_delaunay(points: &[[f64; 2]], indices: &mut [u32], constraints: &HashSet<(usize, usize)>) {
+    let tolerance = EPSILON * scale(points);
+    let triangles = indices.len() / 3;
+    // Every flip makes the triangulation strictly better, this only guards against rounding going round in circles.
+    for _ in 0..triangles * triangles + 1 {
+        let mut shared: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for triangle in 0..triangles {
+            for corner in 0..3 {
+                let a = indices[triangle * 3 + corner] as usize;
+                let b = indices[triangle * 3 + (corner + 1) % 3] as usize;
+                shared.entry(edge(a, b)).or_default().push((triangle, corner));
+            }
+        }
+
+        let flip = shared.iter().find_map(|(edge, sides)| {
+            if sides.len() != 2 || constraints.contains(edge) {
+                return None;
+            }
+            // The first triangle is a, b, c with the edge from a to b, the second has it from b to a and ends in d.
+            let (first, corner) = sides[0];
+            let (second, other) = sides[1];
+            let at = |triangle: usize, corner: usize| indices[triangle * 3 + (corner % 3)] as usize;
+            let [a, b, c] = [at(first, corner), at(first, corner + 1), at(first, corner + 2)];
+            let d = at(second, other + 2);
+            if at(second, other) != b || at(second, other + 1) != a {
+                return None;
+            }
+
+            let [pa, pb, pc, pd] = [a, b, c, d].map(|index| points[index]);
+            let convex = orient(pa, pd, pc) > tolerance && orient(pd, pb, pc) > tolerance;
+            (convex && in_circumcircle(pa, pb, pc, pd) > tolerance).then_some((first, second, [a, d, c], [d, b, c]))
+        });
+
+        match flip {
+            Some((first, second, a, b)) => {
+                indices[first * 3..first * 3 + 3].copy_from_slice(&a.map(|index| index as u32));
+                indices[second * 3..second * 3 + 3].copy_from_slice(&b.map(|index| index as u32));
+            },
+            None => return,
+        }
+    }
+}
+
+/// Positive when `d` is inside the circumcircle of the counter-clockwise triangle `a`, `b`, `c`.
+fn in_circumcircle(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> f64 {
+    let [ax, ay] = [a[0] - d[0], a[1] - d[1]];
+    let [bx, by] = [b[0] - d[0], b[1] - d[1]];
+    let [cx, cy] = [c[0] - d[0], c[1] - d[1]];
+    (ax * ax + ay * ay) * (bx * cy - cx * by) - (bx * bx + by * by) * (ax * cy - cx * ay)
+        + (cx * cx + cy * cy) * (ax * by - bx * ay)
+}
@@ -0,0 +1,26 @@
+/// The `wgpu::VertexBufferLayout` of a vertex or instance struct. Derive it with `#[derive(VertexLayout)]` rather than
+/// writing the attributes out by hand, see the `wgpu_common_derive` crate for the options.
+///
+/// ```text
+/// #[repr(C)]
+/// #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+/// #[vertex(step_mode = "instance")]
+/// pub struct InstanceRaw {
+///     #[vertex(location = 5, matrix)]
+///     model: [[f32; 4]; 4],                   // @location(5) to @location(8), one Float32x4 per column
+/// }
+///
+/// RenderPipelineBuilder::new(&shader).vertex_buffer(Vertex::desc()).vertex_buffer(InstanceRaw::desc())
+/// ```
+pub trait VertexLayout: Sized {
+    const ATTRIBUTES: &'static [wgpu::VertexAttribute];
+    const STEP_MODE: wgpu::VertexStepMode;
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: Self::STEP_MODE,
+            attributes: Self::ATTRIBUTES,
+        }
+    }
+}
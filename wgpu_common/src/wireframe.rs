@@ -0,0 +1,166 @@
+use std::fmt;
+
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+/// What the F key cycles through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireframeMode {
+    Fill,
+    Wireframe,
+    /// The filled geometry with its edges drawn on top.
+    Overlay,
+}
+
+impl WireframeMode {
+    pub fn next(self) -> Self {
+        match self {
+            WireframeMode::Fill => WireframeMode::Wireframe,
+            WireframeMode::Wireframe => WireframeMode::Overlay,
+            WireframeMode::Overlay => WireframeMode::Fill,
+        }
+    }
+}
+
+/// How a pipeline draws its triangles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PipelineStyle {
+    Fill,
+    /// Rasterized as lines by the GPU, needs `Features::POLYGON_MODE_LINE`.
+    NativeLines,
+    /// Filled, but the fragment shader only keeps the pixels close to an edge, using barycentric coordinates. Works
+    /// everywhere, but the vertex shader can only work out the barycentric coordinates for non-indexed draws.
+    Barycentric,
+}
+
+impl PipelineStyle {
+    pub fn polygon_mode(self) -> wgpu::PolygonMode {
+        match self {
+            PipelineStyle::NativeLines => wgpu::PolygonMode::Line,
+            PipelineStyle::Fill | PipelineStyle::Barycentric => wgpu::PolygonMode::Fill,
+        }
+    }
+
+    /// `fs_main` for the filled pipeline, `fs_wireframe` for the edges.
+    pub fn fragment_entry_point(self) -> &'static str {
+        match self {
+            PipelineStyle::Fill => "fs_main",
+            PipelineStyle::NativeLines | PipelineStyle::Barycentric => "fs_wireframe",
+        }
+    }
+
+    /// Value for the `NATIVE_LINES` override constant the wireframe fragment shader checks.
+    pub fn constants(self) -> &'static [(&'static str, f64)] {
+        match self {
+            PipelineStyle::Fill => &[],
+            PipelineStyle::NativeLines => &[("NATIVE_LINES", 1.0)],
+            PipelineStyle::Barycentric => &[("NATIVE_LINES", 0.0)],
+        }
+    }
+
+    /// Edges are blended over what is already there so the barycentric ones can be anti-aliased.
+    pub fn blend(self, fill: Option<wgpu::BlendState>) -> Option<wgpu::BlendState> {
+        match self {
+            PipelineStyle::Fill => fill,
+            PipelineStyle::NativeLines | PipelineStyle::Barycentric => Some(wgpu::BlendState::ALPHA_BLENDING),
+        }
+    }
+}
+
+/// Wireframe toggle for an exercise. F cycles between fill, wireframe and fill with the edges on top.
+///
+/// Edges are drawn natively with `PolygonMode::Line` when the device has `Features::POLYGON_MODE_LINE` (ask for it
+/// with `GpuContextBuilder::optional_features(Wireframe::FEATURES)`), otherwise with the barycentric fallback, which
+/// needs the indexed geometry expanded with [`deindex`]. The exercise's shader provides `fs_wireframe` on top of
+/// `#include "wireframe.wgsl"`, which has the WGSL side: `barycentric`, `NATIVE_LINES` and `wireframe_coverage`.
+#[derive(Clone, Copy, Debug)]
+pub struct Wireframe {
+    mode: WireframeMode,
+    native: bool,
+}
+
+impl Wireframe {
+    pub const FEATURES: wgpu::Features = wgpu::Features::POLYGON_MODE_LINE;
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let native = device.features().contains(Self::FEATURES);
+        log::info!("Wireframe edges are drawn {}", if native { "with PolygonMode::Line" } else { "by the barycentric fallback" });
+
+        Self {
+            mode: WireframeMode::Fill,
+            native,
+        }
+    }
+
+    pub fn mode(&self) -> WireframeMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: WireframeMode) {
+        self.mode = mode;
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.native
+    }
+
+    /// The style to build the edge pipeline with.
+    pub fn edge_style(&self) -> PipelineStyle {
+        if self.native {
+            PipelineStyle::NativeLines
+        } else {
+            PipelineStyle::Barycentric
+        }
+    }
+
+    pub fn draws_fill(&self) -> bool {
+        self.mode != WireframeMode::Wireframe
+    }
+
+    pub fn draws_edges(&self) -> bool {
+        self.mode != WireframeMode::Fill
+    }
+
+    /// Whether the edges have to be drawn from the geometry expanded by [`deindex`] instead of the index buffer.
+    pub fn needs_deindexed(&self) -> bool {
+        !self.native
+    }
+
+    /// Cycles the mode on F. Returns true when the key was used.
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    physical_key: PhysicalKey::Code(KeyCode::KeyF),
+                    state: ElementState::Pressed,
+                    repeat: false,
+                    ..
+                },
+                ..
+            } => {
+                self.mode = self.mode.next();
+                log::info!("Wireframe mode: {}", self);
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Wireframe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            WireframeMode::Fill => return write!(f, "fill"),
+            WireframeMode::Wireframe => "wireframe",
+            WireframeMode::Overlay => "overlay",
+        };
+
+        write!(f, "{} ({})", mode, if self.native { "native lines" } else { "barycentric" })
+    }
+}
+
+/// Expands indexed triangles into a plain triangle list, so vertex `i` is corner `i % 3` of its triangle. That is
+/// what the barycentric wireframe needs, since a vertex shared by several triangles is a different corner in each.
+pub fn deindex<V: Copy, I: Copy + Into<u32>>(vertices: &[V], indices: &[I]) -> Vec<V> {
+    indices.iter().map(|&index| vertices[index.into() as usize]).collect()
+}
@@ -0,0 +1,153 @@
+use std::num::NonZeroU64;
+use wgpu::{BindGroupLayoutEntry, BindingType, ShaderStages};
+use wgpu_common::{BindGroupLayouts, BindingProblem};
+
+const SHADER: &str = r#"
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(0) @binding(0)
+var texture_diffuse: texture_2d<f32>;
+
+@group(0) @binding(1)
+var sampler_diffuse: sampler;
+
+@group(2) @binding(0)
+var<storage, read_write> counters: array<u32>;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return camera.view_proj * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    counters[0] = 1u;
+    return textureSample(texture_diffuse, sampler_diffuse, position.xy) * camera.view_proj[0][0];
+}
+"#;
+
+fn reflect() -> BindGroupLayouts {
+    BindGroupLayouts::from_wgsl("test.wgsl", SHADER).unwrap()
+}
+
+// The texture layout learn_wgpu exercise 7 used to write out by hand.
+fn texture_layout() -> Vec<BindGroupLayoutEntry> {
+    vec![
+        BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            binding: 1,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        },
+    ]
+}
+
+#[test]
+fn reflects_types_and_visibility() {
+    let bind_groups = reflect();
+    assert_eq!(bind_groups.groups().collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(bind_groups.entries(0), texture_layout().as_slice());
+    assert_eq!(
+        bind_groups.entries(1),
+        &[BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(64),
+            },
+            count: None,
+        }]
+    );
+    assert_eq!(
+        bind_groups.entries(2)[0].ty,
+        BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: NonZeroU64::new(4),
+        }
+    );
+    assert!(bind_groups.entries(3).is_empty());
+}
+
+#[test]
+fn matching_handwritten_layout_has_no_problems() {
+    let bind_groups = reflect();
+    assert!(bind_groups.check(0, &texture_layout()).is_empty());
+
+    // Leaving min_binding_size to draw time and filterable: false are both fine.
+    let mut layout = bind_groups.entries(1).to_vec();
+    layout[0].ty = BindingType::Buffer {
+        ty: wgpu::BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+    };
+    assert!(bind_groups.check(1, &layout).is_empty());
+}
+
+#[test]
+fn reports_disagreements_with_the_shader() {
+    let bind_groups = reflect();
+
+    let mut layout = texture_layout();
+    layout[0].ty = BindingType::Texture {
+        multisampled: false,
+        view_dimension: wgpu::TextureViewDimension::D2,
+        sample_type: wgpu::TextureSampleType::Uint,
+    };
+    layout[1].binding = 2;
+    assert_eq!(
+        bind_groups.check(0, &layout),
+        vec![
+            BindingProblem::TypeMismatch {
+                group: 0,
+                binding: 0,
+                shader: texture_layout()[0].ty,
+                layout: layout[0].ty,
+            },
+            BindingProblem::MissingBinding {
+                group: 0,
+                binding: 1,
+                shader: texture_layout()[1].ty,
+            },
+            BindingProblem::UnusedBinding { group: 0, binding: 2 },
+        ]
+    );
+
+    // The camera is read in both stages, and the whole matrix is 64 bytes.
+    let camera = [BindGroupLayoutEntry {
+        binding: 0,
+        visibility: ShaderStages::VERTEX,
+        ty: BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: NonZeroU64::new(16),
+        },
+        count: None,
+    }];
+    let problems = bind_groups.check(1, &camera);
+    assert_eq!(
+        problems.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        vec![
+            "@group(1) @binding(0): the shader reads 64 bytes but the layout's min_binding_size is 16",
+            "@group(1) @binding(0): used in VERTEX | FRAGMENT but only visible in VERTEX",
+        ]
+    );
+    assert!(problems.iter().all(BindingProblem::is_error));
+}
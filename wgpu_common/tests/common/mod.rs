@@ -0,0 +1,60 @@
+// Offscreen rendering shared by the tests that need a device. Not every test uses every helper.
+#![allow(dead_code)]
+
+use wgpu_common::{fallback_adapter_available, GpuContext, Target};
+
+/// Width and height of the target `render` draws into.
+pub const SIZE: u32 = 64;
+
+/// A headless context on the fallback adapter, `None` where there isn't one.
+pub fn context() -> Option<GpuContext> {
+    if !fallback_adapter_available() {
+        eprintln!("skipping: no fallback adapter is available");
+        return None;
+    }
+
+    Some(pollster::block_on(GpuContext::builder().build(Target::headless(SIZE, SIZE))).unwrap())
+}
+
+/// Draws on black with the fallback adapter, `None` where there isn't one. `draw` gets the context to upload with and
+/// the render pass, whose color target has the context's view format.
+pub fn render(draw: impl FnOnce(&GpuContext, &mut wgpu::RenderPass<'_>)) -> Option<image::RgbaImage> {
+    let context = context()?;
+
+    let frame = context.acquire_frame().unwrap();
+    let view = frame.texture().create_view(&wgpu::TextureViewDescriptor {
+        format: Some(context.view_format()),
+        ..Default::default()
+    });
+    let mut encoder = context.device.create_command_encoder(&Default::default());
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &view,
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+    draw(&context, &mut render_pass);
+    drop(render_pass);
+    context.queue.submit([encoder.finish()]);
+    frame.present();
+
+    Some(context.capture().unwrap())
+}
+
+pub fn red(image: &image::RgbaImage, x: u32, y: u32) -> u8 {
+    image.get_pixel(x, y)[0]
+}
+
+/// The pixels in row `y` that are fully covered.
+pub fn covered_columns(image: &image::RgbaImage, y: u32) -> Vec<u32> {
+    (0..SIZE).filter(|&x| red(image, x, y) == 255).collect()
+}
@@ -0,0 +1,50 @@
+use wgpu_common::{CacheFileError, DiskPipelineCache};
+
+const IDENTITY: &str = "llvmpipe (Vulkan, 10005:0000), driver llvmpipe Mesa 24.0.1";
+const DATA: &[u8] = b"opaque driver pipeline data";
+
+#[test]
+fn round_trips_the_data() {
+    let file = DiskPipelineCache::encode(IDENTITY, DATA);
+    assert_eq!(DiskPipelineCache::decode(&file, IDENTITY), Ok(DATA));
+}
+
+#[test]
+fn rejects_files_from_another_driver() {
+    let file = DiskPipelineCache::encode("llvmpipe (Vulkan, 10005:0000), driver llvmpipe Mesa 23.3.0", DATA);
+    assert!(matches!(
+        DiskPipelineCache::decode(&file, IDENTITY),
+        Err(CacheFileError::OtherAdapter { found }) if found.ends_with("Mesa 23.3.0")
+    ));
+}
+
+#[test]
+fn rejects_corrupt_and_truncated_files() {
+    let file = DiskPipelineCache::encode(IDENTITY, DATA);
+
+    let mut flipped = file.clone();
+    *flipped.last_mut().unwrap() ^= 0x01;
+    assert_eq!(DiskPipelineCache::decode(&flipped, IDENTITY), Err(CacheFileError::ChecksumMismatch));
+
+    let mut trailing = file.clone();
+    trailing.push(0);
+    assert_eq!(DiskPipelineCache::decode(&trailing, IDENTITY), Err(CacheFileError::ChecksumMismatch));
+
+    for len in [0, 4, 12, 20, file.len() - 1] {
+        assert!(DiskPipelineCache::decode(&file[..len], IDENTITY).is_err(), "accepted {} of {} bytes", len, file.len());
+    }
+    assert_eq!(DiskPipelineCache::decode(&file[..file.len() - 1], IDENTITY), Err(CacheFileError::Truncated));
+}
+
+#[test]
+fn rejects_other_files_and_versions() {
+    assert_eq!(
+        DiskPipelineCache::decode(b"not a cache file at all", IDENTITY),
+        Err(CacheFileError::NotACacheFile)
+    );
+
+    // The format version follows the 8 byte magic.
+    let mut file = DiskPipelineCache::encode(IDENTITY, DATA);
+    file[8..12].copy_from_slice(&99u32.to_le_bytes());
+    assert_eq!(DiskPipelineCache::decode(&file, IDENTITY), Err(CacheFileError::UnsupportedVersion(99)));
+}
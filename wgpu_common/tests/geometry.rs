@@ -0,0 +1,130 @@
+use wgpu_common::{cone, cube, cylinder, icosphere, plane, torus, uv_sphere, MeshData};
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn all_shapes() -> Vec<MeshData> {
+    vec![
+        cube(1.0),
+        uv_sphere(1.0, 16, 8),
+        icosphere(1.0, 2),
+        plane(2.0, 1.0, 4, 3),
+        cylinder(0.5, 2.0, 12),
+        cone(0.5, 1.0, 12),
+        torus(1.0, 0.25, 16, 8),
+    ]
+}
+
+#[test]
+fn counts_follow_the_resolution() {
+    let counts = |mesh: MeshData| (mesh.vertices.len(), mesh.indices.len() / 3);
+
+    assert_eq!(counts(cube(1.0)), (24, 12));
+    // A ring of 17 vertices (the seam twice) per stack boundary, and one triangle per quad next to the poles.
+    assert_eq!(counts(uv_sphere(1.0, 16, 8)), (17 * 9, 16 * 2 * 7));
+    assert_eq!(counts(plane(2.0, 1.0, 4, 3)), (5 * 4, 4 * 3 * 2));
+    // The side's two rings of 13, and a center and a ring of 12 for each cap.
+    assert_eq!(counts(cylinder(0.5, 2.0, 12)), (2 * 13 + 2 * 13, 12 * 2 + 2 * 12));
+    // The base ring of 13, a tip per side, and the cap.
+    assert_eq!(counts(cone(0.5, 1.0, 12)), (13 + 12 + 13, 12 + 12));
+    assert_eq!(counts(torus(1.0, 0.25, 16, 8)), (17 * 9, 16 * 8 * 2));
+
+    for subdivisions in 0..4 {
+        let (vertices, triangles) = counts(icosphere(1.0, subdivisions));
+        assert_eq!(triangles, 20 * 4usize.pow(subdivisions));
+        // Euler's V - E + F = 2 gives 10 * 4^n + 2 distinct positions, a few more repeat along the seam and poles.
+        assert!(vertices >= 10 * 4usize.pow(subdivisions) + 2);
+        assert!(vertices < 12 * 4usize.pow(subdivisions) + 12);
+    }
+}
+
+#[test]
+fn triangles_are_counter_clockwise_seen_from_their_normals() {
+    for mesh in all_shapes() {
+        assert!(mesh.indices.iter().all(|&index| (index as usize) < mesh.vertices.len()), "{}", mesh.name);
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let face = cross(sub(b.position, a.position), sub(c.position, a.position));
+            assert!(dot(face, face) > 0.0, "{} has a degenerate triangle {:?}", mesh.name, triangle);
+
+            let normals = [a.normal, b.normal, c.normal];
+            let normal = [0, 1, 2].map(|axis| normals.iter().map(|normal| normal[axis]).sum());
+            assert!(dot(face, normal) > 0.0, "{} triangle {:?} is wound clockwise", mesh.name, triangle);
+        }
+    }
+}
+
+#[test]
+fn normals_point_outwards() {
+    for mesh in [uv_sphere(2.0, 16, 8), icosphere(2.0, 2)] {
+        for vertex in &mesh.vertices {
+            assert!((dot(vertex.position, vertex.position).sqrt() - 2.0).abs() < 1e-5, "{}", mesh.name);
+            assert!(dot(vertex.position, vertex.normal) > 1.99, "{}", mesh.name);
+        }
+    }
+
+    // Away from the ring's center line, i.e. from its major circle.
+    for vertex in &torus(1.0, 0.25, 16, 8).vertices {
+        let [x, _, z] = vertex.position;
+        let length = (x * x + z * z).sqrt();
+        let center = [x / length, 0.0, z / length];
+        let outwards = sub(vertex.position, center);
+        assert!((dot(outwards, outwards).sqrt() - 0.25).abs() < 1e-5);
+        assert!(dot(outwards, vertex.normal) > 0.24);
+    }
+}
+
+#[test]
+fn normals_and_tangents_are_unit_length_and_perpendicular() {
+    for mesh in all_shapes() {
+        for vertex in &mesh.vertices {
+            let [x, y, z, w] = vertex.tangent;
+            let tangent = [x, y, z];
+            assert!((dot(vertex.normal, vertex.normal) - 1.0).abs() < 1e-5, "{} normal {:?}", mesh.name, vertex.normal);
+            assert!((dot(tangent, tangent) - 1.0).abs() < 1e-5, "{} tangent {:?}", mesh.name, tangent);
+            assert!(dot(tangent, vertex.normal).abs() < 1e-5, "{}", mesh.name);
+            assert_eq!(w.abs(), 1.0, "{}", mesh.name);
+        }
+    }
+}
+
+#[test]
+fn texture_coordinates_start_in_the_top_left() {
+    for mesh in all_shapes().into_iter().filter(|mesh| mesh.name != "Icosphere") {
+        for vertex in &mesh.vertices {
+            let [u, v] = vertex.texture_coords;
+            assert!((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v), "{} {:?}", mesh.name, vertex.texture_coords);
+        }
+    }
+
+    // The cube's front face shows the texture upright: its top left corner is the top left of the texture.
+    let front = &cube(2.0).vertices[..4];
+    let top_left = front.iter().find(|vertex| vertex.position == [-1.0, 1.0, 1.0]).unwrap();
+    assert_eq!(top_left.texture_coords, [0.0, 0.0]);
+    // Which makes the bitangent (growing v) point down, like the loaded models' tangents.
+    assert!(front.iter().all(|vertex| vertex.tangent == [1.0, 0.0, 0.0, -1.0]));
+
+    // The top pole of the UV sphere is at v = 0.
+    let sphere = uv_sphere(1.0, 16, 8);
+    assert!(sphere.vertices.iter().filter(|vertex| vertex.texture_coords[1] == 0.0).all(|vertex| vertex.position[1] > 0.99));
+}
+
+#[test]
+fn icosphere_seam_triangles_do_not_wrap_around_the_texture() {
+    let mesh = icosphere(1.0, 3);
+    for triangle in mesh.indices.chunks_exact(3) {
+        let us = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].texture_coords[0]);
+        let spread = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(spread < 0.25, "{:?}", us);
+    }
+}
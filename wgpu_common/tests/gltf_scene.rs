@@ -0,0 +1,131 @@
+use std::path::Path;
+use wgpu_common::{InstanceMatrix, SceneData, TextureSource};
+
+fn bundled_scene() -> SceneData {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../learn_wgpu/beginner/exercise7_instancing/.assets/scene.gltf");
+    SceneData::load_gltf(&path).unwrap()
+}
+
+fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+    for (actual, expected) in actual.iter().zip(&expected) {
+        assert!((actual - expected).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+}
+
+// A .glb with one triangle strip quad, an embedded 2x1 PNG and a node placed by a matrix under a translated parent.
+fn glb() -> Vec<u8> {
+    let mut png = Vec::new();
+    image::RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 128])
+        .unwrap()
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+
+    let positions: [[f32; 3]; 4] = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]];
+    let mut bin: Vec<u8> = positions.iter().flatten().flat_map(|value| value.to_le_bytes()).collect();
+    let image_offset = bin.len();
+    bin.extend(&png);
+    while !bin.len().is_multiple_of(4) {
+        bin.push(0);
+    }
+
+    let json = format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "scenes": [{{ "nodes": [0] }}],
+            "nodes": [
+                {{ "name": "Parent", "translation": [0, 0, -2], "children": [1] }},
+                {{ "name": "Quad", "mesh": 0, "matrix": [2,0,0,0, 0,2,0,0, 0,0,2,0, 1,0,0,1] }}
+            ],
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "mode": 5, "material": 0 }}] }}],
+            "materials": [{{ "pbrMetallicRoughness": {{ "baseColorFactor": [0.5, 0.5, 0.5, 1], "baseColorTexture": {{ "index": 0 }} }} }}],
+            "textures": [{{ "source": 0 }}],
+            "images": [{{ "bufferView": 1, "mimeType": "image/png" }}],
+            "buffers": [{{ "byteLength": {bin} }}],
+            "bufferViews": [
+                {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+                {{ "buffer": 0, "byteOffset": {image_offset}, "byteLength": {png} }}
+            ],
+            "accessors": [{{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }}]
+        }}"#,
+        bin = bin.len(),
+        image_offset = image_offset,
+        png = png.len(),
+    );
+    let mut json = json.into_bytes();
+    while !json.len().is_multiple_of(4) {
+        json.push(b' ');
+    }
+
+    let mut glb = Vec::new();
+    glb.extend(b"glTF");
+    glb.extend(2u32.to_le_bytes());
+    glb.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+    glb.extend((json.len() as u32).to_le_bytes());
+    glb.extend(b"JSON");
+    glb.extend(&json);
+    glb.extend((bin.len() as u32).to_le_bytes());
+    glb.extend(b"BIN\0");
+    glb.extend(&bin);
+    glb
+}
+
+#[test]
+fn loads_every_primitive_and_material() {
+    let scene = bundled_scene();
+    assert_eq!(scene.meshes, [0..1, 1..2]);
+    assert_eq!(scene.model.meshes.len(), 2);
+
+    let cube = &scene.model.meshes[0];
+    assert_eq!(cube.vertices.len(), 24);
+    assert_eq!(cube.indices.len(), 36);
+    assert_eq!(cube.material, Some(0));
+    // Tangents are computed when the file has none.
+    assert!(cube.vertices.iter().all(|vertex| vertex.tangent[3].abs() == 1.0));
+
+    let names: Vec<&str> = scene.model.materials.iter().map(|material| material.name.as_str()).collect();
+    assert_eq!(names, ["Happy Tree", "Orange"]);
+    assert!(matches!(scene.model.materials[0].diffuse_texture, Some(TextureSource::Image(_))));
+    assert_eq!(scene.model.materials[1].diffuse_color, [1.0, 0.5, 0.1, 1.0]);
+    assert_eq!(scene.model.materials[1].diffuse_texture, None);
+}
+
+#[test]
+fn children_inherit_their_parents_transform() {
+    let scene = bundled_scene();
+    let names: Vec<Option<&str>> = scene.nodes.iter().map(|node| node.name.as_deref()).collect();
+    assert_eq!(names, [Some("Tree Cube"), Some("Left Moon"), Some("Right Moon")]);
+
+    // The moons share one mesh, so it is drawn once per node.
+    let moons = scene.instances(1);
+    assert_eq!(moons.len(), 2);
+
+    // The cube is turned 45 degrees around y, which moves the left moon's (-0.9, 0.5, 0) to (-0.9 cos 45, 0.5, 0.9 sin 45).
+    let offset = 0.9 * std::f32::consts::FRAC_1_SQRT_2;
+    assert_close(moons[0].model[3], [-offset, 0.5, offset, 1.0]);
+}
+
+#[test]
+fn parses_binary_gltf_with_embedded_images() {
+    let scene = SceneData::parse_gltf(&glb()).unwrap();
+
+    // The strip's two triangles both stay counter-clockwise.
+    let quad = &scene.model.meshes[0];
+    assert_eq!(quad.indices, [0, 1, 2, 2, 1, 3]);
+    assert!(quad.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+
+    match &scene.model.materials[0].diffuse_texture {
+        Some(TextureSource::Image(image)) => {
+            assert_eq!(image.dimensions(), (2, 1));
+            assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255, 128]);
+        },
+        other => panic!("expected an embedded image, got {:?}", other),
+    }
+
+    // Scaled by 2 and moved by (1, 0, 0) inside a parent moved by (0, 0, -2).
+    assert_eq!(
+        scene.instances(0),
+        [InstanceMatrix {
+            model: [[2.0, 0.0, 0.0, 0.0], [0.0, 2.0, 0.0, 0.0], [0.0, 0.0, 2.0, 0.0], [1.0, 0.0, -2.0, 1.0]],
+        }]
+    );
+}
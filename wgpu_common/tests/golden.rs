@@ -0,0 +1,109 @@
+use std::fs;
+
+use image::{Rgba, RgbaImage};
+use wgpu_common::{check_golden, compare, GoldenOutcome, Metric, Tolerance};
+
+const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+/// A black 10x10 image with `pixels` changed.
+fn image(pixels: &[(u32, u32, Rgba<u8>)]) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(10, 10, BLACK);
+    for &(x, y, pixel) in pixels {
+        image.put_pixel(x, y, pixel);
+    }
+    image
+}
+
+fn tolerance(metric: Metric, threshold: f32, max_mismatched: f32) -> Tolerance {
+    Tolerance {
+        metric,
+        threshold,
+        max_mismatched,
+    }
+}
+
+#[test]
+fn only_differences_above_the_threshold_are_mismatched() {
+    let actual = image(&[(1, 1, Rgba([2, 0, 0, 255])), (2, 2, Rgba([0, 0, 0, 252]))]);
+    let comparison = compare(&actual, &image(&[]), &Tolerance::default()).unwrap();
+    assert_eq!((comparison.mismatched, comparison.total), (1, 100));
+    assert_eq!(comparison.max_difference, 3.0);
+
+    let comparison = compare(&actual, &image(&[]), &tolerance(Metric::Channel, 3.0, 0.0)).unwrap();
+    assert_eq!(comparison.mismatched, 0);
+    assert!(comparison.passes(&tolerance(Metric::Channel, 3.0, 0.0)));
+
+    let error = compare(&RgbaImage::new(4, 2), &image(&[]), &Tolerance::default()).err().unwrap();
+    assert_eq!(error.to_string(), "image is 4x2 but the golden is 10x10");
+}
+
+#[test]
+fn perceptual_weighs_channels_by_how_different_they_look() {
+    let green = image(&[(0, 0, Rgba([0, 30, 0, 255]))]);
+    let blue = image(&[(0, 0, Rgba([0, 0, 30, 255]))]);
+    let perceptual = tolerance(Metric::Perceptual, 0.0, 0.0);
+
+    // The same channel difference, but green changes look bigger than blue ones. Alpha is left out.
+    assert_eq!(compare(&green, &image(&[]), &Tolerance::default()).unwrap().max_difference, 30.0);
+    assert_eq!(compare(&blue, &image(&[]), &Tolerance::default()).unwrap().max_difference, 30.0);
+    let green_difference = compare(&green, &image(&[]), &perceptual).unwrap().max_difference;
+    let blue_difference = compare(&blue, &image(&[]), &perceptual).unwrap().max_difference;
+    assert!((green_difference - 20.0).abs() < 0.01, "{}", green_difference);
+    assert!(blue_difference > 17.0 && blue_difference < green_difference, "{}", blue_difference);
+    let alpha = image(&[(0, 0, Rgba([0, 0, 0, 0]))]);
+    assert_eq!(compare(&alpha, &image(&[]), &perceptual).unwrap().max_difference, 0.0);
+
+    // Black against white is close to the top of the same 0-255 scale as Channel.
+    let white = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+    let difference = compare(&white, &image(&[]), &perceptual).unwrap().max_difference;
+    assert!((254.0..=255.0).contains(&difference), "{}", difference);
+}
+
+#[test]
+fn max_mismatched_is_the_fraction_of_pixels_accepted() {
+    // 1 of 100 pixels, what `--max-mismatched 1` (percent) accepts.
+    let comparison = compare(&image(&[(5, 5, Rgba([255, 0, 0, 255]))]), &image(&[]), &Tolerance::default()).unwrap();
+    assert_eq!(comparison.mismatched_fraction(), 0.01);
+    assert!(comparison.passes(&tolerance(Metric::Channel, 2.0, 0.01)));
+    assert!(!comparison.passes(&tolerance(Metric::Channel, 2.0, 0.009)));
+    assert!(!comparison.passes(&Tolerance::default()));
+}
+
+#[test]
+fn diff_marks_mismatches_in_red_over_a_faded_golden() {
+    let expected = image(&[(1, 0, Rgba([255, 255, 255, 255]))]);
+    let actual = image(&[(1, 0, Rgba([255, 255, 255, 255])), (2, 0, Rgba([0, 255, 0, 255]))]);
+    let diff = compare(&actual, &expected, &Tolerance::default()).unwrap().diff;
+
+    assert_eq!(diff.dimensions(), (10, 10));
+    assert_eq!(*diff.get_pixel(0, 0), Rgba([128, 128, 128, 255]));
+    assert_eq!(*diff.get_pixel(1, 0), Rgba([255, 255, 255, 255]));
+    assert_eq!(*diff.get_pixel(2, 0), Rgba([255, 0, 0, 255]));
+}
+
+#[test]
+fn failed_checks_write_the_image_and_diff_next_to_the_golden() {
+    let dir = std::env::temp_dir().join(format!("wgpu_common_golden_{}", std::process::id()));
+    let golden = dir.join("exercise.png");
+    let tolerance = Tolerance::default();
+
+    assert!(matches!(check_golden(&image(&[]), &golden, &tolerance, false).unwrap(), GoldenOutcome::Missing));
+    assert!(matches!(check_golden(&image(&[]), &golden, &tolerance, true).unwrap(), GoldenOutcome::Blessed));
+    assert!(matches!(check_golden(&image(&[]), &golden, &tolerance, false).unwrap(), GoldenOutcome::Passed(_)));
+
+    let changed = image(&[(3, 3, Rgba([255, 255, 255, 255]))]);
+    match check_golden(&changed, &golden, &tolerance, false).unwrap() {
+        GoldenOutcome::Failed {
+            comparison,
+            actual_path,
+            diff_path,
+        } => {
+            assert_eq!(comparison.mismatched, 1);
+            assert_eq!((actual_path, diff_path.clone()), (dir.join("exercise.actual.png"), dir.join("exercise.diff.png")));
+            assert_eq!(image::open(&diff_path).unwrap().to_rgba8(), comparison.diff);
+        },
+        _ => panic!("the changed image passed"),
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,28 @@
+use wgpu_common::Indices;
+
+#[test]
+fn small_meshes_get_u16_indices() {
+    let indices = Indices::narrowest(&[0u32, 1, 2, 65534]);
+    assert_eq!(indices, Indices::U16(vec![0, 1, 2, 65534]));
+    assert_eq!(indices.format(), wgpu::IndexFormat::Uint16);
+    assert_eq!(indices.as_bytes().len(), 8);
+}
+
+#[test]
+fn indices_past_u16_get_u32() {
+    // 65535 is the u16 primitive restart value, so it already needs u32.
+    for largest in [65535u32, 65536, 1_000_000] {
+        let indices = Indices::narrowest(&[0, largest, 1]);
+        assert_eq!(indices, Indices::U32(vec![0, largest, 1]));
+        assert_eq!(indices.format(), wgpu::IndexFormat::Uint32);
+        assert_eq!(indices.len(), 3);
+        assert_eq!(indices.as_bytes().len(), 12);
+    }
+}
+
+#[test]
+fn accepts_u16_sources() {
+    let indices = Indices::narrowest(&[0u16, 1, 4, 1, 2, 4]);
+    assert_eq!(indices, Indices::U16(vec![0, 1, 4, 1, 2, 4]));
+    assert!(Indices::narrowest::<u16>(&[]).is_empty());
+}
@@ -0,0 +1,118 @@
+use std::path::Path;
+use wgpu::{VertexBufferLayout, VertexFormat, VertexStepMode};
+use wgpu_common::{check_vertex_layouts, validate_wgsl, LayoutProblem};
+
+const SHADER: &str = r#"
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput, @location(2) id: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(model.position + model.color * f32(id), 1.0);
+}
+"#;
+
+fn check(attributes: &[wgpu::VertexAttribute]) -> Vec<LayoutProblem> {
+    let module = validate_wgsl(Path::new("test.wgsl"), SHADER).unwrap();
+    let buffers = [VertexBufferLayout {
+        array_stride: 64,
+        step_mode: VertexStepMode::Vertex,
+        attributes,
+    }];
+    check_vertex_layouts(&module, "vs_main", &buffers)
+}
+
+#[test]
+fn matching_layout_has_no_problems() {
+    assert!(check(&wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Uint32]).is_empty());
+}
+
+#[test]
+fn reports_missing_locations_and_unused_attributes() {
+    let problems = check(&wgpu::vertex_attr_array![0 => Float32x3, 2 => Uint32, 3 => Float32x2]);
+    assert_eq!(
+        problems,
+        vec![
+            LayoutProblem::MissingLocation {
+                location: 1,
+                shader_type: "vec3<f32>".to_string(),
+            },
+            LayoutProblem::UnusedAttribute {
+                location: 3,
+                buffer: 0,
+                format: VertexFormat::Float32x2,
+            },
+        ]
+    );
+    assert!(problems[0].is_error());
+    assert!(!problems[1].is_error());
+}
+
+#[test]
+fn reports_type_mismatches_and_duplicates() {
+    let problems = check(&wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 1 => Float32x3, 2 => Float32]);
+    assert_eq!(
+        problems,
+        vec![
+            LayoutProblem::DuplicateLocation {
+                location: 1,
+                buffers: (0, 0),
+            },
+            LayoutProblem::TypeMismatch {
+                location: 2,
+                shader_type: "u32".to_string(),
+                buffer: 0,
+                format: VertexFormat::Float32,
+            },
+        ]
+    );
+    assert_eq!(
+        problems[1].to_string(),
+        "@location(2): the shader reads u32 but vertex buffer 0 provides Float32, which the shader sees as f32"
+    );
+
+    assert!(problems.iter().all(LayoutProblem::is_error));
+}
+
+#[test]
+fn component_count_differences_are_only_warnings() {
+    // Unorm8x4 reads as vec4<f32>, one component too many for vec3<f32>. wgpu only checks the scalar type and drops
+    // the extra one, like it fills in a missing one.
+    let problems = check(&wgpu::vertex_attr_array![0 => Float32x2, 1 => Unorm8x4, 2 => Uint32]);
+    assert_eq!(
+        problems,
+        vec![
+            LayoutProblem::ComponentMismatch {
+                location: 0,
+                shader_type: "vec3<f32>".to_string(),
+                buffer: 0,
+                format: VertexFormat::Float32x2,
+            },
+            LayoutProblem::ComponentMismatch {
+                location: 1,
+                shader_type: "vec3<f32>".to_string(),
+                buffer: 0,
+                format: VertexFormat::Unorm8x4,
+            },
+        ]
+    );
+    assert!(problems.iter().all(|problem| !problem.is_error()));
+
+    // A different scalar type is still an error, whatever the number of components.
+    let problems = check(&wgpu::vertex_attr_array![0 => Float32x3, 1 => Uint32x4, 2 => Uint32]);
+    assert!(matches!(problems[..], [LayoutProblem::TypeMismatch { location: 1, .. }]));
+    assert!(problems[0].is_error());
+}
+
+#[test]
+fn reports_a_missing_entry_point() {
+    let module = validate_wgsl(Path::new("test.wgsl"), SHADER).unwrap();
+    assert_eq!(
+        check_vertex_layouts(&module, "vs_other", &[]),
+        vec![LayoutProblem::MissingEntryPoint {
+            entry_point: "vs_other".to_string(),
+        }]
+    );
+}
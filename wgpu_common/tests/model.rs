@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use wgpu_common::{ModelData, TextureSource};
+
+// Two objects: a textured quad with normals and a triangle without normals or texture coordinates.
+const OBJ: &str = "\
+mtllib scene.mtl
+o Quad
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+vt 0.0 0.0
+vt 1.0 0.0
+vt 1.0 1.0
+vt 0.0 1.0
+vn 0.0 0.0 1.0
+usemtl Bricks
+f 1/1/1 2/2/1 3/3/1 4/4/1
+o Triangle
+v 0.0 0.0 1.0
+v 0.0 1.0 1.0
+v 0.0 0.0 2.0
+usemtl Red
+f 5 6 7
+";
+
+const MTL: &str = "\
+newmtl Bricks
+map_Kd textures/bricks.png
+newmtl Red
+Kd 1.0 0.0 0.0
+";
+
+fn parse(mtl: Option<&'static str>) -> ModelData {
+    ModelData::parse_obj(&mut OBJ.as_bytes(), |path| {
+        assert_eq!(path, Path::new("scene.mtl"));
+        mtl.map(str::to_string)
+    })
+    .unwrap()
+}
+
+#[test]
+fn loads_every_object_as_a_mesh() {
+    let model = parse(Some(MTL));
+    let names: Vec<&str> = model.meshes.iter().map(|mesh| mesh.name.as_str()).collect();
+    assert_eq!(names, ["Quad", "Triangle"]);
+
+    // The quad is triangulated into two triangles over its four corners.
+    let quad = &model.meshes[0];
+    assert_eq!(quad.vertices.len(), 4);
+    assert_eq!(quad.indices.len(), 6);
+    assert!(quad.indices.iter().all(|&index| (index as usize) < quad.vertices.len()));
+    assert!(quad.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn flips_texture_coordinates_to_a_top_left_origin() {
+    let quad = &parse(Some(MTL)).meshes[0];
+    let corner = |position: [f32; 3]| quad.vertices.iter().find(|vertex| vertex.position == position).unwrap();
+    assert_eq!(corner([0.0, 0.0, 0.0]).texture_coords, [0.0, 1.0]);
+    assert_eq!(corner([1.0, 1.0, 0.0]).texture_coords, [1.0, 0.0]);
+}
+
+#[test]
+fn computes_normals_when_the_file_has_none() {
+    let triangle = &parse(Some(MTL)).meshes[1];
+    // Counter-clockwise when seen from +x.
+    assert!(triangle.vertices.iter().all(|vertex| vertex.normal == [1.0, 0.0, 0.0]));
+}
+
+#[test]
+fn reads_the_diffuse_part_of_materials() {
+    let model = parse(Some(MTL));
+    let bricks = &model.materials[model.meshes[0].material.unwrap()];
+    assert_eq!(bricks.name, "Bricks");
+    assert_eq!(bricks.diffuse_texture, Some(TextureSource::File(PathBuf::from("textures/bricks.png"))));
+    assert_eq!(bricks.diffuse_color, [1.0, 1.0, 1.0, 1.0]);
+
+    let red = &model.materials[model.meshes[1].material.unwrap()];
+    assert_eq!(red.diffuse_texture, None);
+    assert_eq!(red.diffuse_color, [1.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn tangents_follow_the_texture_coordinates() {
+    let model = parse(Some(MTL));
+    // The quad's u grows along +x and its v (flipped to a top-left origin) along -y.
+    assert!(model.meshes[0].vertices.iter().all(|vertex| vertex.tangent == [1.0, 0.0, 0.0, -1.0]));
+    // Without texture coordinates any tangent will do, as long as it is a unit vector perpendicular to the normal.
+    for vertex in &model.meshes[1].vertices {
+        let [x, y, z, _] = vertex.tangent;
+        assert!(((x * x + y * y + z * z) - 1.0).abs() < 1e-5);
+        assert!(x.abs() < 1e-5);
+    }
+}
+
+#[test]
+fn a_missing_mtl_file_leaves_the_meshes_without_materials() {
+    let model = parse(None);
+    assert_eq!(model.meshes.len(), 2);
+    assert!(model.materials.is_empty());
+    assert!(model.meshes.iter().all(|mesh| mesh.material.is_none()));
+}
+
+#[test]
+fn load_obj_resolves_textures_next_to_the_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../learn_wgpu/beginner/exercise7_instancing/.assets/cube.obj");
+    let model = ModelData::load_obj(&path).unwrap();
+
+    assert_eq!(model.meshes.len(), 1);
+    assert_eq!(model.meshes[0].indices.len(), 36);
+    match &model.materials[0].diffuse_texture {
+        Some(TextureSource::File(path)) => assert!(path.is_file()),
+        other => panic!("expected a texture file, got {:?}", other),
+    }
+}
@@ -0,0 +1,21 @@
+use wgpu_common::RunOptions;
+
+fn parse(args: &[&str]) -> anyhow::Result<RunOptions> {
+    RunOptions::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn unknown_flags_are_rejected() {
+    for args in [&["--frame", "3"][..], &["--backends", "vulkan"], &["point-list", "--hot-reloa"]] {
+        let error = parse(args).unwrap_err().to_string();
+        assert!(error.contains(args.iter().find(|arg| arg.starts_with("--")).unwrap()), "{}", error);
+    }
+}
+
+#[test]
+fn positional_arguments_are_left_for_the_binary() {
+    // Dr Wu's exercises take their mode as a positional argument.
+    let options = parse(&["line-strip", "--headless", "--frames", "3", "--fallback-adapter"]).unwrap();
+    assert_eq!(options.headless.unwrap().frames, 3);
+    assert!(options.adapter.force_fallback_adapter);
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use wgpu_common::{Blend, GpuContext, RenderPipelineBuilder};
+
+const SHADER: &str = r#"
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+"#;
+
+const POSITION: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x3];
+
+fn shader(context: &GpuContext) -> wgpu::ShaderModule {
+    context.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("test.wgsl"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    })
+}
+
+fn positions(attributes: &[wgpu::VertexAttribute]) -> wgpu::VertexBufferLayout<'_> {
+    wgpu::VertexBufferLayout {
+        array_stride: 12,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes,
+    }
+}
+
+/// The error `build` returns, which has to be there.
+fn error(context: &GpuContext, builder: RenderPipelineBuilder<'_>) -> String {
+    match builder.build(&context.device) {
+        Ok(_) => panic!("the pipeline was built"),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[test]
+fn valid_settings_build() {
+    let context = match common::context() {
+        Some(context) => context,
+        None => return,
+    };
+    let shader = shader(&context);
+
+    RenderPipelineBuilder::new(&shader)
+        .vertex_buffer(positions(&POSITION))
+        .color_target(context.view_format())
+        .blend(Blend::Alpha)
+        .depth(wgpu::TextureFormat::Depth32Float)
+        .build(&context.device)
+        .unwrap();
+}
+
+#[test]
+fn reports_target_mistakes() {
+    let context = match common::context() {
+        Some(context) => context,
+        None => return,
+    };
+    let shader = shader(&context);
+
+    let missing = RenderPipelineBuilder::new(&shader).label("Missing").vertex_buffer(positions(&POSITION));
+    assert_eq!(
+        error(&context, missing),
+        "invalid render pipeline 'Missing':\n  - the fragment stage 'fs_main' has no color target to write to, add one \
+         with .color_target(format)"
+    );
+
+    let depth = RenderPipelineBuilder::new(&shader)
+        .vertex_buffer(positions(&POSITION))
+        .color_target(wgpu::TextureFormat::Depth32Float);
+    assert_eq!(
+        error(&context, depth),
+        "invalid render pipeline:\n  - color target 0 is Depth32Float, which is a depth/stencil format, use \
+         .depth(format) for it"
+    );
+}
+
+#[test]
+fn reports_sample_count_mistakes() {
+    let context = match common::context() {
+        Some(context) => context,
+        None => return,
+    };
+    let shader = shader(&context);
+
+    let unsupported = RenderPipelineBuilder::new(&shader)
+        .vertex_buffer(positions(&POSITION))
+        .color_target(context.view_format())
+        .sample_count(3);
+    assert_eq!(
+        error(&context, unsupported),
+        "invalid render pipeline:\n  - sample count 3 has to be 1, 2, 4, 8 or 16"
+    );
+
+    let coverage = RenderPipelineBuilder::new(&shader)
+        .vertex_buffer(positions(&POSITION))
+        .color_target(context.view_format())
+        .alpha_to_coverage(true);
+    assert_eq!(
+        error(&context, coverage),
+        "invalid render pipeline:\n  - alpha to coverage needs a sample count above 1"
+    );
+}
+
+#[test]
+fn reports_a_strip_index_format_on_a_list() {
+    let context = match common::context() {
+        Some(context) => context,
+        None => return,
+    };
+    let shader = shader(&context);
+
+    let list = RenderPipelineBuilder::new(&shader)
+        .vertex_buffer(positions(&POSITION))
+        .color_target(context.view_format())
+        .strip_index_format(Some(wgpu::IndexFormat::Uint16));
+    assert_eq!(
+        error(&context, list),
+        "invalid render pipeline:\n  - strip index format Uint16 set for TriangleList, only strip topologies take one"
+    );
+}
+
+#[test]
+fn reports_every_vertex_layout_mistake_at_once() {
+    let context = match common::context() {
+        Some(context) => context,
+        None => return,
+    };
+    let shader = shader(&context);
+
+    // Location 0 twice in the first buffer, and again in the second. The color starts at byte 8 of a 16 byte stride.
+    let first = [POSITION[0], POSITION[0]];
+    let second = [wgpu::VertexAttribute {
+        format: wgpu::VertexFormat::Float32x4,
+        offset: 8,
+        shader_location: 1,
+    }];
+    let builder = RenderPipelineBuilder::new(&shader)
+        .label("Layouts")
+        .vertex_buffer(positions(&first))
+        .vertex_buffer(wgpu::VertexBufferLayout {
+            array_stride: 16,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &second,
+        })
+        .color_target(context.view_format());
+    assert_eq!(
+        error(&context, builder),
+        "invalid render pipeline 'Layouts':\n  \
+         - @location(0) is used twice in vertex buffer 0\n  \
+         - @location(1) in vertex buffer 1 ends at byte 24, past the 16 byte stride"
+    );
+
+    let across = wgpu::vertex_attr_array![0 => Float32x3];
+    let builder = RenderPipelineBuilder::new(&shader)
+        .vertex_buffer(positions(&POSITION))
+        .vertex_buffer(positions(&across))
+        .color_target(context.view_format());
+    assert_eq!(
+        error(&context, builder),
+        "invalid render pipeline:\n  - @location(0) is used by vertex buffer 0 and vertex buffer 1"
+    );
+}
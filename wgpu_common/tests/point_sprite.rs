@@ -0,0 +1,137 @@
+mod common;
+
+use std::path::Path;
+
+use common::{covered_columns, red, SIZE};
+use wgpu_common::{validate_wgsl, PointShape, PointSprite, PointSpriteRenderer, PointStyle, SizeUnit};
+
+// Dr Wu exercise 4's points.
+const POSITIONS: [[f32; 3]; 6] = [
+    [-0.9, 0.9, 0.0],
+    [-0.5, 0.1, 0.0],
+    [0.0, 0.5, 0.0],
+    [0.3, -0.3, 0.0],
+    [0.7, 0.6, 0.0],
+    [0.9, -0.7, 0.0],
+];
+
+#[test]
+fn every_point_becomes_a_sprite() {
+    let style = PointStyle {
+        size: 6.0,
+        color: [1.0, 0.0, 0.0, 1.0],
+        shape: PointShape::SoftDisc,
+    };
+    let sprites = PointSprite::from_positions(&POSITIONS, &style);
+    assert_eq!(sprites.len(), 6);
+    for (sprite, position) in sprites.iter().zip(POSITIONS) {
+        assert_eq!(*sprite, PointSprite::new(position, &style));
+        assert_eq!((sprite.size, sprite.color, sprite.shape), (6.0, [1.0, 0.0, 0.0, 1.0], PointShape::SoftDisc.id()));
+    }
+
+    let indices: &[u16] = &[5, 0, 5];
+    let indexed = PointSprite::from_indices(&POSITIONS, indices, &style);
+    assert_eq!(indexed, vec![sprites[5], sprites[0], sprites[5]]);
+
+    let mut shape = PointShape::Square;
+    for expected in [PointShape::Circle, PointShape::SoftDisc, PointShape::Square] {
+        shape = shape.next();
+        assert_eq!(shape, expected);
+    }
+}
+
+#[test]
+fn shader_is_valid() {
+    validate_wgsl(Path::new("point_sprite.wgsl"), PointSpriteRenderer::SHADER).unwrap();
+}
+
+/// Draws `sprites` on black with the fallback adapter, `None` where there isn't one.
+fn render(sprites: &[PointSprite], unit: SizeUnit, view_proj: Option<[[f32; 4]; 4]>) -> Option<image::RgbaImage> {
+    common::render(|context, render_pass| {
+        let renderer = PointSpriteRenderer::new(&context.device, context.view_format(), None, None).unwrap();
+        let mut point_sprites = renderer.sprites(&context.device, sprites, unit, (SIZE, SIZE));
+        if let Some(view_proj) = view_proj {
+            point_sprites.set_view_proj(&context.queue, view_proj);
+        }
+        renderer.draw(render_pass, &point_sprites);
+    })
+}
+
+fn sprite(position: [f32; 3], size: f32, shape: PointShape) -> PointSprite {
+    PointSprite::new(position, &PointStyle { size, shape, ..Default::default() })
+}
+
+#[test]
+fn shapes_are_cut_out_of_the_quad() {
+    // 8 pixels around the middle of the target: pixels 28 to 35.
+    let square = match render(&[sprite([0.0; 3], 8.0, PointShape::Square)], SizeUnit::Pixels, None) {
+        Some(image) => image,
+        None => return,
+    };
+    assert_eq!(covered_columns(&square, 28), (28..36).collect::<Vec<_>>());
+    assert_eq!(covered_columns(&square, 35), (28..36).collect::<Vec<_>>());
+    assert_eq!(red(&square, 27, 31), 0);
+    assert_eq!(red(&square, 31, 36), 0);
+
+    let circle = render(&[sprite([0.0; 3], 8.0, PointShape::Circle)], SizeUnit::Pixels, None).unwrap();
+    // The outermost pixels of the middle rows reach just past the rim.
+    assert_eq!(covered_columns(&circle, 31), (29..35).collect::<Vec<_>>());
+    assert!((200..255).contains(&red(&circle, 28, 31)), "{}", red(&circle, 28, 31));
+    for (x, y) in [(28, 28), (35, 28), (28, 35), (35, 35)] {
+        assert_eq!(red(&circle, x, y), 0, "({}, {})", x, y);
+    }
+
+    // Brightest in the middle and fading out towards the rim.
+    let soft = render(&[sprite([0.0; 3], 8.0, PointShape::SoftDisc)], SizeUnit::Pixels, None).unwrap();
+    let fading: Vec<u8> = (32..37).map(|x| red(&soft, x, 31)).collect();
+    assert_eq!(red(&soft, 31, 31), fading[0]);
+    assert!(fading[..4].windows(2).all(|pair| pair[0] > pair[1]), "{:?}", fading);
+    assert!(fading[0] > 200 && fading[3] > 0 && fading[4] == 0, "{:?}", fading);
+}
+
+#[test]
+fn each_sprite_has_its_own_size_and_color() {
+    let sprites = [
+        PointSprite::new([-0.5, 0.0, 0.0], &PointStyle { size: 4.0, color: [1.0, 0.0, 0.0, 1.0], shape: PointShape::Square }),
+        PointSprite::new([0.5, 0.0, 0.0], &PointStyle { size: 12.0, color: [0.0, 1.0, 0.0, 1.0], shape: PointShape::Square }),
+    ];
+    let image = match render(&sprites, SizeUnit::Pixels, None) {
+        Some(image) => image,
+        None => return,
+    };
+    assert_eq!(covered_columns(&image, 31), (14..18).collect::<Vec<_>>());
+    let green: Vec<u32> = (0..SIZE).filter(|&x| image.get_pixel(x, 31)[1] == 255).collect();
+    assert_eq!(green, (42..54).collect::<Vec<_>>());
+    assert_eq!(*image.get_pixel(16, 31), image::Rgba([255, 0, 0, 255]));
+
+    // Less than a pixel wide: still drawn, but fainter than a full pixel.
+    let tiny = render(&[sprite([0.0; 3], 0.5, PointShape::Square)], SizeUnit::Pixels, None).unwrap();
+    assert!((1..255).contains(&red(&tiny, 31, 31)), "{}", red(&tiny, 31, 31));
+}
+
+#[test]
+fn world_sizes_shrink_with_distance() {
+    // 90 degree field of view, square, looking down -z with wgpu's 0..1 depth.
+    let (near, far) = (0.1, 100.0);
+    let view_proj = [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, far / (near - far), -1.0],
+        [0.0, 0.0, near * far / (near - far), 0.0],
+    ];
+
+    // Half a unit two units away covers an eighth of the view, 8 pixels. Twice as far it is 4.
+    let near_sprite = match render(&[sprite([0.0, 0.0, -2.0], 0.5, PointShape::Square)], SizeUnit::World, Some(view_proj)) {
+        Some(image) => image,
+        None => return,
+    };
+    assert_eq!(covered_columns(&near_sprite, 31), (28..36).collect::<Vec<_>>());
+    let far_sprite = render(&[sprite([0.0, 0.0, -4.0], 0.5, PointShape::Square)], SizeUnit::World, Some(view_proj)).unwrap();
+    assert_eq!(covered_columns(&far_sprite, 31), (30..34).collect::<Vec<_>>());
+
+    // In pixels the distance makes no difference, and points behind the camera aren't drawn.
+    let pixels = render(&[sprite([0.0, 0.0, -4.0], 8.0, PointShape::Square)], SizeUnit::Pixels, Some(view_proj)).unwrap();
+    assert_eq!(covered_columns(&pixels, 31), (28..36).collect::<Vec<_>>());
+    let behind = render(&[sprite([0.0, 0.0, 2.0], 8.0, PointShape::Square)], SizeUnit::Pixels, Some(view_proj)).unwrap();
+    assert!(behind.pixels().all(|pixel| pixel[0] == 0));
+}
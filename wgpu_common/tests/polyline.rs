@@ -0,0 +1,174 @@
+mod common;
+
+use std::path::Path;
+
+use common::{covered_columns, red, SIZE};
+use wgpu_common::{validate_wgsl, LineCap, LineJoin, LineRenderer, LineSegment, LineStyle, Topology};
+
+// Dr Wu exercise 4's points.
+const POSITIONS: [[f32; 3]; 6] = [
+    [-0.9, 0.9, 0.0],
+    [-0.5, 0.1, 0.0],
+    [0.0, 0.5, 0.0],
+    [0.3, -0.3, 0.0],
+    [0.7, 0.6, 0.0],
+    [0.9, -0.7, 0.0],
+];
+
+#[test]
+fn lists_cap_every_segment() {
+    let segments = LineSegment::list(&POSITIONS[..5]);
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[1], LineSegment::new(POSITIONS[2], POSITIONS[3]));
+    assert!(segments.iter().all(|segment| !segment.joins_prev() && !segment.joins_next()));
+}
+
+#[test]
+fn strips_join_their_inner_points() {
+    let segments = LineSegment::strip(&POSITIONS);
+    assert_eq!(segments.len(), 5);
+    assert!(!segments[0].joins_prev() && !segments[4].joins_next());
+    for (i, segment) in segments.iter().enumerate() {
+        assert_eq!((segment.start, segment.end), (POSITIONS[i], POSITIONS[i + 1]));
+    }
+    for pair in segments.windows(2) {
+        assert_eq!((pair[0].next, pair[1].prev), (pair[1].end, pair[0].start));
+    }
+
+    // Repeated points would leave a segment without a direction.
+    let repeated = [POSITIONS[0], POSITIONS[0], POSITIONS[1], POSITIONS[1], POSITIONS[2]];
+    assert_eq!(LineSegment::strip(&repeated), LineSegment::strip(&POSITIONS[..3]));
+    assert!(LineSegment::strip(&[POSITIONS[0]; 3]).is_empty());
+
+    // Back where it started: the first and last segment join instead of being capped.
+    let closed = LineSegment::strip(&[POSITIONS[0], POSITIONS[1], POSITIONS[2], POSITIONS[0]]);
+    assert_eq!(closed.len(), 3);
+    assert!(closed.iter().all(|segment| segment.joins_prev() && segment.joins_next()));
+    assert_eq!((closed[0].prev, closed[2].next), (POSITIONS[2], POSITIONS[1]));
+}
+
+#[test]
+fn indexed_strips_split_at_primitive_restarts() {
+    // Dr Wu exercise 4's two strips.
+    let indices: &[u16] = &[0, 1, 2, u16::MAX, 3, 4, 5];
+    let segments = LineSegment::from_indices(&POSITIONS, indices, wgpu::IndexFormat::Uint16, Topology::LineStrip);
+    let mut expected = LineSegment::strip(&POSITIONS[..3]);
+    expected.extend(LineSegment::strip(&POSITIONS[3..]));
+    assert_eq!(segments, expected);
+
+    let list: &[u32] = &[0, 1, 2, 3, 4, 5];
+    let segments = LineSegment::from_indices(&POSITIONS, list, wgpu::IndexFormat::Uint32, Topology::LineList);
+    assert_eq!(segments, LineSegment::list(&POSITIONS));
+
+    assert!(LineSegment::from_topology(&POSITIONS, Topology::PointList).is_empty());
+    assert!(LineSegment::from_topology(&POSITIONS, Topology::TriangleStrip).is_empty());
+}
+
+#[test]
+fn shader_is_valid() {
+    validate_wgsl(Path::new("polyline.wgsl"), LineRenderer::SHADER).unwrap();
+}
+
+/// Draws `segments` on black with the fallback adapter, `None` where there isn't one.
+fn render(segments: &[LineSegment], style: &LineStyle, view_proj: Option<[[f32; 4]; 4]>) -> Option<image::RgbaImage> {
+    common::render(|context, render_pass| {
+        let renderer = LineRenderer::new(&context.device, context.view_format(), None, None).unwrap();
+        let mut lines = renderer.lines(&context.device, segments, style, (SIZE, SIZE));
+        if let Some(view_proj) = view_proj {
+            lines.set_view_proj(&context.queue, view_proj);
+        }
+        renderer.draw(render_pass, &lines);
+    })
+}
+
+/// The pixels in column `x` that are fully covered.
+fn covered_rows(image: &image::RgbaImage, x: u32) -> Vec<u32> {
+    (0..SIZE).filter(|&y| red(image, x, y) == 255).collect()
+}
+
+#[test]
+fn caps_end_the_line_at_or_past_its_points() {
+    // From pixel 16 to 48 along the middle row boundary, 8 pixels wide: rows 28 to 35.
+    let segment = [LineSegment::new([-0.5, 0.0, 0.0], [0.5, 0.0, 0.0])];
+    let style = |cap| LineStyle { cap, ..Default::default() };
+
+    let butt = match render(&segment, &style(LineCap::Butt), None) {
+        Some(image) => image,
+        None => return,
+    };
+    assert_eq!(covered_rows(&butt, 32), (28..36).collect::<Vec<_>>());
+    assert_eq!(covered_columns(&butt, 31), (16..48).collect::<Vec<_>>());
+    assert_eq!(red(&butt, 32, 27), 0);
+
+    // Half a pixel lower, the edges cut through rows 28 and 36 and those are blended half way.
+    let offset = [LineSegment::new([-0.5, -1.0 / SIZE as f32, 0.0], [0.5, -1.0 / SIZE as f32, 0.0])];
+    let image = render(&offset, &style(LineCap::Butt), None).unwrap();
+    assert_eq!(covered_rows(&image, 32), (29..36).collect::<Vec<_>>());
+    for y in [28, 36] {
+        assert!((100..255).contains(&red(&image, 32, y)), "row {} is {}", y, red(&image, 32, y));
+    }
+
+    let square = render(&segment, &style(LineCap::Square), None).unwrap();
+    assert_eq!(covered_columns(&square, 31), (12..52).collect::<Vec<_>>());
+    assert_eq!(red(&square, 12, 28), 255);
+
+    let round = render(&segment, &style(LineCap::Round), None).unwrap();
+    assert_eq!(red(&round, 13, 31), 255);
+    assert_eq!(red(&round, 12, 28), 0);
+}
+
+#[test]
+fn joins_fill_the_outer_corner_differently() {
+    // Right to pixel (40, 32), then straight up. The miter's tip is at (44, 36).
+    let strip = LineSegment::strip(&[[-0.5, 0.0, 0.0], [0.25, 0.0, 0.0], [0.25, 0.75, 0.0]]);
+    let style = |join| LineStyle { join, ..Default::default() };
+
+    let miter = match render(&strip, &style(LineJoin::Miter), None) {
+        Some(image) => image,
+        None => return,
+    };
+    assert_eq!(red(&miter, 43, 35), 255);
+    for join in [LineJoin::Round, LineJoin::Bevel] {
+        assert_eq!(red(&render(&strip, &style(join), None).unwrap(), 43, 35), 0, "{:?}", join);
+    }
+    // A miter limit below the miter's length (sqrt(2) half widths) bevels it.
+    let limited = LineStyle { miter_limit: 1.2, ..style(LineJoin::Miter) };
+    assert_eq!(red(&render(&strip, &limited, None).unwrap(), 43, 35), 0);
+
+    // Half transparent, so pixels covered by both segments would come out brighter than the rest of the line.
+    let faint = LineStyle { color: [1.0, 1.0, 1.0, 0.5], ..style(LineJoin::Round) };
+    let image = render(&strip, &faint, None).unwrap();
+    let body = red(&image, 24, 30);
+    assert!(body > 0);
+    for (x, y) in [(38, 30), (40, 32), (41, 33), (41, 29)] {
+        assert_eq!(red(&image, x, y), body, "({}, {})", x, y);
+    }
+}
+
+#[test]
+fn width_stays_in_pixels_under_a_perspective_camera() {
+    // 90 degree field of view, square, looking down -z with wgpu's 0..1 depth.
+    let (near, far) = (0.1, 100.0);
+    let view_proj = [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, far / (near - far), -1.0],
+        [0.0, 0.0, near * far / (near - far), 0.0],
+    ];
+
+    // Two units away, the same pixels as the 2D line from -0.5 to 0.5.
+    let segment = [LineSegment::new([-1.0, 0.0, -2.0], [1.0, 0.0, -2.0])];
+    let image = match render(&segment, &LineStyle::default(), Some(view_proj)) {
+        Some(image) => image,
+        None => return,
+    };
+    assert_eq!(covered_rows(&image, 32), (28..36).collect::<Vec<_>>());
+    assert_eq!(covered_columns(&image, 31), (16..48).collect::<Vec<_>>());
+
+    // Running away from the camera it is still 8 pixels wide at every depth, and the part behind the camera is cut off.
+    let receding = [LineSegment::new([0.0, -0.5, 5.0], [0.0, -0.5, -50.0])];
+    let image = render(&receding, &LineStyle::default(), Some(view_proj)).unwrap();
+    for y in [34, 40, 50] {
+        assert_eq!(covered_columns(&image, y), (28..36).collect::<Vec<_>>(), "row {}", y);
+    }
+}
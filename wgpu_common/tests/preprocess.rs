@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::Path;
+use wgpu_common::Preprocessor;
+
+const SHADER: &str = r#"#include "camera.wgsl"
+#include "camera.wgsl"
+
+#ifdef TEXTURED
+@group(0) @binding(0)
+var texture_diffuse: texture_2d<f32>;
+#else
+const COLOR: vec4<f32> = vec4<f32>(1.0, 0.5, 0.0, 1.0);
+#endif
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return camera.view_proj * vec4<f32>(position, 1.0);
+}
+"#;
+
+#[test]
+fn resolves_includes_once_and_maps_lines() {
+    let preprocessed = Preprocessor::default().process("shader.wgsl", SHADER).unwrap();
+    preprocessed.validate().unwrap();
+
+    assert_eq!(preprocessed.source.matches("struct CameraUniform").count(), 1);
+    assert!(preprocessed.source.contains("@group(1) @binding(0)"));
+    assert!(preprocessed.source.contains("const COLOR"));
+    assert!(!preprocessed.source.contains("texture_diffuse"));
+
+    let files = preprocessed.files();
+    assert_eq!(files.len(), 2);
+    assert!(files[1].ends_with("shaders/camera.wgsl"));
+
+    // The first line of the output is the comment at the top of camera.wgsl.
+    assert_eq!(preprocessed.origin(1), Some((files[1].as_path(), 1)));
+    let last = preprocessed.source.lines().count() as u32;
+    assert_eq!(preprocessed.origin(last), Some((Path::new("shader.wgsl"), 14)));
+}
+
+#[test]
+fn defines_switch_features_and_replace_values() {
+    let preprocessed = Preprocessor::default()
+        .define("TEXTURED", "")
+        .define("CAMERA_GROUP", "2")
+        .process("shader.wgsl", SHADER)
+        .unwrap();
+    preprocessed.validate().unwrap();
+
+    assert!(preprocessed.source.contains("var texture_diffuse"));
+    assert!(!preprocessed.source.contains("const COLOR"));
+    assert!(preprocessed.source.contains("@group(2) @binding(0)"));
+}
+
+#[test]
+fn wireframe_snippet_reduces_fs_wireframe_to_a_wrapper() {
+    let source = r#"#include "wireframe.wgsl"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) barycentric: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    out.barycentric = barycentric(vertex_index);
+    return out;
+}
+
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, wireframe_coverage(in.barycentric));
+}
+"#;
+    let module = Preprocessor::default().process("shader.wgsl", source).unwrap().validate().unwrap();
+    // PipelineStyle sets NATIVE_LINES, so the snippet has to bring the override along.
+    assert!(module.overrides.iter().any(|(_, constant)| constant.name.as_deref() == Some("NATIVE_LINES")));
+}
+
+#[test]
+fn errors_point_at_the_original_file_and_line() {
+    let source = "#include \"lighting.wgsl\"\n\nfn main_color() -> vec3<f32> {\n    return light();\n}\n";
+    let lighting = "// Lighting\nfn light() -> vec3<f32> {\n    return vec3<f32>(1.0, 1.0);\n}\n";
+    let preprocessor = Preprocessor::empty().embed("lighting.wgsl", lighting);
+
+    let error = preprocessor.process("shader.wgsl", source).unwrap().validate().unwrap_err();
+    assert_eq!(error.path, Path::new("lighting.wgsl"));
+    assert_eq!(error.location.unwrap().line_number, 3);
+    assert!(error.report.contains("--> lighting.wgsl:3:"), "{}", error.report);
+
+    let error = preprocessor.process("shader.wgsl", "#ifdef A\n#include \"missing.wgsl\"\n#endif\n#include \"missing.wgsl\"\n").unwrap_err();
+    assert_eq!(error.to_string(), "shader.wgsl:4:1: unable to find 'missing.wgsl' to include");
+
+    let error = preprocessor.process("shader.wgsl", "\n#ifdef A\n#else\n").unwrap_err();
+    assert_eq!(error.to_string(), "shader.wgsl:2:1: #ifdef without #endif");
+}
+
+#[test]
+fn files_on_disk_win_over_embedded_snippets() {
+    let dir = std::env::temp_dir().join(format!("wgpu_common_preprocess_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("shader.wgsl"), "#include \"camera.wgsl\"\n").unwrap();
+    fs::write(dir.join("camera.wgsl"), "const CAMERA_FROM_DISK: u32 = 1u;\n").unwrap();
+
+    let preprocessed = Preprocessor::default().process_file(dir.join("shader.wgsl")).unwrap();
+    assert_eq!(preprocessed.source, "const CAMERA_FROM_DISK: u32 = 1u;\n");
+    assert_eq!(preprocessed.files(), &[dir.join("shader.wgsl"), dir.join("camera.wgsl")]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
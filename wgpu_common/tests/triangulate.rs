@@ -0,0 +1,242 @@
+use std::f64::consts::TAU;
+
+use wgpu_common::Polygon;
+
+// Property tests over polygons from a small seeded generator, so failures are reproducible without extra crates.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f64 {
+        // xorshift64*
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, start: f64, end: f64) -> f64 {
+        start + (end - start) * self.next()
+    }
+}
+
+/// Points around `center` at increasing angles, `radius` away. Always simple, convex when the radius is constant.
+fn star(random: &mut Random, center: [f64; 2], points: usize, radius: impl Fn(&mut Random) -> f64) -> Vec<[f32; 2]> {
+    let offset = random.range(0.0, TAU);
+    (0..points)
+        .map(|i| {
+            let angle = offset + TAU * (i as f64 + random.range(0.0, 0.5)) / points as f64;
+            let radius = radius(random);
+            [(center[0] + radius * angle.cos()) as f32, (center[1] + radius * angle.sin()) as f32]
+        })
+        .collect()
+}
+
+fn area(points: &[[f32; 2]]) -> f64 {
+    (0..points.len())
+        .map(|i| {
+            let [a, b] = [points[i], points[(i + 1) % points.len()]];
+            a[0] as f64 * b[1] as f64 - b[0] as f64 * a[1] as f64
+        })
+        .sum::<f64>()
+        .abs()
+        * 0.5
+}
+
+fn triangle_area(points: &[[f32; 2]], triangle: &[u32]) -> f64 {
+    let [a, b, c] = [0, 1, 2].map(|i| points[triangle[i] as usize].map(|value| value as f64));
+    ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) * 0.5
+}
+
+fn contains(polygon: &[[f32; 2]], point: [f64; 2]) -> bool {
+    let mut inside = false;
+    for i in 0..polygon.len() {
+        let [a, b] = [polygon[i], polygon[(i + 1) % polygon.len()]].map(|p| p.map(|value| value as f64));
+        if (a[1] > point[1]) != (b[1] > point[1]) && point[0] < a[0] + (point[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Every triangle counter-clockwise and inside the polygon, and together they cover exactly its area.
+fn assert_covers(polygon: &Polygon, indices: &[u32]) {
+    let points = polygon.points();
+    assert_eq!(indices.len() % 3, 0);
+    assert!(indices.iter().all(|&index| (index as usize) < points.len()));
+
+    let mut covered = 0.0;
+    for triangle in indices.chunks_exact(3) {
+        let area = triangle_area(&points, triangle);
+        assert!(area > 0.0, "{:?} is clockwise or empty in {:?}", triangle, polygon);
+        covered += area;
+
+        let centroid = [0, 1].map(|axis| triangle.iter().map(|&i| points[i as usize][axis] as f64).sum::<f64>() / 3.0);
+        assert!(contains(&polygon.outline, centroid), "{:?} is outside {:?}", triangle, polygon);
+        for hole in &polygon.holes {
+            assert!(!contains(hole, centroid), "{:?} is in a hole of {:?}", triangle, polygon);
+        }
+    }
+
+    let expected = area(&polygon.outline) - polygon.holes.iter().map(|hole| area(hole)).sum::<f64>();
+    assert!((covered - expected).abs() < 1e-4 * expected.max(1.0), "covered {} of {} in {:?}", covered, expected, polygon);
+}
+
+#[test]
+fn convex_polygons_become_a_fan_of_n_minus_2_triangles() {
+    let mut random = Random(1);
+    for _ in 0..200 {
+        let points = 3 + (random.next() * 30.0) as usize;
+        let radius = random.range(0.1, 100.0);
+        let center = [random.range(-50.0, 50.0), 0.0];
+        let polygon = Polygon::new(star(&mut random, center, points, |_| radius));
+
+        let indices = polygon.triangulate();
+        assert_eq!(indices.len(), (points - 2) * 3);
+        assert_covers(&polygon, &indices);
+    }
+}
+
+#[test]
+fn concave_polygons_in_either_winding() {
+    let mut random = Random(2);
+    for case in 0..200 {
+        let points = 4 + (random.next() * 40.0) as usize;
+        let mut outline = star(&mut random, [0.0, 0.0], points, |random| random.range(0.2, 1.0));
+        if case % 2 == 1 {
+            outline.reverse();
+        }
+        let polygon = Polygon::new(outline);
+
+        let indices = polygon.triangulate();
+        assert_eq!(indices.len(), (points - 2) * 3);
+        assert_covers(&polygon, &indices);
+        assert_covers(&polygon, &polygon.triangulate_delaunay());
+    }
+}
+
+#[test]
+fn holes_are_left_uncovered() {
+    let mut random = Random(3);
+    for case in 0..200 {
+        let outline = star(&mut random, [0.0, 0.0], 5 + case % 20, |random| random.range(4.0, 5.0));
+        let mut polygon = Polygon::new(outline);
+        // Up to three small holes around the middle that can't reach the outline or each other.
+        for hole in 0..1 + case % 3 {
+            let center = [hole as f64 * 2.0 - 2.0, random.range(-1.0, 1.0)];
+            let mut points = star(&mut random, center, 3 + case % 7, |random| random.range(0.3, 0.9));
+            if case % 2 == 0 {
+                points.reverse();
+            }
+            polygon = polygon.with_hole(points);
+        }
+
+        let points = polygon.points().len();
+        let holes = polygon.holes.len();
+        let indices = polygon.triangulate();
+        // Each bridge adds two points to the ring, so two triangles per hole on top of the n - 2.
+        assert_eq!(indices.len() / 3, points + 2 * holes - 2);
+        assert_covers(&polygon, &indices);
+        assert_covers(&polygon, &polygon.triangulate_delaunay());
+    }
+}
+
+#[test]
+fn degenerate_input() {
+    // Too few points, or no area.
+    assert!(Polygon::new([]).triangulate().is_empty());
+    assert!(Polygon::new([[0.0, 0.0], [1.0, 1.0]]).triangulate().is_empty());
+    assert!(Polygon::new([[0.0, 0.0], [1.0, 1.0], [2.0, 2.0], [3.0, 3.0]]).triangulate().is_empty());
+    assert!(Polygon::new([[1.0, 1.0]; 5]).triangulate().is_empty());
+
+    // Repeated points, a closing copy of the first point and points along the edges still cover the square.
+    let square = Polygon::new([
+        [0.0, 0.0],
+        [0.0, 0.0],
+        [0.5, 0.0],
+        [1.0, 0.0],
+        [1.0, 0.5],
+        [1.0, 0.5],
+        [1.0, 1.0],
+        [0.0, 1.0],
+        [0.0, 0.0],
+    ]);
+    assert_covers(&square, &square.triangulate());
+    assert_covers(&square, &square.triangulate_delaunay());
+
+    // A hole without area, and a hole outside the outline, are ignored.
+    let polygon = Polygon::new([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]])
+        .with_hole([[0.2, 0.2], [0.4, 0.4], [0.6, 0.6]])
+        .with_hole([[2.0, 2.0], [3.0, 2.0], [3.0, 3.0]]);
+    assert_eq!(polygon.triangulate().len(), 6);
+
+    // Random polygons with every point doubled and a few collinear midpoints.
+    let mut random = Random(4);
+    for _ in 0..100 {
+        let points = 6 + (random.next() * 10.0) as usize;
+        let star = star(&mut random, [0.0, 0.0], points, |random| random.range(0.2, 1.0));
+        let mut outline = Vec::new();
+        for i in 0..star.len() {
+            let [a, b] = [star[i], star[(i + 1) % star.len()]];
+            outline.extend([a, a, [(a[0] + b[0]) * 0.5, (a[1] + b[1]) * 0.5]]);
+        }
+        let polygon = Polygon::new(outline);
+        assert_covers(&polygon, &polygon.triangulate());
+        assert_covers(&polygon, &polygon.triangulate_delaunay());
+    }
+}
+
+#[test]
+fn delaunay_leaves_no_point_inside_a_circumcircle_of_a_convex_polygon() {
+    let mut random = Random(5);
+    for _ in 0..50 {
+        // Points on an ellipse: convex, but not all on one circle.
+        let outline: Vec<[f32; 2]> = star(&mut random, [0.0, 0.0], 12, |_| 1.0)
+            .iter()
+            .map(|&[x, y]| [x * 4.0, y])
+            .collect();
+        let polygon = Polygon::new(outline);
+        let points = polygon.points();
+        let indices = polygon.triangulate_delaunay();
+        assert_covers(&polygon, &indices);
+
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| points[triangle[i] as usize].map(|value| value as f64));
+            for (index, d) in points.iter().enumerate() {
+                if triangle.contains(&(index as u32)) {
+                    continue;
+                }
+                let d = d.map(|value| value as f64);
+                let [ax, ay, bx, by, cx, cy] = [a[0] - d[0], a[1] - d[1], b[0] - d[0], b[1] - d[1], c[0] - d[0], c[1] - d[1]];
+                let determinant = (ax * ax + ay * ay) * (bx * cy - cx * by) - (bx * bx + by * by) * (ax * cy - cx * ay)
+                    + (cx * cx + cy * cy) * (ax * by - bx * ay);
+                assert!(determinant < 1e-6, "{:?} is inside the circumcircle of {:?}", index, triangle);
+            }
+        }
+    }
+}
+
+#[test]
+fn texture_coordinates_fill_the_bounding_box_from_the_top_left() {
+    let polygon = Polygon::new([[-1.0, -0.5], [3.0, -0.5], [3.0, 1.5], [1.0, 0.5]]).with_hole([[0.0, 0.0]]);
+    assert_eq!(polygon.texture_coords(), [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.5, 0.5], [0.25, 0.75]]);
+
+    let mesh = polygon.mesh("Polygon");
+    assert_eq!(mesh.vertices.len(), 5);
+    assert!(mesh.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0] && vertex.position[2] == 0.0));
+    assert_eq!(mesh.indices.len(), 6);
+}
+
+#[test]
+fn triangulates_the_index_buffer_pentagon() {
+    // learn_wgpu exercise 4b's pentagon, whose INDICES used to be written out by hand.
+    let pentagon = Polygon::new([
+        [-0.0868241, 0.49240386],
+        [-0.49513406, 0.06958647],
+        [-0.21918549, -0.44939706],
+        [0.35966998, -0.3473291],
+        [0.44147372, 0.2347359],
+    ]);
+    let indices = pentagon.triangulate();
+    assert_eq!(indices.len(), 9);
+    assert_covers(&pentagon, &indices);
+}
@@ -0,0 +1,92 @@
+use std::mem;
+use wgpu_common::VertexLayout;
+
+// The learn_wgpu exercise 7 types, which used to spell their layouts out by hand.
+#[repr(C)]
+#[derive(VertexLayout)]
+#[allow(dead_code)]
+struct Vertex {
+    position: [f32; 3],
+    texture_coords: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(VertexLayout)]
+#[vertex(step_mode = "instance")]
+#[allow(dead_code)]
+struct InstanceRaw {
+    #[vertex(location = 5, matrix)]
+    model: [[f32; 4]; 4],
+}
+
+#[repr(C)]
+#[derive(VertexLayout)]
+#[allow(dead_code)]
+struct Particle {
+    position: [f32; 3],
+    #[vertex(skip)]
+    _padding: u32,
+    #[vertex(format = "Unorm8x4")]
+    color: [u8; 4],
+    size: f32,
+}
+
+#[test]
+fn vertex_matches_the_hand_written_layout() {
+    let expected = wgpu::VertexBufferLayout {
+        array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+            wgpu::VertexAttribute {
+                offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                shader_location: 1,
+                format: wgpu::VertexFormat::Float32x2,
+            },
+        ],
+    };
+
+    assert_eq!(Vertex::desc(), expected);
+}
+
+#[test]
+fn matrix_is_split_into_one_attribute_per_column() {
+    let expected = wgpu::VertexBufferLayout {
+        array_stride: mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &wgpu::vertex_attr_array![5 => Float32x4, 6 => Float32x4, 7 => Float32x4, 8 => Float32x4],
+    };
+
+    assert_eq!(InstanceRaw::desc(), expected);
+}
+
+#[test]
+fn skipped_fields_and_explicit_formats() {
+    let expected = wgpu::VertexBufferLayout {
+        array_stride: 24,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x3,
+            },
+            wgpu::VertexAttribute {
+                offset: 16,
+                shader_location: 1,
+                format: wgpu::VertexFormat::Unorm8x4,
+            },
+            wgpu::VertexAttribute {
+                offset: 20,
+                shader_location: 2,
+                format: wgpu::VertexFormat::Float32,
+            },
+        ],
+    };
+
+    assert_eq!(Particle::desc(), expected);
+}
@@ -0,0 +1,242 @@
+//! `#[derive(VertexLayout)]` for the vertex and instance structs of the exercises. Use it through `wgpu_common`,
+//! which re-exports it next to the `VertexLayout` trait it implements.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, Lit, LitInt, LitStr, Type};
+
+/// Implements `wgpu_common::VertexLayout` for a `#[repr(C)]` struct with named fields.
+///
+/// Every field becomes one attribute, in declaration order, at the next free `@location` starting from 0. The
+/// format comes from the field type (`f32`, `[f32; 3]`, `[u32; 2]`, `[u8; 4]`, ...) and the offset from
+/// `offset_of!`, so padding and reordering are accounted for.
+///
+/// - `#[vertex(step_mode = "instance")]` on the struct makes it per-instance data. The default is `"vertex"`.
+/// - `#[vertex(location = 5)]` on a field moves it, and the fields after it, to that location.
+/// - `#[vertex(matrix)]` splits a `[[f32; 4]; 4]` field into one `Float32x4` attribute per column.
+/// - `#[vertex(format = "Unorm8x4")]` picks the format instead of inferring it, e.g. for normalized bytes. It has to
+///   be the same size as the field (or column).
+/// - `#[vertex(skip)]` leaves a field, e.g. explicit padding, out of the layout.
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(input.generics.span(), "VertexLayout can't be derived for generic structs"));
+    }
+    if !is_repr_c(input)? {
+        return Err(syn::Error::new(
+            name.span(),
+            "VertexLayout needs #[repr(C)], otherwise the compiler is free to reorder the fields",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new(name.span(), "VertexLayout needs a struct with named fields")),
+        },
+        _ => return Err(syn::Error::new(name.span(), "VertexLayout can only be derived for structs")),
+    };
+
+    let step_mode = struct_step_mode(input)?;
+    let mut location = 0u32;
+    let mut attributes = Vec::new();
+    let mut size_checks = Vec::new();
+
+    for field in fields {
+        let options = FieldOptions::parse(field)?;
+        if options.skip {
+            continue;
+        }
+        if let Some(explicit) = options.location {
+            location = explicit;
+        }
+
+        let field_name = field.ident.as_ref().unwrap();
+        let (columns, column_type) = if options.matrix {
+            match &field.ty {
+                Type::Array(array) => (array_len(&array.len)?, (*array.elem).clone()),
+                other => return Err(syn::Error::new(other.span(), "#[vertex(matrix)] needs an array of arrays, e.g. [[f32; 4]; 4]")),
+            }
+        } else {
+            (1, field.ty.clone())
+        };
+
+        let format = match &options.format {
+            Some(format) => format.clone(),
+            None => infer_format(&column_type)?,
+        };
+
+        for column in 0..columns {
+            let offset = quote! {
+                (::core::mem::offset_of!(#name, #field_name) + #column * ::core::mem::size_of::<#column_type>())
+                    as ::wgpu_common::wgpu::BufferAddress
+            };
+            attributes.push(quote! {
+                ::wgpu_common::wgpu::VertexAttribute {
+                    format: ::wgpu_common::wgpu::VertexFormat::#format,
+                    offset: #offset,
+                    shader_location: #location,
+                }
+            });
+            location += 1;
+        }
+
+        let message = format!(
+            "#[derive(VertexLayout)]: {} is not the same size as `{}`",
+            format,
+            column_type.to_token_stream()
+        );
+        size_checks.push(quote! {
+            assert!(
+                ::wgpu_common::wgpu::VertexFormat::#format.size() == ::core::mem::size_of::<#column_type>() as u64,
+                #message
+            );
+        });
+    }
+
+    Ok(quote! {
+        impl ::wgpu_common::VertexLayout for #name {
+            const ATTRIBUTES: &'static [::wgpu_common::wgpu::VertexAttribute] = &[#(#attributes),*];
+            const STEP_MODE: ::wgpu_common::wgpu::VertexStepMode = ::wgpu_common::wgpu::VertexStepMode::#step_mode;
+        }
+
+        const _: () = {
+            #(#size_checks)*
+        };
+    })
+}
+
+fn is_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            // Skip over arguments such as align(16).
+            if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(repr_c)
+}
+
+fn struct_step_mode(input: &DeriveInput) -> syn::Result<Ident> {
+    let mut step_mode = format_ident!("Vertex");
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("vertex")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("step_mode") {
+                let value: LitStr = meta.value()?.parse()?;
+                step_mode = match value.value().as_str() {
+                    "vertex" => format_ident!("Vertex"),
+                    "instance" => format_ident!("Instance"),
+                    _ => return Err(syn::Error::new(value.span(), "expected step_mode = \"vertex\" or \"instance\"")),
+                };
+                Ok(())
+            } else {
+                Err(meta.error("unknown struct attribute, expected #[vertex(step_mode = \"...\")]"))
+            }
+        })?;
+    }
+    Ok(step_mode)
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    location: Option<u32>,
+    format: Option<Ident>,
+    matrix: bool,
+    skip: bool,
+}
+
+impl FieldOptions {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut options = FieldOptions::default();
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("vertex")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    let value: LitInt = meta.value()?.parse()?;
+                    options.location = Some(value.base10_parse()?);
+                } else if meta.path.is_ident("format") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    options.format = Some(value.parse()?);
+                } else if meta.path.is_ident("matrix") {
+                    options.matrix = true;
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else {
+                    return Err(meta.error("unknown field attribute, expected location, format, matrix or skip"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// The vertex format for a scalar or a 2-4 element array of one.
+fn infer_format(ty: &Type) -> syn::Result<Ident> {
+    let (scalar, len) = match ty {
+        Type::Path(path) => (path.path.get_ident().map(Ident::to_string), 1),
+        Type::Array(array) => match &*array.elem {
+            Type::Path(path) => (path.path.get_ident().map(Ident::to_string), array_len(&array.len)?),
+            Type::Array(_) => {
+                return Err(syn::Error::new(ty.span(), "nested arrays need #[vertex(matrix)] to become one attribute per column"))
+            },
+            _ => (None, 0),
+        },
+        _ => (None, 0),
+    };
+
+    let base = match scalar.as_deref() {
+        Some("f32") => "Float32",
+        Some("f64") => "Float64",
+        Some("u32") => "Uint32",
+        Some("i32") => "Sint32",
+        Some("u16") => "Uint16",
+        Some("i16") => "Sint16",
+        Some("u8") => "Uint8",
+        Some("i8") => "Sint8",
+        _ => return Err(no_format(ty)),
+    };
+    // 8 and 16 bit formats only come in 1, 2 and 4 components.
+    let small = matches!(base, "Uint16" | "Sint16" | "Uint8" | "Sint8");
+
+    match len {
+        1 => Ok(Ident::new(base, Span::call_site())),
+        2 | 4 => Ok(format_ident!("{}x{}", base, len)),
+        3 if !small => Ok(format_ident!("{}x{}", base, len)),
+        _ => Err(no_format(ty)),
+    }
+}
+
+fn no_format(ty: &Type) -> syn::Error {
+    syn::Error::new(
+        ty.span(),
+        format!(
+            "no vertex format for `{}`, add #[vertex(format = \"...\")] or #[vertex(skip)]",
+            ty.to_token_stream()
+        ),
+    )
+}
+
+fn array_len(len: &Expr) -> syn::Result<usize> {
+    match len {
+        Expr::Lit(expr) => match &expr.lit {
+            Lit::Int(int) => int.base10_parse(),
+            _ => Err(syn::Error::new(len.span(), "expected an array length")),
+        },
+        _ => Err(syn::Error::new(len.span(), "VertexLayout needs array lengths written as numbers")),
+    }
+}
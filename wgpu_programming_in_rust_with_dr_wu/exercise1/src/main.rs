@@ -1,13 +1,63 @@
+mod report;
+mod table;
+
+use std::env;
+use std::fs;
+
 pub fn main() {
-    list_gpus();
+    env_logger::init();
+
+    if let Err(e) = run() {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
 }
 
-fn list_gpus() {
-    let instance = wgpu::Instance::default();
-    
-    println!("Found Adapters:");
+fn run() -> anyhow::Result<()> {
+    let mut json = false;
+    let mut out = None;
+    let mut with_window = true;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--out" => out = Some(args.next().ok_or_else(|| anyhow::anyhow!("--out is missing its file name"))?),
+            "--no-window" => with_window = false,
+            "help" | "--help" => {
+                print_help();
+                return Ok(());
+            },
+            _ => {
+                print_help();
+                anyhow::bail!("Unknown argument: '{}'", arg);
+            }
+        }
+    }
+
+    let report = report::inspect(with_window);
 
-    for adapter in instance.enumerate_adapters(wgpu::Backends::all()) {
-        println!("  {:?}", adapter.get_info());
+    if let Some(out) = &out {
+        fs::write(out, serde_json::to_string_pretty(&report)?)?;
+        println!("Wrote {}", out);
     }
-}
\ No newline at end of file
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if out.is_none() {
+        table::print_report(&report);
+    }
+
+    Ok(())
+}
+
+fn print_help() {
+    println!("Usage: cargo run -- [options]");
+    println!("Lists every adapter wgpu can find, per backend, with its features, limits, downlevel capabilities,");
+    println!("texture format capabilities and (when a window can be opened) surface capabilities.");
+    println!("Options:");
+    println!("  --json         - Print the report as JSON instead of tables");
+    println!("  --out <file>   - Write the JSON report to a file, e.g. to attach it to a bug report");
+    println!("  --no-window    - Don't open a window, which skips the surface capabilities");
+    println!("  help           - Show this help message");
+}
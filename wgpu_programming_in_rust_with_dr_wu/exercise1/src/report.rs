@@ -0,0 +1,234 @@
+use std::sync::Arc;
+use serde::Serialize;
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::window::{Window, WindowId};
+
+/// Everything we know about the adapters on this machine. Serializes to the JSON written by `--json`.
+#[derive(Serialize)]
+pub struct Report {
+    pub adapters: Vec<AdapterReport>,
+    /// Why surface capabilities are missing, e.g. when there is no display to open a window on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AdapterReport {
+    pub info: wgpu::AdapterInfo,
+    pub features: Vec<String>,
+    pub limits: wgpu::Limits,
+    pub downlevel: DownlevelReport,
+    pub texture_formats: Vec<TextureFormatReport>,
+    /// `None` when no window was available to create a surface with.
+    pub surface: Option<SurfaceReport>,
+}
+
+#[derive(Serialize)]
+pub struct DownlevelReport {
+    pub is_webgpu_compliant: bool,
+    pub shader_model: String,
+    pub flags: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TextureFormatReport {
+    pub format: String,
+    pub allowed_usages: Vec<String>,
+    pub flags: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct SurfaceReport {
+    /// False when the adapter can't present to the window at all. The lists below are empty in that case.
+    pub supported: bool,
+    pub formats: Vec<String>,
+    pub present_modes: Vec<String>,
+    pub alpha_modes: Vec<String>,
+    pub usages: Vec<String>,
+}
+
+// The formats we ask every adapter about. Every uncompressed format plus one of each compressed family, which is
+// enough to tell whether the family is supported at all.
+const TEXTURE_FORMATS: &[wgpu::TextureFormat] = &[
+    wgpu::TextureFormat::R8Unorm,
+    wgpu::TextureFormat::R8Snorm,
+    wgpu::TextureFormat::R8Uint,
+    wgpu::TextureFormat::R8Sint,
+    wgpu::TextureFormat::R16Uint,
+    wgpu::TextureFormat::R16Sint,
+    wgpu::TextureFormat::R16Unorm,
+    wgpu::TextureFormat::R16Snorm,
+    wgpu::TextureFormat::R16Float,
+    wgpu::TextureFormat::Rg8Unorm,
+    wgpu::TextureFormat::Rg8Snorm,
+    wgpu::TextureFormat::Rg8Uint,
+    wgpu::TextureFormat::Rg8Sint,
+    wgpu::TextureFormat::R32Uint,
+    wgpu::TextureFormat::R32Sint,
+    wgpu::TextureFormat::R32Float,
+    wgpu::TextureFormat::Rg16Uint,
+    wgpu::TextureFormat::Rg16Sint,
+    wgpu::TextureFormat::Rg16Unorm,
+    wgpu::TextureFormat::Rg16Snorm,
+    wgpu::TextureFormat::Rg16Float,
+    wgpu::TextureFormat::Rgba8Unorm,
+    wgpu::TextureFormat::Rgba8UnormSrgb,
+    wgpu::TextureFormat::Rgba8Snorm,
+    wgpu::TextureFormat::Rgba8Uint,
+    wgpu::TextureFormat::Rgba8Sint,
+    wgpu::TextureFormat::Bgra8Unorm,
+    wgpu::TextureFormat::Bgra8UnormSrgb,
+    wgpu::TextureFormat::Rgb9e5Ufloat,
+    wgpu::TextureFormat::Rgb10a2Uint,
+    wgpu::TextureFormat::Rgb10a2Unorm,
+    wgpu::TextureFormat::Rg11b10Ufloat,
+    wgpu::TextureFormat::R64Uint,
+    wgpu::TextureFormat::Rg32Uint,
+    wgpu::TextureFormat::Rg32Sint,
+    wgpu::TextureFormat::Rg32Float,
+    wgpu::TextureFormat::Rgba16Uint,
+    wgpu::TextureFormat::Rgba16Sint,
+    wgpu::TextureFormat::Rgba16Unorm,
+    wgpu::TextureFormat::Rgba16Snorm,
+    wgpu::TextureFormat::Rgba16Float,
+    wgpu::TextureFormat::Rgba32Uint,
+    wgpu::TextureFormat::Rgba32Sint,
+    wgpu::TextureFormat::Rgba32Float,
+    wgpu::TextureFormat::Stencil8,
+    wgpu::TextureFormat::Depth16Unorm,
+    wgpu::TextureFormat::Depth24Plus,
+    wgpu::TextureFormat::Depth24PlusStencil8,
+    wgpu::TextureFormat::Depth32Float,
+    wgpu::TextureFormat::Depth32FloatStencil8,
+    wgpu::TextureFormat::NV12,
+    wgpu::TextureFormat::Bc1RgbaUnorm,
+    wgpu::TextureFormat::Bc4RUnorm,
+    wgpu::TextureFormat::Bc5RgUnorm,
+    wgpu::TextureFormat::Bc6hRgbUfloat,
+    wgpu::TextureFormat::Bc7RgbaUnorm,
+    wgpu::TextureFormat::Etc2Rgb8Unorm,
+    wgpu::TextureFormat::EacR11Unorm,
+    wgpu::TextureFormat::Astc {
+        block: wgpu::AstcBlock::B4x4,
+        channel: wgpu::AstcChannel::Unorm,
+    },
+];
+
+/// Inspects every adapter of every backend. With `with_window` a hidden window is opened so surface capabilities
+/// can be reported too. Failing to open one (e.g. no display) is not an error, the report just says why.
+pub fn inspect(with_window: bool) -> Report {
+    let instance = wgpu::Instance::default();
+    let adapters = instance.enumerate_adapters(wgpu::Backends::all());
+
+    let (surfaces, surface_error) = if with_window {
+        match probe_surfaces(&instance, &adapters) {
+            Ok(surfaces) => (surfaces, None),
+            Err(e) => {
+                log::warn!("Skipping surface capabilities: {:#}", e);
+                (Vec::new(), Some(format!("{:#}", e)))
+            }
+        }
+    } else {
+        (Vec::new(), None)
+    };
+
+    let mut surfaces = surfaces.into_iter();
+    let adapters = adapters
+        .iter()
+        .map(|adapter| inspect_adapter(adapter, surfaces.next()))
+        .collect();
+
+    Report {
+        adapters,
+        surface_error,
+    }
+}
+
+fn inspect_adapter(adapter: &wgpu::Adapter, surface: Option<SurfaceReport>) -> AdapterReport {
+    let downlevel = adapter.get_downlevel_capabilities();
+
+    let texture_formats = TEXTURE_FORMATS
+        .iter()
+        .map(|format| {
+            let features = adapter.get_texture_format_features(*format);
+            TextureFormatReport {
+                format: format!("{:?}", format),
+                allowed_usages: features.allowed_usages.iter_names().map(|(name, _)| name.to_string()).collect(),
+                flags: features.flags.iter_names().map(|(name, _)| name.to_string()).collect(),
+            }
+        })
+        .collect();
+
+    AdapterReport {
+        info: adapter.get_info(),
+        features: adapter.features().iter_names().map(|(name, _)| name.to_string()).collect(),
+        limits: adapter.limits(),
+        downlevel: DownlevelReport {
+            is_webgpu_compliant: downlevel.is_webgpu_compliant(),
+            shader_model: format!("{:?}", downlevel.shader_model),
+            flags: downlevel.flags.iter_names().map(|(name, _)| name.to_string()).collect(),
+        },
+        texture_formats,
+        surface,
+    }
+}
+
+fn surface_report(surface: &wgpu::Surface, adapter: &wgpu::Adapter) -> SurfaceReport {
+    let capabilities = surface.get_capabilities(adapter);
+
+    SurfaceReport {
+        supported: adapter.is_surface_supported(surface),
+        formats: capabilities.formats.iter().map(|format| format!("{:?}", format)).collect(),
+        present_modes: capabilities.present_modes.iter().map(|mode| format!("{:?}", mode)).collect(),
+        alpha_modes: capabilities.alpha_modes.iter().map(|mode| format!("{:?}", mode)).collect(),
+        usages: capabilities.usages.iter_names().map(|(name, _)| name.to_string()).collect(),
+    }
+}
+
+// Surfaces need a live window, and the window only exists while the event loop is running, so the capabilities are
+// collected from inside `resumed` and the loop exits right after.
+fn probe_surfaces(instance: &wgpu::Instance, adapters: &[wgpu::Adapter]) -> anyhow::Result<Vec<SurfaceReport>> {
+    let event_loop = EventLoop::new()?;
+
+    let mut probe = SurfaceProbe {
+        instance,
+        adapters,
+        result: None,
+    };
+    event_loop.run_app(&mut probe)?;
+
+    probe
+        .result
+        .unwrap_or_else(|| Err(anyhow::anyhow!("the event loop exited before a window was created")))
+}
+
+struct SurfaceProbe<'a> {
+    instance: &'a wgpu::Instance,
+    adapters: &'a [wgpu::Adapter],
+    result: Option<anyhow::Result<Vec<SurfaceReport>>>,
+}
+
+impl SurfaceProbe<'_> {
+    fn probe(&self, event_loop: &ActiveEventLoop) -> anyhow::Result<Vec<SurfaceReport>> {
+        let window_attributes = Window::default_attributes()
+            .with_title("wgpu inspector")
+            .with_visible(false);
+        let window = Arc::new(event_loop.create_window(window_attributes)?);
+        let surface = self.instance.create_surface(window)?;
+
+        Ok(self.adapters.iter().map(|adapter| surface_report(&surface, adapter)).collect())
+    }
+}
+
+impl ApplicationHandler for SurfaceProbe<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.result.is_none() {
+            self.result = Some(self.probe(event_loop));
+        }
+        event_loop.exit();
+    }
+
+    fn window_event(&mut self, _event_loop: &ActiveEventLoop, _id: WindowId, _event: WindowEvent) {}
+}
@@ -0,0 +1,116 @@
+use crate::report::{AdapterReport, Report, TextureFormatReport};
+
+/// Prints the report as human readable tables, grouped by backend.
+pub fn print_report(report: &Report) {
+    if report.adapters.is_empty() {
+        println!("No adapters found.");
+    }
+
+    let mut backends: Vec<wgpu::Backend> = Vec::new();
+    for adapter in &report.adapters {
+        if !backends.contains(&adapter.info.backend) {
+            backends.push(adapter.info.backend);
+        }
+    }
+
+    for backend in backends {
+        println!("==================== {:?} ====================", backend);
+
+        for (index, adapter) in report.adapters.iter().enumerate().filter(|(_, adapter)| adapter.info.backend == backend) {
+            print_adapter(index, adapter);
+        }
+    }
+
+    if let Some(error) = &report.surface_error {
+        println!("Surface capabilities unavailable: {}", error);
+    }
+}
+
+fn print_adapter(index: usize, adapter: &AdapterReport) {
+    let info = &adapter.info;
+
+    println!();
+    println!("Adapter {}: {} ({:?})", index, info.name, info.device_type);
+    print_row("Vendor", &format!("{:#06x}", info.vendor));
+    print_row("Device", &format!("{:#06x}", info.device));
+    print_row("Driver", format!("{} {}", info.driver, info.driver_info).trim());
+
+    println!();
+    println!("  Features ({})", adapter.features.len());
+    for feature in &adapter.features {
+        println!("    {}", feature);
+    }
+
+    println!();
+    println!("  Limits");
+    // Going through serde gives us every limit with its field name, without listing them by hand.
+    if let Ok(serde_json::Value::Object(limits)) = serde_json::to_value(&adapter.limits) {
+        for (name, value) in limits {
+            println!("    {:<48} {}", name, value);
+        }
+    }
+
+    println!();
+    println!("  Downlevel");
+    print_row("WebGPU", if adapter.downlevel.is_webgpu_compliant { "compliant" } else { "not compliant" });
+    print_row("Shader model", &adapter.downlevel.shader_model);
+    for flag in &adapter.downlevel.flags {
+        println!("    {}", flag);
+    }
+
+    println!();
+    println!("  Texture formats");
+    println!("    {:<38} {:<8} {:<8} {:<8} {:<8} {:<8} MSAA", "Format", "Render", "Sample", "Storage", "Filter", "Blend");
+    for format in &adapter.texture_formats {
+        print_texture_format(format);
+    }
+
+    println!();
+    println!("  Surface");
+    match &adapter.surface {
+        Some(surface) if surface.supported => {
+            print_row("Formats", &surface.formats.join(", "));
+            print_row("Present modes", &surface.present_modes.join(", "));
+            print_row("Alpha modes", &surface.alpha_modes.join(", "));
+            print_row("Usages", &surface.usages.join(", "));
+        },
+        Some(_) => println!("    Can't present to the window"),
+        None => println!("    No window available"),
+    }
+
+    println!();
+}
+
+fn print_row(name: &str, value: &str) {
+    println!("  {:<16} {}", name, value);
+}
+
+fn print_texture_format(format: &TextureFormatReport) {
+    let has_usage = |usage: &str| yes_no(format.allowed_usages.iter().any(|allowed| allowed == usage));
+    let has_flag = |flag: &str| yes_no(format.flags.iter().any(|supported| supported == flag));
+
+    let msaa: Vec<&str> = [("MULTISAMPLE_X2", "x2"), ("MULTISAMPLE_X4", "x4"), ("MULTISAMPLE_X8", "x8"), ("MULTISAMPLE_X16", "x16")]
+        .iter()
+        .filter(|(flag, _)| format.flags.iter().any(|supported| supported == flag))
+        .map(|(_, label)| *label)
+        .collect();
+
+    println!(
+        "    {:<38} {:<8} {:<8} {:<8} {:<8} {:<8} {}",
+        format.format,
+        has_usage("RENDER_ATTACHMENT"),
+        has_usage("TEXTURE_BINDING"),
+        has_usage("STORAGE_BINDING"),
+        has_flag("FILTERABLE"),
+        has_flag("BLENDABLE"),
+        if msaa.is_empty() { "-".to_string() } else { msaa.join(" ") },
+    );
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "-"
+    }
+}
@@ -1,13 +1,8 @@
-mod app;
-use crate::app::App;
-use winit::event_loop::{EventLoop,ControlFlow};
-
 pub fn main() {
     env_logger::init();
 
-    let event_loop = EventLoop::new().unwrap();
-    event_loop.set_control_flow(ControlFlow::Poll);
-
-    let mut app = App::default();
-    event_loop.run_app(&mut app).unwrap();
-}
\ No newline at end of file
+    let mut registry = wgpu_common::Registry::default();
+    web_gpu_exercise2::register(&mut registry);
+    let options = wgpu_common::RunOptions::from_env().unwrap();
+    wgpu_common::run(registry, None, options).unwrap();
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use winit::window::Window;
+use wgpu_common::{Exercise, Target};
+
+pub struct State {
+    // There is nothing to render yet, so headless runs don't have a window at all.
+    window: Option<Arc<Window>>,
+}
+
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        Ok(State {
+            window: target.window().cloned(),
+        })
+    }
+
+    fn resize(&mut self, _width: u32, _height: u32) {}
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+        Ok(())
+    }
+}
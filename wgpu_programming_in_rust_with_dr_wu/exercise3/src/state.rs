@@ -1,83 +1,62 @@
-use std::sync::Arc;
-use winit::window::Window;
+use wgpu_common::{Exercise, GpuContext, PipelineKey, RenderPipelineBuilder, RenderPipelineCache, ShaderLayouts, Target};
 
 pub struct State {
-    window: Arc<Window>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    size: winit::dpi::PhysicalSize<u32>,
-    surface: wgpu::Surface<'static>,
-    surface_format: wgpu::TextureFormat,
+    context: GpuContext,
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: RenderPipelineCache,
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>) -> State {
-        let instance_descriptor = wgpu::InstanceDescriptor::default();
-        let instance = wgpu::Instance::new(&instance_descriptor);
-        let adapter_options = wgpu::RequestAdapterOptions::default();
-        let adapter = instance.request_adapter(&adapter_options).await.unwrap();
-        let device_descriptor = wgpu::DeviceDescriptor::default();
-        let (device, queue) = adapter.request_device(&device_descriptor).await.unwrap();
-        let size = window.inner_size();
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let capabilities = surface.get_capabilities(&adapter);
-        let surface_format = capabilities.formats[0];
-
-        let state = State {
-            window,
-            device,
-            queue,
-            size,
-            surface,
-            surface_format
-        };
-
-        // Configure surface for the first time
-        state.configure_surface();
+    pub async fn new(target: Target) -> anyhow::Result<State> {
+        let context = GpuContext::builder()
+            .srgb_view_format(true)     // Request compatibility with the sRGB-format texture view we're going to create later.
+            .present_mode(wgpu::PresentMode::AutoVsync)
+            .alpha_mode(wgpu::CompositeAlphaMode::Auto)
+            .build(target)
+            .await?;
+
+        let shader = context.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        let pipeline_layout = context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
 
-        state
-    }
+        // bench builds the pipelines again every frame to show what the cache saves.
+        let pipelines = RenderPipelineCache::new().rebuild_always(context.rebuilds_pipelines());
 
-    pub fn get_window(&self) -> &Window {
-        &self.window
+        Ok(State {
+            context,
+            shader,
+            pipeline_layout,
+            pipelines,
+        })
     }
+}
 
-    pub fn configure_surface(&self) {
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: self.surface_format,
-            // Request compatibility with the sRGB-format texture view we're going to create later.
-            view_formats: vec![ self.surface_format.add_srgb_suffix() ],
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            width: self.size.width,
-            height: self.size.height,
-            desired_maximum_frame_latency: 2,
-            present_mode: wgpu::PresentMode::AutoVsync,
-        };
-
-        self.surface.configure(&self.device, &surface_config);
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target))
     }
 
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.size = new_size;
-
-        // reconfigure the surface
-        self.configure_surface();
+    fn resize(&mut self, width: u32, height: u32) {
+        self.context.resize(width, height);
     }
 
-    pub fn render(&mut self) {
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         // Create texture view
-        let surface_texture = self.surface.get_current_texture().expect("failed to acquire next swapchain texture");
+        let frame = self.context.acquire_frame()?;
 
         let texture_view_descriptor = wgpu::TextureViewDescriptor {
             // Without add_srgb_suffix() the image we will be working with might not be "gamma correct".
-            format: Some(self.surface_format.add_srgb_suffix()),
+            format: Some(self.context.view_format()),
             ..Default::default()
         };
 
-        let texture_view = surface_texture.texture.create_view(&texture_view_descriptor);
+        let texture_view = frame.texture().create_view(&texture_view_descriptor);
 
-        let mut encoder = self.device.create_command_encoder(&Default::default());
+        let mut encoder = self.context.device.create_command_encoder(&Default::default());
         
         // Create the renderpass which will clear the screen
         let color_attachment_operations = wgpu::Operations {
@@ -102,44 +81,16 @@ impl State {
         let mut renderpass = encoder.begin_render_pass(&renderpass_descriptor);
 
         // If you wanted to call any drawing commands, they would go here.
-        let shader = self.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
-
-        let pipeline_layout = self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
-
-        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState{ 
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: self.surface_format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent::REPLACE,
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        // The pipeline is only built the first time this topology/format combination is drawn.
+        let key = PipelineKey {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            format: self.context.config.format,
+        };
+        let (device, cache, pipeline_layout, shader) =
+            (&self.context.device, self.context.pipeline_cache(), &self.pipeline_layout, &self.shader);
+        let pipeline = self.pipelines.get_or_create(key, |key| create_pipeline(device, cache, pipeline_layout, shader, key));
 
-        renderpass.set_pipeline(&pipeline);
+        renderpass.set_pipeline(pipeline);
         renderpass.draw(0..3, 0..1);
 
 
@@ -147,8 +98,37 @@ impl State {
         drop(renderpass);
 
         // Submit the command in the queue to execute
-        self.queue.submit([encoder.finish()]);
-        self.window.pre_present_notify();
-        surface_texture.present();
+        self.context.queue.submit([encoder.finish()]);
+        self.context.pre_present_notify();
+        frame.present();
+        Ok(())
+    }
+
+    fn context(&self) -> Option<&GpuContext> {
+        Some(&self.context)
     }
-}
\ No newline at end of file
+}
+
+/// The vertex buffers the pipeline feeds `vs_main`, checked against shader.wgsl by `check-layouts`.
+pub const SHADER_LAYOUTS: ShaderLayouts = ShaderLayouts {
+    path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+    source: include_str!("shader.wgsl"),
+    entry_point: "vs_main",
+    buffers: Vec::new,
+};
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    cache: Option<&wgpu::PipelineCache>,
+    pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    key: &PipelineKey,
+) -> wgpu::RenderPipeline {
+    RenderPipelineBuilder::new(shader)
+        .layout(pipeline_layout)
+        .color_target(key.format)
+        .topology(key.topology)
+        .cache(cache)
+        .build(device)
+        .expect("unable to create the render pipeline")
+}
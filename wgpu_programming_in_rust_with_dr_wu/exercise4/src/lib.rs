@@ -0,0 +1,30 @@
+mod state;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::{Exercise, Registry, Target, Topology};
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register_fn("point-list", "Dr Wu - Point List Primitive", |target| init_mode(target, Topology::PointList, false, false))
+        .with_shader_layouts(SHADER_LAYOUTS);
+    registry
+        .register_fn("line-list", "Dr Wu - Line List Primitive", |target| init_mode(target, Topology::LineList, false, false))
+        .with_shader_layouts(SHADER_LAYOUTS);
+    registry
+        .register_fn("line-strip", "Dr Wu - Line Strip Primitive", |target| init_mode(target, Topology::LineStrip, false, false))
+        .with_shader_layouts(SHADER_LAYOUTS);
+    registry
+        .register_fn("thick-line-list", "Dr Wu - Thick Line List", |target| init_mode(target, Topology::LineList, true, false))
+        .with_shader_layouts(SHADER_LAYOUTS);
+    registry
+        .register_fn("thick-line-strip", "Dr Wu - Thick Line Strip", |target| init_mode(target, Topology::LineStrip, true, false))
+        .with_shader_layouts(SHADER_LAYOUTS);
+    registry
+        .register_fn("point-sprites", "Dr Wu - Point Sprites", |target| init_mode(target, Topology::PointList, false, true))
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
+
+fn init_mode(target: Target, topology: Topology, thick_lines: bool, point_sprites: bool) -> anyhow::Result<Box<dyn Exercise>> {
+    Ok(Box::new(pollster::block_on(State::new(target, topology, thick_lines, point_sprites))?))
+}
@@ -3,28 +3,11 @@ struct VOutput {
     @location(0) v_color: vec4<f32>,
 };
 
+// The six points come from VERTICES in state.rs, which the thick lines are built from too.
 @vertex
-fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VOutput {
-    var pos = array<vec2<f32>, 6>(
-        vec2<f32>(-0.9,  0.9),
-        vec2<f32>(-0.5,  0.1),
-        vec2<f32>( 0.0,  0.5),
-        vec2<f32>( 0.3, -0.3),
-        vec2<f32>( 0.7,  0.6),
-        vec2<f32>( 0.9, -0.7),
-    );
-
-    var color = array<vec3<f32>, 6>(
-        vec3<f32>(1.0, 0.0, 0.0),
-        vec3<f32>(0.0, 1.0, 0.0),
-        vec3<f32>(0.0, 0.0, 1.0),
-        vec3<f32>(1.0, 1.0, 0.0),
-        vec3<f32>(1.0, 0.0, 1.0),
-        vec3<f32>(0.0, 1.0, 1.0),
-    );
-
+fn vs_main(@location(0) position: vec3<f32>) -> VOutput {
     var out: VOutput;
-    out.position = vec4<f32>(pos[in_vertex_index], 0.0, 1.0);
+    out.position = vec4<f32>(position, 1.0);
     out.v_color = vec4<f32>(1.0, 1.0, 0.0, 1.0); // yellow
     return out;
 }
@@ -1,87 +1,310 @@
-use std::sync::Arc;
-use winit::window::Window;
+use std::ops::Range;
 
-use crate::user_input::UserSelection;
+use wgpu::util::DeviceExt;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use wgpu_common::{
+    strip_ranges, Exercise, GpuContext, LineRenderer, LineSegment, LineStyle, Lines, PipelineKey, PointShape, PointSprite,
+    PointSpriteRenderer, PointSprites, PointStyle, RenderPipelineBuilder, RenderPipelineCache, ShaderLayouts, SizeUnit,
+    Target, Topology, TopologyControls, VertexLayout,
+};
+
+const TITLE: &str = "Dr Wu - Point/Line Primitives";
+const INDEX_FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint16;
+// With a strip_index_format set, the largest index value ends the current strip instead of naming a vertex.
+const RESTART: u16 = u16::MAX;
+// Two strips of three vertices each, split by the primitive restart value.
+const STRIP_INDICES: &[u16] = &[0, 1, 2, RESTART, 3, 4, 5];
+// Primitive restart only applies to strips, list topologies draw every vertex once.
+const LIST_INDICES: &[u16] = &[0, 1, 2, 3, 4, 5];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+struct Vertex {
+    position: [f32; 3],
+}
+
+// The points every topology is drawn from, hardware primitives, thick lines and point sprites alike.
+const VERTICES: &[Vertex] = &[
+    Vertex { position: [-0.9, 0.9, 0.0] },
+    Vertex { position: [-0.5, 0.1, 0.0] },
+    Vertex { position: [0.0, 0.5, 0.0] },
+    Vertex { position: [0.3, -0.3, 0.0] },
+    Vertex { position: [0.7, 0.6, 0.0] },
+    Vertex { position: [0.9, -0.7, 0.0] },
+];
+
+// The yellow the shader draws with.
+const LINE_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+// A color for each point sprite, in the order of VERTICES.
+const POINT_COLORS: [[f32; 4]; 6] = [
+    [1.0, 0.0, 0.0, 1.0],
+    [0.0, 1.0, 0.0, 1.0],
+    [0.0, 0.0, 1.0, 1.0],
+    [1.0, 1.0, 0.0, 1.0],
+    [1.0, 0.0, 1.0, 1.0],
+    [0.0, 1.0, 1.0, 1.0],
+];
+// Point sprite sizes in pixels: the first point is POINT_SIZE across, every next one a step bigger.
+const POINT_SIZE: f32 = 8.0;
+const POINT_SIZE_STEP: f32 = 2.0;
 
 pub struct State {
-    window: Arc<Window>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    size: winit::dpi::PhysicalSize<u32>,
-    surface: wgpu::Surface<'static>,
-    surface_format: wgpu::TextureFormat,
-    topology: wgpu::PrimitiveTopology,
+    context: GpuContext,
+    controls: TopologyControls,
+    // T swaps the line topologies' one pixel hardware lines for thick ones, J and C cycle their joins and caps.
+    thick_lines: bool,
+    line_renderer: LineRenderer,
+    lines: Lines,
+    // P swaps PointList's single pixels for point sprites, S cycles their shape.
+    point_sprites: bool,
+    point_shape: PointShape,
+    sprite_renderer: PointSpriteRenderer,
+    sprites: PointSprites,
+    vertex_buffer: wgpu::Buffer,
+    strip_index_buffer: wgpu::Buffer,
+    list_index_buffer: wgpu::Buffer,
+    strip_ranges: Vec<Range<u32>>,
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: RenderPipelineCache,
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>, selection: UserSelection) -> State {
-        let instance_descriptor = wgpu::InstanceDescriptor::default();
-        let instance = wgpu::Instance::new(&instance_descriptor);
-        let adapter_options = wgpu::RequestAdapterOptions::default();
-        let adapter = instance.request_adapter(&adapter_options).await.unwrap();
-        let device_descriptor = wgpu::DeviceDescriptor::default();
-        let (device, queue) = adapter.request_device(&device_descriptor).await.unwrap();
-        let size = window.inner_size();
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let capabilities = surface.get_capabilities(&adapter);
-        let surface_format = capabilities.formats[0];
-        let topology = match selection {
-                UserSelection::PointList => wgpu::PrimitiveTopology::PointList,
-                UserSelection::LineList => wgpu::PrimitiveTopology::LineList,
-                UserSelection::LineStrip => wgpu::PrimitiveTopology::LineStrip,
-                UserSelection::Help => unreachable!(),
-            };
+    pub async fn new(target: Target, topology: Topology, thick_lines: bool, point_sprites: bool) -> anyhow::Result<State> {
+        let context = GpuContext::builder()
+            .srgb_view_format(true)     // Request compatibility with the sRGB-format texture view we're going to create later.
+            .present_mode(wgpu::PresentMode::AutoVsync)
+            .alpha_mode(wgpu::CompositeAlphaMode::Auto)
+            .build(target)
+            .await?;
+        let controls = TopologyControls::new(topology);
+
+        let vertex_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(VERTICES),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let strip_index_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Strip Index Buffer"),
+            contents: bytemuck::cast_slice(STRIP_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let list_index_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("List Index Buffer"),
+            contents: bytemuck::cast_slice(LIST_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let strip_ranges = strip_ranges(STRIP_INDICES, INDEX_FORMAT, context.adapter.get_info().backend);
+
+        let shader = context.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        let pipeline_layout = context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
+
+        let line_renderer = LineRenderer::new(&context.device, context.view_format(), None, context.pipeline_cache())?;
+        let viewport = (context.config.width, context.config.height);
+        let style = LineStyle {
+            color: LINE_COLOR,
+            ..Default::default()
+        };
+        let lines = line_renderer.lines(&context.device, &line_segments(&controls), &style, viewport);
+
+        let sprite_renderer = PointSpriteRenderer::new(&context.device, context.view_format(), None, context.pipeline_cache())?;
+        let point_shape = PointShape::Circle;
+        let sprites = sprite_renderer.sprites(&context.device, &point_sprites_for(&controls, point_shape), SizeUnit::Pixels, viewport);
+
+        let pipelines = RenderPipelineCache::new().rebuild_always(context.rebuilds_pipelines());
 
         let state = State {
-            window,
-            device,
-            queue,
-            size,
-            surface,
-            surface_format,
-            topology,
+            context,
+            controls,
+            thick_lines,
+            line_renderer,
+            lines,
+            point_sprites,
+            point_shape,
+            sprite_renderer,
+            sprites,
+            vertex_buffer,
+            strip_index_buffer,
+            list_index_buffer,
+            strip_ranges,
+            shader,
+            pipeline_layout,
+            pipelines,
         };
+        state.show_title();
+        Ok(state)
+    }
+
+    fn draws_thick_lines(&self) -> bool {
+        self.thick_lines && matches!(self.controls.topology, Topology::LineList | Topology::LineStrip)
+    }
+
+    fn draws_point_sprites(&self) -> bool {
+        self.point_sprites && self.controls.topology == Topology::PointList
+    }
 
-        state.configure_surface();
+    fn line_input(&mut self, code: KeyCode) -> bool {
+        let mut style = *self.lines.style();
+        match code {
+            KeyCode::KeyT => self.thick_lines = !self.thick_lines,
+            KeyCode::KeyJ => style.join = style.join.next(),
+            KeyCode::KeyC => style.cap = style.cap.next(),
+            _ => return false,
+        }
+        self.lines.set_style(&self.context.queue, &style);
+        true
+    }
 
-        state
+    fn sprite_input(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::KeyP => self.point_sprites = !self.point_sprites,
+            KeyCode::KeyS => {
+                self.point_shape = self.point_shape.next();
+                self.sprites.set_sprites(&self.context.device, &point_sprites_for(&self.controls, self.point_shape));
+            },
+            _ => return false,
+        }
+        true
     }
 
-    pub fn get_window(&self) -> &Window {
-        &self.window
+    fn show_title(&self) {
+        let title = match self.controls.topology {
+            Topology::PointList if self.point_sprites => format!("{} - {:?} point sprites (P/S)", TITLE, self.point_shape),
+            Topology::PointList => format!("{} (P: point sprites)", TITLE),
+            _ if self.thick_lines => {
+                let style = self.lines.style();
+                format!("{} - {}px lines, {:?} joins, {:?} caps (T/J/C)", TITLE, style.width, style.join, style.cap)
+            },
+            _ => format!("{} (T: thick lines)", TITLE),
+        };
+        self.controls.show_in(self.context.window.as_ref(), &title);
     }
 
-    pub fn configure_surface(&self) {
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: self.surface_format,
-            view_formats: vec![ self.surface_format.add_srgb_suffix() ],    // Request compatibility with the sRGB-format texture view we're going to create later.
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            width: self.size.width,
-            height: self.size.height,
-            desired_maximum_frame_latency: 2,
-            present_mode: wgpu::PresentMode::AutoVsync,
+    /// The vertices as they are, with the current topology's pipeline.
+    fn draw_primitives(&mut self, renderpass: &mut wgpu::RenderPass<'_>) {
+        // The pipeline is only built the first time this topology/format combination is drawn. Like the line and sprite
+        // renderers it targets the view format the render pass attachment is created with.
+        let key = PipelineKey {
+            topology: self.controls.topology.primitive_topology(),
+            format: self.context.view_format(),
         };
+        let (device, cache, pipeline_layout, shader) =
+            (&self.context.device, self.context.pipeline_cache(), &self.pipeline_layout, &self.shader);
+        let pipeline = self.pipelines.get_or_create(key, |key| create_pipeline(device, cache, pipeline_layout, shader, key));
+
+        renderpass.set_pipeline(pipeline);
+        renderpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+
+        if self.controls.indexed && self.controls.topology.is_strip() {
+            // One range where primitive restart works, one per strip where it doesn't (GL).
+            renderpass.set_index_buffer(self.strip_index_buffer.slice(..), INDEX_FORMAT);
+            for range in &self.strip_ranges {
+                renderpass.draw_indexed(range.clone(), 0, 0..1);
+            }
+        } else if self.controls.indexed {
+            renderpass.set_index_buffer(self.list_index_buffer.slice(..), INDEX_FORMAT);
+            renderpass.draw_indexed(0..LIST_INDICES.len() as u32, 0, 0..1);
+        } else {
+            renderpass.draw(0..VERTICES.len() as u32, 0..1);
+        }
+    }
+}
 
-        self.surface.configure(&self.device, &surface_config);
+/// What the current topology and index mode draw, as thick line segments.
+fn line_segments(controls: &TopologyControls) -> Vec<LineSegment> {
+    let positions: Vec<[f32; 3]> = VERTICES.iter().map(|vertex| vertex.position).collect();
+    let topology = controls.topology;
+
+    match (controls.indexed, topology.is_strip()) {
+        (true, true) => LineSegment::from_indices(&positions, STRIP_INDICES, INDEX_FORMAT, topology),
+        (true, false) => LineSegment::from_indices(&positions, LIST_INDICES, INDEX_FORMAT, topology),
+        (false, _) => LineSegment::from_topology(&positions, topology),
     }
+}
+
+/// What PointList draws in the current index mode, as `shape` sprites that grow from one point to the next.
+fn point_sprites_for(controls: &TopologyControls, shape: PointShape) -> Vec<PointSprite> {
+    let positions: Vec<[f32; 3]> = VERTICES.iter().map(|vertex| vertex.position).collect();
+    let style = PointStyle {
+        size: POINT_SIZE,
+        shape,
+        ..Default::default()
+    };
 
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.size = new_size;
-        self.configure_surface();
+    // Each sprite's size and color follow the vertex it was made from.
+    let (mut sprites, vertex_indices): (Vec<PointSprite>, Vec<usize>) = if controls.indexed {
+        let indices = LIST_INDICES.iter().map(|&index| index as usize).collect();
+        (PointSprite::from_indices(&positions, LIST_INDICES, &style), indices)
+    } else {
+        (PointSprite::from_positions(&positions, &style), (0..positions.len()).collect())
+    };
+    for (sprite, index) in sprites.iter_mut().zip(vertex_indices) {
+        sprite.size += POINT_SIZE_STEP * index as f32;
+        sprite.color = POINT_COLORS[index];
     }
+    sprites
+}
 
-    pub fn render(&mut self) {
-        let surface_texture = self.surface.get_current_texture().expect("failed to acquire next swapchain texture");
+fn pressed_key(event: &WindowEvent) -> Option<KeyCode> {
+    match event {
+        WindowEvent::KeyboardInput {
+            event: KeyEvent {
+                physical_key: PhysicalKey::Code(code),
+                state: ElementState::Pressed,
+                repeat: false,
+                ..
+            },
+            ..
+        } => Some(*code),
+        _ => None,
+    }
+}
+
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target, Topology::PointList, false, false))
+    }
+
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        if let Some(code) = pressed_key(event) {
+            if self.line_input(code) || self.sprite_input(code) {
+                self.show_title();
+                return true;
+            }
+        }
+        if !self.controls.input(event) {
+            return false;
+        }
+
+        self.lines.set_segments(&self.context.device, &line_segments(&self.controls));
+        self.sprites.set_sprites(&self.context.device, &point_sprites_for(&self.controls, self.point_shape));
+        self.show_title();
+        true
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.context.resize(width, height);
+        self.lines.set_viewport(&self.context.queue, self.context.config.width, self.context.config.height);
+        self.sprites.set_viewport(&self.context.queue, self.context.config.width, self.context.config.height);
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let frame = self.context.acquire_frame()?;
 
         let texture_view_descriptor = wgpu::TextureViewDescriptor {
-            format: Some(self.surface_format.add_srgb_suffix()),
+            format: Some(self.context.view_format()),
             ..Default::default()
         };
 
-        let texture_view = surface_texture.texture.create_view(&texture_view_descriptor);
+        let texture_view = frame.texture().create_view(&texture_view_descriptor);
 
-        let mut encoder = self.device.create_command_encoder(&Default::default());
+        let mut encoder = self.context.device.create_command_encoder(&Default::default());
         
         let color_attachment_operations = wgpu::Operations {
             load: wgpu::LoadOp::Clear(wgpu::Color{ r: 0.5, g:0.5, b: 0.5, a: 1.0,}), // gray
@@ -103,54 +326,52 @@ impl State {
 
         let mut renderpass = encoder.begin_render_pass(&renderpass_descriptor);
 
-        let shader = self.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        if self.draws_thick_lines() {
+            self.line_renderer.draw(&mut renderpass, &self.lines);
+        } else if self.draws_point_sprites() {
+            self.sprite_renderer.draw(&mut renderpass, &self.sprites);
+        } else {
+            self.draw_primitives(&mut renderpass);
+        }
 
-        let pipeline_layout = self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
+        drop(renderpass);
 
-        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu::FragmentState{ 
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: self.surface_format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent::REPLACE,
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: self.topology,
-                ..Default::default()
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        self.context.queue.submit([encoder.finish()]);
+        self.context.pre_present_notify();
+        frame.present();
+        Ok(())
+    }
 
-        renderpass.set_pipeline(&pipeline);
-        
-        renderpass.draw(0..6, 0..1);
+    fn context(&self) -> Option<&GpuContext> {
+        Some(&self.context)
+    }
+}
 
-        drop(renderpass);
+/// The vertex buffers the pipeline feeds `vs_main`, checked against shader.wgsl by `check-layouts`.
+pub const SHADER_LAYOUTS: ShaderLayouts = ShaderLayouts {
+    path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+    source: include_str!("shader.wgsl"),
+    entry_point: "vs_main",
+    buffers: vertex_buffers,
+};
 
-        self.queue.submit([encoder.finish()]);
-        self.window.pre_present_notify();
-        surface_texture.present();
-    }
-}
\ No newline at end of file
+fn vertex_buffers() -> Vec<wgpu::VertexBufferLayout<'static>> {
+    vec![Vertex::desc()]
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    cache: Option<&wgpu::PipelineCache>,
+    pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    key: &PipelineKey,
+) -> wgpu::RenderPipeline {
+    RenderPipelineBuilder::new(shader)
+        .layout(pipeline_layout)
+        .vertex_buffers(vertex_buffers())
+        .color_target(key.format)
+        .primitive(Topology::from(key.topology).primitive_state(INDEX_FORMAT))
+        .cache(cache)
+        .build(device)
+        .expect("unable to create the render pipeline")
+}
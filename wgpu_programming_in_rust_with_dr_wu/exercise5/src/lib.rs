@@ -0,0 +1,18 @@
+mod state;
+
+pub use crate::state::{State, SHADER_LAYOUTS};
+
+use wgpu_common::{Exercise, Registry, Target, Topology};
+
+pub fn register(registry: &mut Registry) {
+    registry
+        .register_fn("triangle-list", "Dr Wu - Triangle List Primitive", |target| init_mode(target, Topology::TriangleList))
+        .with_shader_layouts(SHADER_LAYOUTS);
+    registry
+        .register_fn("triangle-strip", "Dr Wu - Triangle Strip Primitive", |target| init_mode(target, Topology::TriangleStrip))
+        .with_shader_layouts(SHADER_LAYOUTS);
+}
+
+fn init_mode(target: Target, topology: Topology) -> anyhow::Result<Box<dyn Exercise>> {
+    Ok(Box::new(pollster::block_on(State::new(target, topology))?))
+}
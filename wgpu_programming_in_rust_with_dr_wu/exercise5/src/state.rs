@@ -1,84 +1,102 @@
-use std::sync::Arc;
-use winit::window::Window;
-
-use crate::user_input::UserSelection;
+use std::ops::Range;
+
+use wgpu::util::DeviceExt;
+use winit::event::WindowEvent;
+use wgpu_common::{strip_ranges, Exercise, GpuContext, PipelineKey, RenderPipelineBuilder, RenderPipelineCache, ShaderLayouts, Target, Topology, TopologyControls};
+
+const TITLE: &str = "Dr Wu - Triangle Primitives";
+const VERTEX_COUNT: u32 = 9;
+const INDEX_FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint16;
+// With a strip_index_format set, the largest index value ends the current strip instead of naming a vertex.
+const RESTART: u16 = u16::MAX;
+// A strip of five vertices and one of the remaining five, split by the primitive restart value. Vertex 4 is shared.
+const STRIP_INDICES: &[u16] = &[0, 1, 2, 3, 4, RESTART, 4, 5, 6, 7, 8];
+// Primitive restart only applies to strips, list topologies draw every vertex once.
+const LIST_INDICES: &[u16] = &[0, 1, 2, 3, 4, 5, 6, 7, 8];
 
 pub struct State {
-    window: Arc<Window>,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    size:winit::dpi::PhysicalSize<u32>,
-    surface: wgpu::Surface<'static>,
-    surface_format: wgpu::TextureFormat,
-    topology: wgpu::PrimitiveTopology,
+    context: GpuContext,
+    controls: TopologyControls,
+    strip_index_buffer: wgpu::Buffer,
+    list_index_buffer: wgpu::Buffer,
+    strip_ranges: Vec<Range<u32>>,
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipelines: RenderPipelineCache,
 }
 
 impl State {
-    pub async fn new(window: Arc<Window>, selection: UserSelection) -> State {
-        let instance_descriptor = wgpu::InstanceDescriptor::default();
-        let instance = wgpu::Instance::new(&instance_descriptor);
-        let adapater_options = wgpu::RequestAdapterOptions::default();
-        let adapter = instance.request_adapter(&adapater_options).await.unwrap();
-        let device_descriptor = wgpu::DeviceDescriptor::default();
-        let (device, queue) = adapter.request_device(&device_descriptor).await.unwrap();
-        let size = window.inner_size();
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let capabilities = surface.get_capabilities(&adapter);
-        let surface_format = capabilities.formats[0];
-
-        let topology = match selection {
-            UserSelection::TriangleList => wgpu::PrimitiveTopology::TriangleList,
-            UserSelection::TriangleStrip => wgpu::PrimitiveTopology::TriangleStrip,
-            UserSelection::Help => unreachable!(),
-        };
-        
-        let state = State {
-            window,
-            device,
-            queue,
-            size,
-            surface,
-            surface_format,
-            topology,
-        };
+    pub async fn new(target: Target, topology: Topology) -> anyhow::Result<State> {
+        let context = GpuContext::builder()
+            .srgb_view_format(true)     // Request compatibility with the sRGB-format texture view we're going to create later.
+            .present_mode(wgpu::PresentMode::AutoVsync)
+            .alpha_mode(wgpu::CompositeAlphaMode::Auto)
+            .build(target)
+            .await?;
+
+        let controls = TopologyControls::new(topology);
+        controls.show_in(context.window.as_ref(), TITLE);
+
+        let strip_index_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Strip Index Buffer"),
+            contents: bytemuck::cast_slice(STRIP_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let list_index_buffer = context.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("List Index Buffer"),
+            contents: bytemuck::cast_slice(LIST_INDICES),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let strip_ranges = strip_ranges(STRIP_INDICES, INDEX_FORMAT, context.adapter.get_info().backend);
 
-        state.configure_surface();
+        let shader = context.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+        let pipeline_layout = context.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[],
+            push_constant_ranges: &[],
+        });
 
-        state
+        let pipelines = RenderPipelineCache::new().rebuild_always(context.rebuilds_pipelines());
+
+        Ok(State {
+            context,
+            controls,
+            strip_index_buffer,
+            list_index_buffer,
+            strip_ranges,
+            shader,
+            pipeline_layout,
+            pipelines,
+        })
     }
+}
 
-    pub fn get_window(&self) -> &Window {
-        &self.window
+impl Exercise for State {
+    fn init(target: Target) -> anyhow::Result<Self> {
+        pollster::block_on(Self::new(target, Topology::TriangleList))
     }
 
-    pub fn configure_surface(&self) {
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: self.surface_format,
-            view_formats: vec![ self.surface_format.add_srgb_suffix() ],
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            width: self.size.width,
-            height: self.size.height,
-            desired_maximum_frame_latency: 2,
-            present_mode: wgpu::PresentMode::AutoVsync,
-        };
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        if !self.controls.input(event) {
+            return false;
+        }
 
-        self.surface.configure(&self.device, &surface_config);
+        self.controls.show_in(self.context.window.as_ref(), TITLE);
+        true
     }
 
-    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        self.size = new_size;
-        self.configure_surface();
+    fn resize(&mut self, width: u32, height: u32) {
+        self.context.resize(width, height);
     }
 
-    pub fn render (&mut self) {
-        let surface_texture = self.surface.get_current_texture().expect("failed to acquire next swapchain texture");
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let frame = self.context.acquire_frame()?;
         let texture_view_descriptor = wgpu::TextureViewDescriptor {
-            format: Some(self.surface_format.add_srgb_suffix()),
+            format: Some(self.context.view_format()),
             ..Default::default()
         };
-        let texture_view = surface_texture.texture.create_view(&texture_view_descriptor);
-        let mut encoder = self.device.create_command_encoder(&Default::default());
+        let texture_view = frame.texture().create_view(&texture_view_descriptor);
+        let mut encoder = self.context.device.create_command_encoder(&Default::default());
         let color_attachment_operations = wgpu::Operations {
             load: wgpu::LoadOp::Clear(wgpu::Color{ r: 0.5, g: 0.5, b: 0.5, a: 1.0}),
             store: wgpu::StoreOp::Store,
@@ -99,52 +117,63 @@ impl State {
 
         let mut renderpass = encoder.begin_render_pass(&renderpass_descriptor);
 
-        let shader = self.device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
-        let pipeline_layout = self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: None,
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
-        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor{
-            label: None,
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: Default::default(),
-            },
-            fragment: Some(wgpu:: FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: self.surface_format,
-                    blend: Some(wgpu::BlendState {
-                        color: wgpu::BlendComponent::REPLACE,
-                        alpha: wgpu::BlendComponent::REPLACE,
-                    }),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: self.topology,
-                ..Default::default()
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
-
-        renderpass.set_pipeline(&pipeline);
-
-        renderpass.draw(0..9, 0..1);
+        // The pipeline is only built the first time this topology/format combination is drawn.
+        let key = PipelineKey {
+            topology: self.controls.topology.primitive_topology(),
+            format: self.context.config.format,
+        };
+        let (device, cache, pipeline_layout, shader) =
+            (&self.context.device, self.context.pipeline_cache(), &self.pipeline_layout, &self.shader);
+        let pipeline = self.pipelines.get_or_create(key, |key| create_pipeline(device, cache, pipeline_layout, shader, key));
+
+        renderpass.set_pipeline(pipeline);
+
+        if self.controls.indexed && self.controls.topology.is_strip() {
+            // One range where primitive restart works, one per strip where it doesn't (GL).
+            renderpass.set_index_buffer(self.strip_index_buffer.slice(..), INDEX_FORMAT);
+            for range in &self.strip_ranges {
+                renderpass.draw_indexed(range.clone(), 0, 0..1);
+            }
+        } else if self.controls.indexed {
+            renderpass.set_index_buffer(self.list_index_buffer.slice(..), INDEX_FORMAT);
+            renderpass.draw_indexed(0..LIST_INDICES.len() as u32, 0, 0..1);
+        } else {
+            renderpass.draw(0..VERTEX_COUNT, 0..1);
+        }
 
         drop(renderpass);
 
-        self.queue.submit([encoder.finish()]);
-        self.window.pre_present_notify();
-        surface_texture.present();
+        self.context.queue.submit([encoder.finish()]);
+        self.context.pre_present_notify();
+        frame.present();
+        Ok(())
+    }
+
+    fn context(&self) -> Option<&GpuContext> {
+        Some(&self.context)
     }
-}
\ No newline at end of file
+}
+
+/// The vertex buffers the pipeline feeds `vs_main`, checked against shader.wgsl by `check-layouts`.
+pub const SHADER_LAYOUTS: ShaderLayouts = ShaderLayouts {
+    path: concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader.wgsl"),
+    source: include_str!("shader.wgsl"),
+    entry_point: "vs_main",
+    buffers: Vec::new,
+};
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    cache: Option<&wgpu::PipelineCache>,
+    pipeline_layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    key: &PipelineKey,
+) -> wgpu::RenderPipeline {
+    RenderPipelineBuilder::new(shader)
+        .layout(pipeline_layout)
+        .color_target(key.format)
+        .primitive(Topology::from(key.topology).primitive_state(INDEX_FORMAT))
+        .cache(cache)
+        .build(device)
+        .expect("unable to create the render pipeline")
+}